@@ -0,0 +1,13 @@
+-- 同步历史与健康度
+-- Migration: 006_add_sync_history
+
+-- 记录请求体大小，用于统计平均负载
+ALTER TABLE sync_logs ADD COLUMN IF NOT EXISTS payload_bytes INTEGER;
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_sync_logs_status ON sync_logs(status);
+CREATE INDEX IF NOT EXISTS idx_sync_logs_client_created ON sync_logs(client_id, created_at DESC);
+
+-- 注释
+COMMENT ON COLUMN sync_logs.error_message IS '失败或部分同步的错误信息';
+COMMENT ON COLUMN sync_logs.payload_bytes IS '同步请求体大小（字节）';
@@ -0,0 +1,25 @@
+-- 同步请求幂等性
+-- Migration: 007_add_sync_idempotency
+
+-- 已处理的同步请求（按幂等键缓存结果）
+CREATE TABLE IF NOT EXISTS sync_requests (
+    id SERIAL PRIMARY KEY,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    response JSONB NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(client_id, idempotency_key)
+);
+
+-- 幂等键保留时长（小时）
+INSERT INTO settings (key, value) VALUES
+    ('sync_idempotency_retention_hours', '24')
+ON CONFLICT (key) DO NOTHING;
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_sync_requests_created_at ON sync_requests(created_at);
+
+-- 注释
+COMMENT ON TABLE sync_requests IS '同步请求幂等记录';
+COMMENT ON COLUMN sync_requests.idempotency_key IS '客户端提供的幂等键（Idempotency-Key）';
+COMMENT ON COLUMN sync_requests.response IS '首次处理时返回的 SyncResponse';
@@ -0,0 +1,23 @@
+-- 完整课程模型：地点、备注、教室与扩展属性
+-- Migration: 008_add_course_attributes
+
+-- 课程表
+ALTER TABLE courses ADD COLUMN IF NOT EXISTS location VARCHAR(255);
+ALTER TABLE courses ADD COLUMN IF NOT EXISTS attributes JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+-- 课程表条目
+ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS note TEXT;
+ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS room VARCHAR(255);
+ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS attributes JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_courses_location ON courses(location);
+CREATE INDEX IF NOT EXISTS idx_courses_attributes ON courses USING GIN (attributes);
+CREATE INDEX IF NOT EXISTS idx_schedule_entries_attributes ON schedule_entries USING GIN (attributes);
+
+-- 注释
+COMMENT ON COLUMN courses.location IS '上课地点';
+COMMENT ON COLUMN courses.attributes IS '扩展属性（学分、课程代码、班级等）';
+COMMENT ON COLUMN schedule_entries.note IS '条目备注';
+COMMENT ON COLUMN schedule_entries.room IS '条目教室，覆盖课程地点';
+COMMENT ON COLUMN schedule_entries.attributes IS '条目扩展属性';
@@ -0,0 +1,22 @@
+-- 课程表快照
+-- Migration: 009_add_timetable_snapshots
+
+-- 每次同步或回滚后的课程表版本（客户端同步格式）
+CREATE TABLE IF NOT EXISTS timetable_snapshots (
+    id SERIAL PRIMARY KEY,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    version INTEGER NOT NULL,
+    source VARCHAR(50) NOT NULL DEFAULT 'sync',  -- sync, rollback
+    courses JSONB NOT NULL DEFAULT '[]'::jsonb,
+    schedule_entries JSONB NOT NULL DEFAULT '[]'::jsonb,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(client_id, version)
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_timetable_snapshots_client ON timetable_snapshots(client_id, version DESC);
+
+-- 注释
+COMMENT ON TABLE timetable_snapshots IS '客户端课程表版本快照';
+COMMENT ON COLUMN timetable_snapshots.version IS '客户端内递增的版本号';
+COMMENT ON COLUMN timetable_snapshots.source IS '快照来源：sync, rollback';
@@ -0,0 +1,41 @@
+-- 课程表周次改为整数数组
+-- Migration: 010_typed_schedule_weeks
+
+-- 无法转换的原始周次文本，保留待服务器启动时按周次表达式解析或人工处理
+ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS weeks_legacy TEXT;
+
+-- 将 JSON 文本（如 "[1,2,3]"）转换为 INTEGER[]，已转换时跳过
+DO $$
+DECLARE
+    unparsed INTEGER;
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'schedule_entries' AND column_name = 'weeks' AND data_type = 'text'
+    ) THEN
+        ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS weeks_array INTEGER[];
+
+        UPDATE schedule_entries
+        SET weeks_array = ARRAY(SELECT jsonb_array_elements_text(weeks::jsonb)::INTEGER)
+        WHERE weeks ~ '^\s*\[[0-9,\s]*\]\s*$';
+
+        -- 其余非空文本不能当作“每周”丢弃
+        UPDATE schedule_entries
+        SET weeks_legacy = weeks
+        WHERE weeks_array IS NULL AND weeks IS NOT NULL AND btrim(weeks) NOT IN ('', 'null');
+        GET DIAGNOSTICS unparsed = ROW_COUNT;
+        IF unparsed > 0 THEN
+            RAISE NOTICE '% schedule entries have weeks that could not be converted; kept in weeks_legacy', unparsed;
+        END IF;
+
+        ALTER TABLE schedule_entries DROP COLUMN weeks;
+        ALTER TABLE schedule_entries RENAME COLUMN weeks_array TO weeks;
+    END IF;
+END $$;
+
+-- 索引（支持 weeks @> ARRAY[n] 查询）
+CREATE INDEX IF NOT EXISTS idx_schedule_entries_weeks ON schedule_entries USING GIN (weeks);
+
+-- 注释
+COMMENT ON COLUMN schedule_entries.weeks IS '上课周次，NULL 表示每周';
+COMMENT ON COLUMN schedule_entries.weeks_legacy IS '迁移时无法转换的原始周次文本，转换成功后清空';
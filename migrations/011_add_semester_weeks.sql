@@ -0,0 +1,7 @@
+-- 学期周数
+-- Migration: 011_add_semester_weeks
+
+-- 同步的周次不能超过学期周数
+INSERT INTO settings (key, value) VALUES
+    ('semester_weeks', '20')
+ON CONFLICT (key) DO NOTHING;
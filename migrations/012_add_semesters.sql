@@ -0,0 +1,25 @@
+-- 学期管理
+-- Migration: 012_add_semesters
+
+-- 学期表
+CREATE TABLE IF NOT EXISTS semesters (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    week_count INTEGER NOT NULL,
+    is_active BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK (end_date >= start_date),
+    CHECK (week_count > 0)
+);
+
+-- 同一时间只有一个当前学期
+CREATE UNIQUE INDEX IF NOT EXISTS idx_semesters_active ON semesters(is_active) WHERE is_active;
+CREATE INDEX IF NOT EXISTS idx_semesters_start_date ON semesters(start_date);
+
+-- 注释
+COMMENT ON TABLE semesters IS '学期';
+COMMENT ON COLUMN semesters.start_date IS '学期开始日期，所在周为第 1 周';
+COMMENT ON COLUMN semesters.week_count IS '教学周数';
+COMMENT ON COLUMN semesters.is_active IS '是否为当前学期';
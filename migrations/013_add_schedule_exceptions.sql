@@ -0,0 +1,30 @@
+-- 课表例外日历
+-- Migration: 013_add_schedule_exceptions
+
+-- 节假日、考试周停课，调课日，以及单节课的取消与调整
+CREATE TABLE IF NOT EXISTS schedule_exceptions (
+    id SERIAL PRIMARY KEY,
+    client_id INTEGER REFERENCES clients(id) ON DELETE CASCADE,  -- NULL 表示所有客户端
+    date DATE NOT NULL,
+    kind VARCHAR(30) NOT NULL,  -- cancel_day, remap_day, cancel_entry, relocate_entry
+    follows_day_of_week INTEGER,
+    entry_id INTEGER REFERENCES schedule_entries(id) ON DELETE CASCADE,
+    new_date DATE,
+    new_start_time VARCHAR(10),
+    new_end_time VARCHAR(10),
+    new_room VARCHAR(255),
+    reason TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK (kind IN ('cancel_day', 'remap_day', 'cancel_entry', 'relocate_entry'))
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_schedule_exceptions_date ON schedule_exceptions(date);
+CREATE INDEX IF NOT EXISTS idx_schedule_exceptions_new_date ON schedule_exceptions(new_date);
+CREATE INDEX IF NOT EXISTS idx_schedule_exceptions_client ON schedule_exceptions(client_id);
+
+-- 注释
+COMMENT ON TABLE schedule_exceptions IS '课表例外（节假日、调课、停课）';
+COMMENT ON COLUMN schedule_exceptions.follows_day_of_week IS 'remap_day：当天按该星期的课表上课';
+COMMENT ON COLUMN schedule_exceptions.entry_id IS 'cancel_entry / relocate_entry 针对的课表条目';
+COMMENT ON COLUMN schedule_exceptions.new_date IS 'relocate_entry：调到的新日期';
@@ -0,0 +1,23 @@
+-- 日历订阅
+-- Migration: 014_add_calendar_feeds
+
+-- 按客户端、教师或教室订阅的 iCalendar 课表，令牌即凭据
+CREATE TABLE IF NOT EXISTS calendar_feeds (
+    id SERIAL PRIMARY KEY,
+    token VARCHAR(64) UNIQUE NOT NULL,
+    scope VARCHAR(20) NOT NULL,  -- client, teacher, location
+    target VARCHAR(255) NOT NULL,
+    name VARCHAR(255),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    last_accessed_at TIMESTAMP,
+    CHECK (scope IN ('client', 'teacher', 'location'))
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_calendar_feeds_scope ON calendar_feeds(scope, target);
+
+-- 注释
+COMMENT ON TABLE calendar_feeds IS 'iCalendar 课表订阅';
+COMMENT ON COLUMN calendar_feeds.token IS '订阅地址中的令牌，无需登录即可访问';
+COMMENT ON COLUMN calendar_feeds.target IS 'client：客户端 ID；teacher：教师姓名；location：教室';
+COMMENT ON COLUMN calendar_feeds.last_accessed_at IS '日历应用最近一次拉取的时间';
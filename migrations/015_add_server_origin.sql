@@ -0,0 +1,10 @@
+-- 服务器端维护的课程与课表条目
+-- Migration: 015_add_server_origin
+
+-- 来源：client 为客户端同步，server 为管理端创建或修改
+ALTER TABLE courses ADD COLUMN IF NOT EXISTS origin VARCHAR(20) NOT NULL DEFAULT 'client';
+ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS origin VARCHAR(20) NOT NULL DEFAULT 'client';
+
+-- 注释
+COMMENT ON COLUMN courses.origin IS '来源：client（客户端同步）或 server（管理端维护，客户端同步不会覆盖）';
+COMMENT ON COLUMN schedule_entries.origin IS '来源：client（客户端同步）或 server（管理端维护，客户端同步不会覆盖）';
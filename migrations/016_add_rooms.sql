@@ -0,0 +1,24 @@
+-- 教室
+-- Migration: 016_add_rooms
+
+CREATE TABLE IF NOT EXISTS rooms (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) UNIQUE NOT NULL,
+    building VARCHAR(255),
+    floor INTEGER,
+    capacity INTEGER CHECK (capacity IS NULL OR capacity >= 0),
+    equipment JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 客户端（班牌）安装所在的教室
+ALTER TABLE clients ADD COLUMN IF NOT EXISTS room_id INTEGER REFERENCES rooms(id) ON DELETE SET NULL;
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_clients_room ON clients(room_id);
+
+-- 注释
+COMMENT ON TABLE rooms IS '教室登记，课表条目按名称（条目教室优先于课程地点）关联，未填写地点时使用客户端所在教室';
+COMMENT ON COLUMN rooms.name IS '与课程地点、条目教室中填写的名称一致';
+COMMENT ON COLUMN rooms.equipment IS '设备列表，如投影仪、电子白板';
+COMMENT ON COLUMN clients.room_id IS '客户端所在的教室';
@@ -0,0 +1,43 @@
+-- 课表模板
+-- Migration: 017_add_timetable_templates
+
+-- 模板中的课程与条目使用同步数据格式，ID 仅在模板内有效
+CREATE TABLE IF NOT EXISTS timetable_templates (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(255) UNIQUE NOT NULL,
+    description TEXT,
+    courses JSONB NOT NULL DEFAULT '[]',
+    schedule_entries JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 使用模板的客户端及其覆盖项
+CREATE TABLE IF NOT EXISTS template_clients (
+    template_id INTEGER NOT NULL REFERENCES timetable_templates(id) ON DELETE CASCADE,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    overrides JSONB NOT NULL DEFAULT '{}',
+    published_at TIMESTAMP,
+    PRIMARY KEY (template_id, client_id)
+);
+
+-- 模板条目与客户端上条目的对应关系，发布时据此更新或删除
+CREATE TABLE IF NOT EXISTS template_items (
+    template_id INTEGER NOT NULL,
+    client_id INTEGER NOT NULL,
+    item_type VARCHAR(20) NOT NULL,  -- course, schedule_entry
+    template_item_id INTEGER NOT NULL,
+    client_item_id INTEGER NOT NULL,
+    PRIMARY KEY (template_id, client_id, item_type, template_item_id),
+    FOREIGN KEY (template_id, client_id)
+        REFERENCES template_clients(template_id, client_id) ON DELETE CASCADE,
+    CHECK (item_type IN ('course', 'schedule_entry'))
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_template_clients_client ON template_clients(client_id);
+
+-- 注释
+COMMENT ON TABLE timetable_templates IS '可应用到多个客户端的课表模板';
+COMMENT ON COLUMN template_clients.overrides IS '该客户端对模板课程、条目的覆盖与排除';
+COMMENT ON COLUMN template_items.client_item_id IS '客户端上的课程或条目 ID（course_id_on_client / entry_id_on_client）';
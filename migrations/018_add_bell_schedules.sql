@@ -0,0 +1,33 @@
+-- 作息时间表（节次）
+-- Migration: 018_add_bell_schedules
+
+-- 每套作息时间表列出各节次的起止时间，如常规日、短课日
+CREATE TABLE IF NOT EXISTS bell_schedules (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) UNIQUE NOT NULL,
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    periods JSONB NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 至多一套默认作息时间表
+CREATE UNIQUE INDEX IF NOT EXISTS idx_bell_schedules_default ON bell_schedules(is_default) WHERE is_default;
+
+-- 按节次安排的课表条目，起止时间由默认作息时间表解析
+ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS period_start INTEGER;
+ALTER TABLE schedule_entries ADD COLUMN IF NOT EXISTS period_end INTEGER;
+
+-- 新的例外类型：当天改用另一套作息时间表
+ALTER TABLE schedule_exceptions ADD COLUMN IF NOT EXISTS bell_schedule_id INTEGER REFERENCES bell_schedules(id) ON DELETE CASCADE;
+ALTER TABLE schedule_exceptions DROP CONSTRAINT IF EXISTS schedule_exceptions_kind_check;
+ALTER TABLE schedule_exceptions ADD CONSTRAINT schedule_exceptions_kind_check
+    CHECK (kind IN ('cancel_day', 'remap_day', 'cancel_entry', 'relocate_entry', 'bell_schedule'));
+
+-- 注释
+COMMENT ON TABLE bell_schedules IS '作息时间表，列出各节次的起止时间';
+COMMENT ON COLUMN bell_schedules.is_default IS '默认作息时间表，按节次安排的条目按它解析时间';
+COMMENT ON COLUMN bell_schedules.periods IS '节次列表：[{"period": 1, "start_time": "08:00", "end_time": "08:45"}]';
+COMMENT ON COLUMN schedule_entries.period_start IS '起始节次，为空表示直接给出时间';
+COMMENT ON COLUMN schedule_entries.period_end IS '结束节次（含）';
+COMMENT ON COLUMN schedule_exceptions.bell_schedule_id IS 'bell_schedule：当天采用的作息时间表';
@@ -0,0 +1,50 @@
+-- 客户端分组与公告
+-- Migration: 019_add_announcements
+
+-- 客户端分组，公告等按组投放
+CREATE TABLE IF NOT EXISTS client_groups (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(100) UNIQUE NOT NULL,
+    description TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS client_group_members (
+    group_id INTEGER NOT NULL REFERENCES client_groups(id) ON DELETE CASCADE,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    PRIMARY KEY (group_id, client_id)
+);
+
+-- 公告，client_ids 与 group_ids 均为空时投放到所有客户端
+CREATE TABLE IF NOT EXISTS announcements (
+    id SERIAL PRIMARY KEY,
+    title VARCHAR(200) NOT NULL,
+    body TEXT NOT NULL DEFAULT '',
+    priority VARCHAR(20) NOT NULL DEFAULT 'normal',
+    starts_at TIMESTAMP NOT NULL,
+    ends_at TIMESTAMP,
+    client_ids INTEGER[] NOT NULL DEFAULT '{}',
+    group_ids INTEGER[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK (priority IN ('low', 'normal', 'high')),
+    CHECK (ends_at IS NULL OR ends_at > starts_at)
+);
+
+-- 每个客户端的送达与显示确认
+CREATE TABLE IF NOT EXISTS announcement_deliveries (
+    announcement_id INTEGER NOT NULL REFERENCES announcements(id) ON DELETE CASCADE,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    delivered_at TIMESTAMP,
+    acknowledged_at TIMESTAMP,
+    PRIMARY KEY (announcement_id, client_id)
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_client_group_members_client ON client_group_members(client_id);
+CREATE INDEX IF NOT EXISTS idx_announcements_window ON announcements(starts_at, ends_at);
+
+-- 注释
+COMMENT ON TABLE client_groups IS '客户端分组，如年级或楼栋';
+COMMENT ON COLUMN announcements.starts_at IS '开始显示的时间（学校当地时间）';
+COMMENT ON COLUMN announcement_deliveries.acknowledged_at IS '显示屏确认已显示的时间';
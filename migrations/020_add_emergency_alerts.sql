@@ -0,0 +1,32 @@
+-- 紧急广播
+-- Migration: 020_add_emergency_alerts
+
+-- 紧急广播，client_ids 与 group_ids 均为空时发往所有客户端；cleared_at 为解除时间
+CREATE TABLE IF NOT EXISTS emergency_alerts (
+    id SERIAL PRIMARY KEY,
+    kind VARCHAR(20) NOT NULL,
+    message TEXT NOT NULL,
+    drill BOOLEAN NOT NULL DEFAULT FALSE,
+    client_ids INTEGER[] NOT NULL DEFAULT '{}',
+    group_ids INTEGER[] NOT NULL DEFAULT '{}',
+    started_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    cleared_at TIMESTAMP,
+    CHECK (kind IN ('fire', 'lockdown', 'evacuation', 'shelter', 'other'))
+);
+
+-- 每个客户端的发送次数与确认
+CREATE TABLE IF NOT EXISTS emergency_deliveries (
+    alert_id INTEGER NOT NULL REFERENCES emergency_alerts(id) ON DELETE CASCADE,
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_sent_at TIMESTAMP,
+    acknowledged_at TIMESTAMP,
+    PRIMARY KEY (alert_id, client_id)
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_emergency_alerts_active ON emergency_alerts(id) WHERE cleared_at IS NULL;
+
+-- 注释
+COMMENT ON TABLE emergency_alerts IS '火警、封校等紧急广播，未确认的客户端会重复收到';
+COMMENT ON COLUMN emergency_deliveries.attempts IS '已发送的次数，确认前每隔几秒重发';
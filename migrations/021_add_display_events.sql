@@ -0,0 +1,24 @@
+-- 显示屏倒计时
+-- Migration: 021_add_display_events
+
+-- 考试、运动会、学期结束等倒计时，client_ids 与 group_ids 均为空时显示在所有客户端
+CREATE TABLE IF NOT EXISTS display_events (
+    id SERIAL PRIMARY KEY,
+    label VARCHAR(100) NOT NULL,
+    target_date DATE NOT NULL,
+    style VARCHAR(20) NOT NULL DEFAULT 'default',
+    show_from DATE,
+    client_ids INTEGER[] NOT NULL DEFAULT '{}',
+    group_ids INTEGER[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    CHECK (style IN ('default', 'exam', 'sport', 'holiday', 'celebration')),
+    CHECK (show_from IS NULL OR show_from <= target_date)
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_display_events_target ON display_events(target_date);
+
+-- 注释
+COMMENT ON COLUMN display_events.style IS '显示样式，由客户端决定配色与图标';
+COMMENT ON COLUMN display_events.show_from IS '开始显示的日期，为空时创建后立即显示；目标日期之后不再显示';
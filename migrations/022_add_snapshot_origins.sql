@@ -0,0 +1,10 @@
+-- 快照记录服务器端维护的条目
+-- Migration: 022_add_snapshot_origins
+
+-- 快照时 origin 为 server 的课程与课表条目（客户端 ID）；NULL 表示旧快照未记录
+ALTER TABLE timetable_snapshots ADD COLUMN IF NOT EXISTS server_course_ids INTEGER[];
+ALTER TABLE timetable_snapshots ADD COLUMN IF NOT EXISTS server_entry_ids INTEGER[];
+
+-- 注释
+COMMENT ON COLUMN timetable_snapshots.server_course_ids IS '快照时由服务器维护的课程（客户端课程 ID），回滚时据此恢复 origin';
+COMMENT ON COLUMN timetable_snapshots.server_entry_ids IS '快照时由服务器维护的课表条目（客户端条目 ID），回滚时据此恢复 origin';
@@ -0,0 +1,8 @@
+-- 幂等键绑定请求内容
+-- Migration: 023_add_sync_request_hash
+
+-- 首次请求内容的 SHA-256；同一幂等键携带不同内容时拒绝重放，NULL 表示旧记录未保存
+ALTER TABLE sync_requests ADD COLUMN IF NOT EXISTS request_hash VARCHAR(64);
+
+-- 注释
+COMMENT ON COLUMN sync_requests.request_hash IS '首次请求内容的 SHA-256（十六进制）';
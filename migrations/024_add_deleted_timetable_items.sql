@@ -0,0 +1,16 @@
+-- 管理端删除的课程与课表条目
+-- Migration: 024_add_deleted_timetable_items
+
+-- 客户端同步时剔除这些条目，避免过期的客户端数据把已删除的条目加回来
+CREATE TABLE IF NOT EXISTS deleted_timetable_items (
+    client_id INTEGER NOT NULL REFERENCES clients(id) ON DELETE CASCADE,
+    item_type VARCHAR(20) NOT NULL,  -- course, schedule_entry
+    item_id INTEGER NOT NULL,
+    deleted_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (client_id, item_type, item_id),
+    CHECK (item_type IN ('course', 'schedule_entry'))
+);
+
+-- 注释
+COMMENT ON TABLE deleted_timetable_items IS '管理端删除的课程与课表条目，同 ID 的条目再次出现在服务器上之前拒绝客户端同步加回';
+COMMENT ON COLUMN deleted_timetable_items.item_id IS '客户端上的课程或条目 ID（course_id_on_client / entry_id_on_client）';
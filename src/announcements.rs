@@ -0,0 +1,146 @@
+use crate::models::{Announcement, ClientGroup};
+use chrono::NaiveDateTime;
+
+/// 公告优先级
+pub const PRIORITY_LOW: &str = "low";
+pub const PRIORITY_NORMAL: &str = "normal";
+pub const PRIORITY_HIGH: &str = "high"; // 显示屏应置顶或全屏显示
+
+pub const PRIORITIES: &[&str] = &[PRIORITY_LOW, PRIORITY_NORMAL, PRIORITY_HIGH];
+
+pub const MAX_TITLE_LEN: usize = 200;
+
+/// 检查待投放公告的间隔；新连接的客户端最迟在下一次检查时收到
+pub const DELIVERY_INTERVAL_SECS: u64 = 15;
+
+/// 推送给显示屏的命令
+pub const COMMAND_SHOW: &str = "show_announcement";
+pub const COMMAND_WITHDRAW: &str = "withdraw_announcement";
+
+/// 校验公告字段，返回 `字段: 原因` 形式的错误
+pub fn validate_announcement(
+    title: &str,
+    priority: &str,
+    starts_at: NaiveDateTime,
+    ends_at: Option<NaiveDateTime>,
+) -> Result<(), String> {
+    if title.trim().is_empty() {
+        return Err("title: must not be empty".to_string());
+    }
+    if title.chars().count() > MAX_TITLE_LEN {
+        return Err(format!(
+            "title: must be at most {} characters",
+            MAX_TITLE_LEN
+        ));
+    }
+    if !PRIORITIES.contains(&priority) {
+        return Err(format!(
+            "priority: must be one of {}",
+            PRIORITIES.join(", ")
+        ));
+    }
+    if ends_at.is_some_and(|end| end <= starts_at) {
+        return Err("ends_at: must be after starts_at".to_string());
+    }
+    Ok(())
+}
+
+/// 公告在 `now` 时是否处于显示时段
+pub fn is_active(announcement: &Announcement, now: NaiveDateTime) -> bool {
+    announcement.starts_at <= now && announcement.ends_at.is_none_or(|end| now < end)
+}
+
+/// 按客户端与分组指定的范围是否包含该客户端；两者均为空时包含所有客户端
+pub fn in_scope(
+    client_ids: &[i32],
+    group_ids: &[i32],
+    client_id: i32,
+    groups: &[ClientGroup],
+) -> bool {
+    if client_ids.is_empty() && group_ids.is_empty() {
+        return true;
+    }
+    client_ids.contains(&client_id)
+        || groups
+            .iter()
+            .any(|group| group_ids.contains(&group.id) && group.client_ids.contains(&client_id))
+}
+
+/// 公告是否投放到该客户端
+pub fn targets(announcement: &Announcement, client_id: i32, groups: &[ClientGroup]) -> bool {
+    in_scope(
+        &announcement.client_ids,
+        &announcement.group_ids,
+        client_id,
+        groups,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 10, 28)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn announcement(client_ids: Vec<i32>, group_ids: Vec<i32>) -> Announcement {
+        Announcement {
+            id: 1,
+            title: "Assembly".to_string(),
+            body: String::new(),
+            priority: PRIORITY_NORMAL.to_string(),
+            starts_at: at(8),
+            ends_at: Some(at(10)),
+            client_ids,
+            group_ids,
+            created_at: at(7),
+            updated_at: at(7),
+        }
+    }
+
+    #[test]
+    fn test_validate_announcement() {
+        assert!(validate_announcement("Exam", PRIORITY_HIGH, at(8), None).is_ok());
+        assert!(validate_announcement(" ", PRIORITY_HIGH, at(8), None).is_err());
+        assert_eq!(
+            validate_announcement("Exam", "urgent", at(8), None),
+            Err("priority: must be one of low, normal, high".to_string())
+        );
+        assert_eq!(
+            validate_announcement("Exam", PRIORITY_LOW, at(8), Some(at(8))),
+            Err("ends_at: must be after starts_at".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_active() {
+        let mut notice = announcement(Vec::new(), Vec::new());
+        assert!(!is_active(&notice, at(7)));
+        assert!(is_active(&notice, at(8)));
+        assert!(!is_active(&notice, at(10)));
+        notice.ends_at = None;
+        assert!(is_active(&notice, at(23)));
+    }
+
+    #[test]
+    fn test_targets() {
+        let groups = vec![ClientGroup {
+            id: 5,
+            name: "Grade 7".to_string(),
+            description: None,
+            client_ids: vec![2, 3],
+            created_at: at(7),
+        }];
+
+        assert!(targets(&announcement(Vec::new(), Vec::new()), 9, &groups));
+        let scoped = announcement(vec![1], vec![5]);
+        assert!(targets(&scoped, 1, &groups));
+        assert!(targets(&scoped, 3, &groups));
+        assert!(!targets(&scoped, 4, &groups));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::models::{BellPeriod, ScheduleEntry};
+use crate::validation::parse_hhmm;
+
+/// 作息时间表名称的最大长度
+pub const MAX_BELL_NAME_LEN: usize = 100;
+
+/// 校验作息时间表：节次为正且不重复，按节次顺序时间依次排开、互不重叠
+pub fn validate_bell_schedule(name: &str, periods: &[BellPeriod]) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("name: must not be empty".to_string());
+    }
+    if name.chars().count() > MAX_BELL_NAME_LEN {
+        return Err(format!(
+            "name: must be at most {} characters",
+            MAX_BELL_NAME_LEN
+        ));
+    }
+    if periods.is_empty() {
+        return Err("periods: at least one period is required".to_string());
+    }
+
+    let mut sorted: Vec<&BellPeriod> = periods.iter().collect();
+    sorted.sort_by_key(|p| p.period);
+    let mut previous: Option<(i32, u32)> = None;
+    for period in sorted {
+        if period.period < 1 {
+            return Err("periods: period numbers must be positive".to_string());
+        }
+        let (Some(start), Some(end)) =
+            (parse_hhmm(&period.start_time), parse_hhmm(&period.end_time))
+        else {
+            return Err(format!(
+                "periods: period {} times must be HH:MM",
+                period.period
+            ));
+        };
+        if start >= end {
+            return Err(format!(
+                "periods: period {} must end after it starts",
+                period.period
+            ));
+        }
+        match previous {
+            Some((number, _)) if number == period.period => {
+                return Err(format!("periods: period {} is listed twice", period.period));
+            }
+            Some((number, previous_end)) if start < previous_end => {
+                return Err(format!(
+                    "periods: period {} starts before period {} ends",
+                    period.period, number
+                ));
+            }
+            _ => {}
+        }
+        previous = Some((period.period, end));
+    }
+    Ok(())
+}
+
+/// 节次范围对应的起止时间：首节开始至末节结束
+pub fn period_times(
+    periods: &[BellPeriod],
+    period_start: i32,
+    period_end: i32,
+) -> Result<(String, String), String> {
+    if period_end < period_start {
+        return Err("period_end: must not be before period_start".to_string());
+    }
+    let find = |number: i32, field: &str| {
+        periods
+            .iter()
+            .find(|p| p.period == number)
+            .ok_or_else(|| format!("{}: period {} is not in the bell schedule", field, number))
+    };
+    let first = find(period_start, "period_start")?;
+    let last = find(period_end, "period_end")?;
+    Ok((first.start_time.clone(), last.end_time.clone()))
+}
+
+/// 条目的起止时间：给出节次时按作息时间表（`periods`）解析，否则使用给出的时间
+///
+/// 节次与时间不能同时给出；`period_end` 省略时与 `period_start` 相同。
+pub fn entry_times(
+    periods: Option<&[BellPeriod]>,
+    period_start: Option<i32>,
+    period_end: Option<i32>,
+    start_time: Option<String>,
+    end_time: Option<String>,
+) -> Result<(String, String), String> {
+    match period_start {
+        None if period_end.is_some() => {
+            Err("period_start: required when period_end is given".to_string())
+        }
+        None => match (start_time, end_time) {
+            (Some(start), Some(end)) => Ok((start, end)),
+            _ => {
+                Err("start_time: start_time and end_time are required without periods".to_string())
+            }
+        },
+        Some(_) if start_time.is_some() || end_time.is_some() => {
+            Err("period_start: give either periods or start_time/end_time, not both".to_string())
+        }
+        Some(start) => {
+            let periods = periods
+                .ok_or_else(|| "period_start: no default bell schedule is set".to_string())?;
+            period_times(periods, start, period_end.unwrap_or(start))
+        }
+    }
+}
+
+/// 按节次给出的条目换用另一套作息时间表的时间；节次不在表中时保持原时间
+pub fn retime(entry: &ScheduleEntry, periods: &[BellPeriod]) -> ScheduleEntry {
+    let mut entry = entry.clone();
+    if let Some(start) = entry.period_start {
+        if let Ok((start_time, end_time)) =
+            period_times(periods, start, entry.period_end.unwrap_or(start))
+        {
+            entry.start_time = start_time;
+            entry.end_time = end_time;
+        }
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(number: i32, start: &str, end: &str) -> BellPeriod {
+        BellPeriod {
+            period: number,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+        }
+    }
+
+    fn regular() -> Vec<BellPeriod> {
+        vec![
+            period(1, "08:00", "08:45"),
+            period(2, "08:55", "09:40"),
+            period(3, "10:00", "10:45"),
+        ]
+    }
+
+    #[test]
+    fn test_validate_bell_schedule() {
+        assert!(validate_bell_schedule("Regular", &regular()).is_ok());
+        assert!(validate_bell_schedule("Regular", &[]).is_err());
+
+        let mut overlapping = regular();
+        overlapping[1].start_time = "08:30".to_string();
+        assert_eq!(
+            validate_bell_schedule("Regular", &overlapping),
+            Err("periods: period 2 starts before period 1 ends".to_string())
+        );
+
+        let duplicate = vec![period(1, "08:00", "08:45"), period(1, "09:00", "09:45")];
+        assert!(validate_bell_schedule("Regular", &duplicate).is_err());
+    }
+
+    #[test]
+    fn test_entry_times() {
+        let periods = regular();
+        assert_eq!(
+            entry_times(Some(&periods), Some(2), Some(3), None, None),
+            Ok(("08:55".to_string(), "10:45".to_string()))
+        );
+        assert_eq!(
+            entry_times(Some(&periods), Some(3), None, None, None),
+            Ok(("10:00".to_string(), "10:45".to_string()))
+        );
+        assert!(entry_times(Some(&periods), Some(4), None, None, None).is_err());
+        assert!(entry_times(None, Some(1), None, None, None).is_err());
+        assert!(entry_times(
+            Some(&periods),
+            Some(1),
+            None,
+            Some("08:00".to_string()),
+            None
+        )
+        .is_err());
+        assert_eq!(
+            entry_times(
+                None,
+                None,
+                None,
+                Some("13:00".to_string()),
+                Some("13:45".to_string())
+            ),
+            Ok(("13:00".to_string(), "13:45".to_string()))
+        );
+    }
+}
@@ -0,0 +1,537 @@
+use crate::bells;
+use crate::models::{
+    CreateScheduleException, EffectiveEntry, ScheduleEntry, ScheduleException, Semester,
+};
+use crate::semester::week_number;
+use crate::validation::{parse_hhmm, MAX_LOCATION_LEN};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+/// 例外类型
+pub const CANCEL_DAY: &str = "cancel_day"; // 全天停课（节假日、考试周）
+pub const REMAP_DAY: &str = "remap_day"; // 调课日：当天按另一个星期的课表上课
+pub const CANCEL_ENTRY: &str = "cancel_entry"; // 取消单节课
+pub const RELOCATE_ENTRY: &str = "relocate_entry"; // 单节课改时间、教室或日期
+pub const BELL_SCHEDULE: &str = "bell_schedule"; // 当天改用另一套作息时间表（如短课日）
+
+pub const EXCEPTION_KINDS: &[&str] = &[
+    CANCEL_DAY,
+    REMAP_DAY,
+    CANCEL_ENTRY,
+    RELOCATE_ENTRY,
+    BELL_SCHEDULE,
+];
+
+/// 生效课表中条目的状态
+pub const STATUS_SCHEDULED: &str = "scheduled";
+pub const STATUS_CANCELLED: &str = "cancelled";
+pub const STATUS_RELOCATED: &str = "relocated";
+
+/// 查找下一节课时最多向后查看的天数
+pub const NEXT_CLASS_LOOKAHEAD_DAYS: i64 = 14;
+
+/// 日期对应的星期（1 = 周一 … 7 = 周日），与 `day_of_week` 一致
+pub fn day_of_week(date: NaiveDate) -> i32 {
+    date.weekday().number_from_monday() as i32
+}
+
+/// 按类型检查例外所需的字段
+pub fn validate_exception(exception: &CreateScheduleException) -> Result<(), String> {
+    if !EXCEPTION_KINDS.contains(&exception.kind.as_str()) {
+        return Err(format!(
+            "kind must be one of {}",
+            EXCEPTION_KINDS.join(", ")
+        ));
+    }
+
+    let is_entry_kind = exception.kind == CANCEL_ENTRY || exception.kind == RELOCATE_ENTRY;
+    if is_entry_kind && exception.entry_id.is_none() {
+        return Err(format!("entry_id is required for {}", exception.kind));
+    }
+    if !is_entry_kind && exception.entry_id.is_some() {
+        return Err(format!("entry_id is not allowed for {}", exception.kind));
+    }
+
+    if exception.kind == REMAP_DAY {
+        match exception.follows_day_of_week {
+            Some(day) if (1..=7).contains(&day) => {}
+            Some(_) => return Err("follows_day_of_week must be between 1 and 7".to_string()),
+            None => return Err("follows_day_of_week is required for remap_day".to_string()),
+        }
+    }
+
+    if exception.kind == BELL_SCHEDULE && exception.bell_schedule_id.is_none() {
+        return Err("bell_schedule_id is required for bell_schedule".to_string());
+    }
+    if exception.kind != BELL_SCHEDULE && exception.bell_schedule_id.is_some() {
+        return Err(format!(
+            "bell_schedule_id is not allowed for {}",
+            exception.kind
+        ));
+    }
+
+    if exception.kind == RELOCATE_ENTRY {
+        let start = exception.new_start_time.as_deref();
+        let end = exception.new_end_time.as_deref();
+        if start.is_none()
+            && end.is_none()
+            && exception.new_room.is_none()
+            && exception.new_date.is_none()
+        {
+            return Err(
+                "relocate_entry needs new_date, new_start_time/new_end_time or new_room"
+                    .to_string(),
+            );
+        }
+        if start.is_some() != end.is_some() {
+            return Err("new_start_time and new_end_time must be given together".to_string());
+        }
+        if let (Some(start), Some(end)) = (start, end) {
+            match (parse_hhmm(start), parse_hhmm(end)) {
+                (Some(start), Some(end)) if start < end => {}
+                (Some(_), Some(_)) => {
+                    return Err("new_end_time must be after new_start_time".to_string())
+                }
+                _ => return Err("new_start_time and new_end_time must be HH:MM".to_string()),
+            }
+        }
+        if exception
+            .new_room
+            .as_deref()
+            .is_some_and(|room| room.chars().count() > MAX_LOCATION_LEN)
+        {
+            return Err(format!(
+                "new_room must be at most {} characters",
+                MAX_LOCATION_LEN
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// 某一天实际按哪个星期的课表上课；全天停课时返回 `None`
+pub fn effective_day_of_week(date: NaiveDate, exceptions: &[ScheduleException]) -> Option<i32> {
+    if exceptions
+        .iter()
+        .any(|e| e.date == date && e.kind == CANCEL_DAY)
+    {
+        return None;
+    }
+    Some(scheduled_day_of_week(date, exceptions))
+}
+
+/// 某一天按课表应上哪个星期的课（考虑调课日），不考虑全天停课
+fn scheduled_day_of_week(date: NaiveDate, exceptions: &[ScheduleException]) -> i32 {
+    exceptions
+        .iter()
+        .filter(|e| e.date == date && e.kind == REMAP_DAY)
+        .find_map(|e| e.follows_day_of_week)
+        .unwrap_or_else(|| day_of_week(date))
+}
+
+/// 由课表条目与例外计算某一天的生效课表
+///
+/// `entries` 为客户端全部课表条目（调到当天的课可能来自其他星期），
+/// `exceptions` 还需包含这些课原日期上的调课日与停课日，
+/// `week` 为当天的教学周，`None` 表示不按周次过滤。
+/// 被取消或调走的课仍会返回，状态为 `cancelled`。
+/// 当天改用其他作息时间表时，按节次安排的条目换用该表的时间。
+pub fn effective_schedule(
+    date: NaiveDate,
+    week: Option<i32>,
+    entries: &[ScheduleEntry],
+    exceptions: &[ScheduleException],
+) -> Vec<EffectiveEntry> {
+    let Some(weekday) = effective_day_of_week(date, exceptions) else {
+        return Vec::new();
+    };
+    let bell_periods = exceptions
+        .iter()
+        .filter(|e| e.date == date && e.kind == BELL_SCHEDULE)
+        .find_map(|e| e.bell_periods.as_deref());
+    let on_bells = |entry: &ScheduleEntry| match bell_periods {
+        Some(periods) => bells::retime(entry, periods),
+        None => entry.clone(),
+    };
+    let held_in_week =
+        |entry: &ScheduleEntry, week: Option<i32>| week.is_none_or(|w| entry.held_in_week(w));
+    let entry_exception = |entry: &ScheduleEntry, on: NaiveDate| {
+        exceptions.iter().find(|e| {
+            e.entry_id == Some(entry.id)
+                && e.date == on
+                && (e.kind == CANCEL_ENTRY || e.kind == RELOCATE_ENTRY)
+        })
+    };
+
+    let mut result: Vec<EffectiveEntry> = entries
+        .iter()
+        .filter(|entry| entry.day_of_week == weekday && held_in_week(entry, week))
+        .map(|entry| match entry_exception(entry, date) {
+            None => EffectiveEntry::new(on_bells(entry), STATUS_SCHEDULED, None),
+            Some(exception) if exception.kind == CANCEL_ENTRY => {
+                EffectiveEntry::new(on_bells(entry), STATUS_CANCELLED, Some(exception))
+            }
+            Some(exception) if exception.new_date.is_some_and(|d| d != date) => {
+                let mut moved =
+                    EffectiveEntry::new(on_bells(entry), STATUS_CANCELLED, Some(exception));
+                moved.relocated_to = exception.new_date.map(|d| d.format("%Y-%m-%d").to_string());
+                moved
+            }
+            Some(exception) => relocated(&on_bells(entry), exception),
+        })
+        .collect();
+
+    // Classes moved here from another date, if they were scheduled on that date.
+    // A holiday on the original date does not matter: that is the usual make-up case.
+    for exception in exceptions
+        .iter()
+        .filter(|e| e.kind == RELOCATE_ENTRY && e.new_date == Some(date) && e.date != date)
+    {
+        let original_weekday = scheduled_day_of_week(exception.date, exceptions);
+        let original_week = week.map(|w| w + week_number(date, exception.date) - 1);
+        if let Some(entry) = entries.iter().find(|e| {
+            Some(e.id) == exception.entry_id
+                && e.day_of_week == original_weekday
+                && held_in_week(e, original_week)
+        }) {
+            result.push(relocated(&on_bells(entry), exception));
+        }
+    }
+
+    result.sort_by(|a, b| a.entry.start_time.cmp(&b.entry.start_time));
+    result
+}
+
+/// 某一天的生效课表，考虑当前学期：学期外没有课，学期内按教学周过滤
+///
+/// `semester` 为 `None`（未设置当前学期）时不按周次过滤。
+pub fn day_entries(
+    date: NaiveDate,
+    semester: Option<&Semester>,
+    entries: &[ScheduleEntry],
+    exceptions: &[ScheduleException],
+) -> Vec<EffectiveEntry> {
+    match semester {
+        Some(s) if date < s.start_date || date > s.end_date => Vec::new(),
+        Some(s) => effective_schedule(
+            date,
+            Some(week_number(s.start_date, date)),
+            entries,
+            exceptions,
+        ),
+        None => effective_schedule(date, None, entries, exceptions),
+    }
+}
+
+/// 某一时刻正在上的课与下一节课（含其日期）
+///
+/// 已取消的课不参与计算；下一节课最多向后查找 [`NEXT_CLASS_LOOKAHEAD_DAYS`] 天。
+pub fn now_and_next(
+    at: NaiveDateTime,
+    semester: Option<&Semester>,
+    entries: &[ScheduleEntry],
+    exceptions: &[ScheduleException],
+) -> (Option<EffectiveEntry>, Option<(NaiveDate, EffectiveEntry)>) {
+    let now = at.time().hour() * 60 + at.time().minute();
+    let held = |date: NaiveDate| {
+        day_entries(date, semester, entries, exceptions)
+            .into_iter()
+            .filter(|e| e.status != STATUS_CANCELLED)
+            .filter_map(|e| {
+                let start = parse_hhmm(&e.entry.start_time)?;
+                let end = parse_hhmm(&e.entry.end_time)?;
+                Some((start, end, e))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let today = held(at.date());
+    let current = today
+        .iter()
+        .find(|(start, end, _)| *start <= now && now < *end)
+        .map(|(_, _, e)| e.clone());
+    let next_today = today
+        .into_iter()
+        .filter(|(start, _, _)| *start > now)
+        .min_by_key(|(start, _, _)| *start)
+        .map(|(_, _, e)| (at.date(), e));
+
+    let next = next_today.or_else(|| {
+        (1..=NEXT_CLASS_LOOKAHEAD_DAYS).find_map(|days| {
+            let date = at.date() + Duration::days(days);
+            held(date)
+                .into_iter()
+                .min_by_key(|(start, _, _)| *start)
+                .map(|(_, _, e)| (date, e))
+        })
+    });
+
+    (current, next)
+}
+
+fn relocated(entry: &ScheduleEntry, exception: &ScheduleException) -> EffectiveEntry {
+    let mut entry = entry.clone();
+    if let (Some(start), Some(end)) = (&exception.new_start_time, &exception.new_end_time) {
+        entry.start_time = start.clone();
+        entry.end_time = end.clone();
+    }
+    if let Some(room) = &exception.new_room {
+        entry.room = Some(room.clone());
+    }
+    EffectiveEntry::new(entry, STATUS_RELOCATED, Some(exception))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Attributes;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn entry(id: i32, day: i32, start: &str, weeks: Option<Vec<i32>>) -> ScheduleEntry {
+        ScheduleEntry {
+            id,
+            client_id: 1,
+            entry_id_on_client: id,
+            course_id: 1,
+            course_name: None,
+            teacher: None,
+            location: None,
+            color: None,
+            day_of_week: day,
+            start_time: start.to_string(),
+            end_time: "23:59".to_string(),
+            weeks,
+            weeks_text: None,
+            weeks_legacy: None,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+            origin: "client".to_string(),
+            room_id: None,
+            period_start: None,
+            period_end: None,
+        }
+    }
+
+    fn exception(kind: &str, on: &str) -> ScheduleException {
+        ScheduleException {
+            id: 1,
+            client_id: None,
+            date: date(on),
+            kind: kind.to_string(),
+            follows_day_of_week: None,
+            bell_schedule_id: None,
+            bell_periods: None,
+            entry_id: None,
+            new_date: None,
+            new_start_time: None,
+            new_end_time: None,
+            new_room: None,
+            reason: None,
+            created_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    // 2024-10-07 is a Monday, 2024-10-12 a Saturday
+    #[test]
+    fn test_weekday_and_weeks_filter() {
+        let entries = vec![
+            entry(1, 1, "08:00", None),
+            entry(2, 1, "10:00", Some(vec![2, 4])),
+            entry(3, 2, "08:00", None),
+            // Weeks the migration could not read are not taken as every week
+            ScheduleEntry {
+                weeks_legacy: Some("单周".to_string()),
+                ..entry(4, 1, "12:00", None)
+            },
+        ];
+        let monday = date("2024-10-07");
+        let ids = |week: Option<i32>| -> Vec<i32> {
+            effective_schedule(monday, week, &entries, &[])
+                .iter()
+                .map(|e| e.entry.id)
+                .collect()
+        };
+
+        assert_eq!(ids(Some(3)), vec![1]);
+        assert_eq!(ids(Some(4)), vec![1, 2]);
+        assert_eq!(ids(None), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn test_holiday_and_make_up_day() {
+        let entries = vec![entry(1, 1, "08:00", None)];
+        let holiday = exception(CANCEL_DAY, "2024-10-07");
+        let make_up = ScheduleException {
+            follows_day_of_week: Some(1),
+            ..exception(REMAP_DAY, "2024-10-12")
+        };
+        let exceptions = vec![holiday, make_up];
+
+        assert!(effective_schedule(date("2024-10-07"), None, &entries, &exceptions).is_empty());
+        let saturday = effective_schedule(date("2024-10-12"), None, &entries, &exceptions);
+        assert_eq!(saturday.len(), 1);
+        assert_eq!(saturday[0].status, STATUS_SCHEDULED);
+    }
+
+    #[test]
+    fn test_now_and_next() {
+        let entries = vec![
+            entry(1, 1, "08:00", None),
+            entry(2, 1, "10:00", None),
+            entry(3, 3, "09:00", None),
+        ];
+        let at = |value: &str| NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap();
+        let ids =
+            |(current, next): (Option<EffectiveEntry>, Option<(NaiveDate, EffectiveEntry)>)| {
+                (
+                    current.map(|e| e.entry.id),
+                    next.map(|(d, e)| (d, e.entry.id)),
+                )
+            };
+
+        // Monday 08:30: in class 1, class 2 is next
+        assert_eq!(
+            ids(now_and_next(at("2024-10-07 08:30"), None, &entries, &[])),
+            (Some(1), Some((date("2024-10-07"), 2)))
+        );
+        // Monday night: next class is on Wednesday
+        assert_eq!(
+            ids(now_and_next(at("2024-10-07 23:59"), None, &entries, &[])),
+            (None, Some((date("2024-10-09"), 3)))
+        );
+        // Wednesday cancelled: next is the following Monday
+        let holiday = exception(CANCEL_DAY, "2024-10-09");
+        assert_eq!(
+            ids(now_and_next(
+                at("2024-10-07 23:59"),
+                None,
+                &entries,
+                &[holiday]
+            )),
+            (None, Some((date("2024-10-14"), 1)))
+        );
+    }
+
+    #[test]
+    fn test_entry_cancellation_and_relocation() {
+        let entries = vec![entry(1, 1, "08:00", None), entry(2, 1, "10:00", None)];
+        let cancel = ScheduleException {
+            entry_id: Some(1),
+            ..exception(CANCEL_ENTRY, "2024-10-07")
+        };
+        let relocate = ScheduleException {
+            entry_id: Some(2),
+            new_date: Some(date("2024-10-08")),
+            new_start_time: Some("14:00".to_string()),
+            new_end_time: Some("15:00".to_string()),
+            new_room: Some("B201".to_string()),
+            ..exception(RELOCATE_ENTRY, "2024-10-07")
+        };
+        let exceptions = vec![cancel, relocate];
+
+        let monday = effective_schedule(date("2024-10-07"), None, &entries, &exceptions);
+        assert_eq!(monday.len(), 2);
+        assert!(monday.iter().all(|e| e.status == STATUS_CANCELLED));
+        assert_eq!(monday[1].relocated_to.as_deref(), Some("2024-10-08"));
+
+        let tuesday = effective_schedule(date("2024-10-08"), None, &entries, &exceptions);
+        assert_eq!(tuesday.len(), 1);
+        assert_eq!(tuesday[0].status, STATUS_RELOCATED);
+        assert_eq!(tuesday[0].entry.start_time, "14:00");
+        assert_eq!(tuesday[0].entry.room.as_deref(), Some("B201"));
+    }
+
+    #[test]
+    fn test_relocation_requires_original_class() {
+        let entries = vec![
+            entry(1, 1, "08:00", None),
+            entry(2, 1, "10:00", Some(vec![2])),
+        ];
+        let relocate = |id: i32, from: &str| ScheduleException {
+            entry_id: Some(id),
+            new_date: Some(date("2024-10-10")),
+            ..exception(RELOCATE_ENTRY, from)
+        };
+
+        // A Monday class cannot be moved from a Wednesday, nor a week-2 class from week 1
+        let exceptions = vec![relocate(1, "2024-10-09"), relocate(2, "2024-10-07")];
+        let thursday = effective_schedule(date("2024-10-10"), Some(1), &entries, &exceptions);
+        assert!(thursday.is_empty());
+
+        // Next Monday's week-2 class can be moved to this Thursday
+        let exceptions = vec![relocate(2, "2024-10-14")];
+        let thursday = effective_schedule(date("2024-10-10"), Some(1), &entries, &exceptions);
+        assert_eq!(thursday.len(), 1);
+        assert_eq!(thursday[0].status, STATUS_RELOCATED);
+    }
+
+    #[test]
+    fn test_relocation_from_holiday_or_remapped_day() {
+        let entries = vec![entry(1, 1, "08:00", None), entry(2, 5, "10:00", None)];
+        let relocate = |id: i32, from: &str| ScheduleException {
+            entry_id: Some(id),
+            new_date: Some(date("2024-10-12")),
+            ..exception(RELOCATE_ENTRY, from)
+        };
+
+        // Monday's class is made up on Saturday although Monday is a holiday
+        let exceptions = vec![
+            exception(CANCEL_DAY, "2024-10-07"),
+            relocate(1, "2024-10-07"),
+        ];
+        let monday = effective_schedule(date("2024-10-07"), None, &entries, &exceptions);
+        assert!(monday.is_empty());
+        let saturday = effective_schedule(date("2024-10-12"), None, &entries, &exceptions);
+        assert_eq!(saturday.len(), 1);
+        assert_eq!(saturday[0].entry.id, 1);
+
+        // Wednesday follows Friday's timetable, so its Friday class can be moved away
+        let exceptions = vec![
+            ScheduleException {
+                follows_day_of_week: Some(5),
+                ..exception(REMAP_DAY, "2024-10-09")
+            },
+            relocate(2, "2024-10-09"),
+        ];
+        let saturday = effective_schedule(date("2024-10-12"), None, &entries, &exceptions);
+        assert_eq!(saturday.len(), 1);
+        assert_eq!(saturday[0].entry.id, 2);
+        assert_eq!(saturday[0].status, STATUS_RELOCATED);
+    }
+
+    #[test]
+    fn test_short_day_bell_schedule() {
+        let by_period = ScheduleEntry {
+            period_start: Some(2),
+            period_end: Some(3),
+            end_time: "10:45".to_string(),
+            ..entry(1, 1, "08:55", None)
+        };
+        let entries = vec![by_period, entry(2, 1, "13:00", None)];
+        let period = |number: i32, start: &str, end: &str| crate::models::BellPeriod {
+            period: number,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+        };
+        let short_day = ScheduleException {
+            bell_schedule_id: Some(2),
+            bell_periods: Some(vec![
+                period(1, "08:00", "08:35"),
+                period(2, "08:45", "09:20"),
+                period(3, "09:30", "10:05"),
+            ]),
+            ..exception(BELL_SCHEDULE, "2024-10-07")
+        };
+
+        let monday = effective_schedule(date("2024-10-07"), None, &entries, &[short_day]);
+        let times: Vec<(&str, &str)> = monday
+            .iter()
+            .map(|e| (e.entry.start_time.as_str(), e.entry.end_time.as_str()))
+            .collect();
+        assert_eq!(times, vec![("08:45", "10:05"), ("13:00", "23:59")]);
+    }
+}
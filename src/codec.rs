@@ -0,0 +1,172 @@
+use crate::error::{AppError, AppResult};
+use actix_web::http::header::{self, Accept, Header};
+use actix_web::{HttpRequest, HttpResponse, HttpResponseBuilder};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// 请求/响应体编码格式
+///
+/// JSON 为默认格式；MessagePack 与 CBOR 用于计量流量的客户端。
+/// 压缩的请求体（`Content-Encoding: gzip`、`zstd`、`br`）由 actix-web
+/// 的 `web::Bytes` 提取器解压，这里拿到的总是解压后的数据。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyFormat {
+    Json,
+    MessagePack,
+    Cbor,
+}
+
+impl BodyFormat {
+    /// 根据 MIME 类型（不含参数）识别格式
+    pub fn from_mime(mime: &str) -> Option<Self> {
+        match mime.trim().to_ascii_lowercase().as_str() {
+            "application/json" => Some(BodyFormat::Json),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(BodyFormat::MessagePack)
+            }
+            "application/cbor" => Some(BodyFormat::Cbor),
+            _ => None,
+        }
+    }
+
+    /// 请求体格式，由 `Content-Type` 决定，缺省为 JSON
+    pub fn from_content_type(req: &HttpRequest) -> AppResult<Self> {
+        let Some(value) = req.headers().get(header::CONTENT_TYPE) else {
+            return Ok(BodyFormat::Json);
+        };
+        let content_type = value
+            .to_str()
+            .map_err(|_| AppError::BadRequest("Invalid Content-Type header".to_string()))?;
+        let mime = content_type.split(';').next().unwrap_or_default();
+
+        BodyFormat::from_mime(mime).ok_or_else(|| {
+            AppError::UnsupportedMediaType(format!(
+                "Unsupported Content-Type '{}', expected application/json, application/msgpack or application/cbor",
+                mime.trim()
+            ))
+        })
+    }
+
+    /// 响应体格式，取 `Accept` 中优先级最高的受支持类型，缺省为 JSON
+    pub fn from_accept(req: &HttpRequest) -> Self {
+        Accept::parse(req)
+            .ok()
+            .and_then(|accept| {
+                accept
+                    .ranked()
+                    .iter()
+                    .find_map(|mime| BodyFormat::from_mime(mime.essence_str()))
+            })
+            .unwrap_or(BodyFormat::Json)
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            BodyFormat::Json => "application/json",
+            BodyFormat::MessagePack => "application/msgpack",
+            BodyFormat::Cbor => "application/cbor",
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(&self, body: &[u8]) -> AppResult<T> {
+        let result = match self {
+            BodyFormat::Json => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            BodyFormat::MessagePack => rmp_serde::from_slice(body).map_err(|e| e.to_string()),
+            BodyFormat::Cbor => ciborium::from_reader(body).map_err(|e| e.to_string()),
+        };
+        result.map_err(|e| AppError::BadRequest(format!("Invalid {} body: {}", self.mime(), e)))
+    }
+
+    pub fn encode<T: Serialize>(&self, value: &T) -> AppResult<Vec<u8>> {
+        let result = match self {
+            BodyFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            // Named fields keep the MessagePack layout identical to the JSON one
+            BodyFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            BodyFormat::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(value, &mut buf)
+                    .map(|_| buf)
+                    .map_err(|e| e.to_string())
+            }
+        };
+        result.map_err(|e| AppError::Internal(format!("Failed to encode response: {}", e)))
+    }
+}
+
+/// 按 `Content-Type` 解码请求体
+pub fn decode_body<T: DeserializeOwned>(req: &HttpRequest, body: &[u8]) -> AppResult<T> {
+    BodyFormat::from_content_type(req)?.decode(body)
+}
+
+/// 按 `Accept` 编码响应体
+pub fn respond<T: Serialize>(
+    req: &HttpRequest,
+    mut builder: HttpResponseBuilder,
+    value: &T,
+) -> AppResult<HttpResponse> {
+    let format = BodyFormat::from_accept(req);
+    let body = format.encode(value)?;
+    Ok(builder
+        .content_type(format.mime())
+        .insert_header((header::VARY, "Accept"))
+        .body(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        name: String,
+        weeks: Option<Vec<i32>>,
+    }
+
+    #[test]
+    fn test_roundtrip_all_formats() {
+        let sample = Sample {
+            name: "数学".to_string(),
+            weeks: Some(vec![1, 2, 3]),
+        };
+        for format in [BodyFormat::Json, BodyFormat::MessagePack, BodyFormat::Cbor] {
+            let bytes = format.encode(&sample).unwrap();
+            assert_eq!(format.decode::<Sample>(&bytes).unwrap(), sample);
+        }
+    }
+
+    #[test]
+    fn test_content_type_negotiation() {
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(
+            BodyFormat::from_content_type(&req).unwrap(),
+            BodyFormat::Json
+        );
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/x-msgpack"))
+            .to_http_request();
+        assert_eq!(
+            BodyFormat::from_content_type(&req).unwrap(),
+            BodyFormat::MessagePack
+        );
+
+        let req = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .to_http_request();
+        assert!(BodyFormat::from_content_type(&req).is_err());
+    }
+
+    #[test]
+    fn test_accept_negotiation() {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "text/html;q=1.0, application/cbor;q=0.5"))
+            .to_http_request();
+        assert_eq!(BodyFormat::from_accept(&req), BodyFormat::Cbor);
+
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, "*/*"))
+            .to_http_request();
+        assert_eq!(BodyFormat::from_accept(&req), BodyFormat::Json);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::models::{ScheduleConflict, ScheduleEntry};
+use crate::validation::{parse_hhmm, weeks_intersect};
+use crate::weeks;
+use std::collections::BTreeMap;
+
+/// 冲突类型
+pub const KIND_TEACHER: &str = "teacher"; // 同一教师同时在两处上课
+pub const KIND_LOCATION: &str = "location"; // 同一教室同时安排两门课（条目教室优先于课程地点）
+
+pub const CONFLICT_KINDS: &[&str] = &[KIND_TEACHER, KIND_LOCATION];
+
+/// 条目在某类冲突中占用的资源：教师姓名或教室，空白时不参与检测
+pub fn resource<'a>(entry: &'a ScheduleEntry, kind: &str) -> Option<&'a str> {
+    let value = match kind {
+        KIND_TEACHER => entry.teacher.as_deref(),
+        _ => entry.room.as_deref().or(entry.location.as_deref()),
+    };
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// 两个条目共同上课的周次，`None` 表示每周
+fn shared_weeks(a: Option<&[i32]>, b: Option<&[i32]>) -> Option<Vec<i32>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.iter().copied().filter(|w| b.contains(w)).collect()),
+        (Some(weeks), None) | (None, Some(weeks)) => Some(weeks.to_vec()),
+        (None, None) => None,
+    }
+}
+
+fn overlaps(a: &ScheduleEntry, b: &ScheduleEntry) -> bool {
+    let (Some(a_start), Some(a_end), Some(b_start), Some(b_end)) = (
+        parse_hhmm(&a.start_time),
+        parse_hhmm(&a.end_time),
+        parse_hhmm(&b.start_time),
+        parse_hhmm(&b.end_time),
+    ) else {
+        return false;
+    };
+    a_start < b_end && b_start < a_end && weeks_intersect(a.weeks.as_deref(), b.weeks.as_deref())
+}
+
+/// 在所有客户端的条目中查找教师与教室冲突
+///
+/// 同一资源在同一天时间段重叠、且周次相交的每对条目记为一个冲突，
+/// 按类型、资源、星期与开始时间排序。
+pub fn find_conflicts(entries: &[ScheduleEntry]) -> Vec<ScheduleConflict> {
+    let mut conflicts = Vec::new();
+
+    for kind in CONFLICT_KINDS {
+        let mut groups: BTreeMap<(&str, i32), Vec<&ScheduleEntry>> = BTreeMap::new();
+        for entry in entries {
+            if let Some(resource) = resource(entry, kind) {
+                groups
+                    .entry((resource, entry.day_of_week))
+                    .or_default()
+                    .push(entry);
+            }
+        }
+
+        for ((resource, day_of_week), mut group) in groups {
+            group.sort_by(|a, b| (&a.start_time, a.id).cmp(&(&b.start_time, b.id)));
+            for (i, a) in group.iter().enumerate() {
+                for b in &group[i + 1..] {
+                    if !overlaps(a, b) {
+                        continue;
+                    }
+                    let weeks = shared_weeks(a.weeks.as_deref(), b.weeks.as_deref());
+                    conflicts.push(ScheduleConflict {
+                        kind: kind.to_string(),
+                        resource: resource.to_string(),
+                        day_of_week,
+                        weeks_text: weeks.as_deref().map(weeks::format),
+                        weeks,
+                        entries: vec![(*a).clone(), (*b).clone()],
+                    });
+                }
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Attributes;
+
+    fn entry(
+        id: i32,
+        client_id: i32,
+        teacher: &str,
+        location: &str,
+        start: &str,
+        end: &str,
+        weeks: Option<Vec<i32>>,
+    ) -> ScheduleEntry {
+        ScheduleEntry {
+            id,
+            client_id,
+            entry_id_on_client: id,
+            course_id: id,
+            course_name: None,
+            teacher: Some(teacher.to_string()),
+            location: Some(location.to_string()),
+            color: None,
+            day_of_week: 1,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            weeks,
+            weeks_text: None,
+            weeks_legacy: None,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+            origin: "client".to_string(),
+            room_id: None,
+            period_start: None,
+            period_end: None,
+        }
+    }
+
+    #[test]
+    fn test_teacher_in_two_rooms() {
+        let conflicts = find_conflicts(&[
+            entry(1, 1, "Li", "A101", "08:00", "08:45", Some(vec![1, 2, 3])),
+            entry(2, 2, "Li", "B201", "08:30", "09:15", Some(vec![3, 4])),
+            entry(3, 3, "Li", "C301", "08:45", "09:30", None),
+        ]);
+
+        assert_eq!(conflicts.len(), 2);
+        let pairs: Vec<(i32, i32)> = conflicts
+            .iter()
+            .map(|c| (c.entries[0].id, c.entries[1].id))
+            .collect();
+        assert_eq!(pairs, vec![(1, 2), (2, 3)]);
+        assert_eq!(conflicts[0].kind, KIND_TEACHER);
+        assert_eq!(conflicts[0].weeks, Some(vec![3]));
+        assert_eq!(conflicts[1].weeks, Some(vec![3, 4]));
+    }
+
+    #[test]
+    fn test_room_overrides_course_location() {
+        let mut moved = entry(2, 2, "Wang", "A101", "08:00", "08:45", None);
+        moved.room = Some("B201".to_string());
+        let conflicts = find_conflicts(&[
+            entry(1, 1, "Li", "A101", "08:00", "08:45", None),
+            moved,
+            entry(3, 3, "Zhao", " B201 ", "08:15", "09:00", None),
+        ]);
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, KIND_LOCATION);
+        assert_eq!(conflicts[0].resource, "B201");
+        assert_eq!(conflicts[0].weeks, None);
+    }
+
+    #[test]
+    fn test_disjoint_weeks_are_not_conflicts() {
+        let conflicts = find_conflicts(&[
+            entry(1, 1, "Li", "A101", "08:00", "08:45", Some(vec![1, 3, 5])),
+            entry(2, 2, "Li", "A101", "08:00", "08:45", Some(vec![2, 4, 6])),
+        ]);
+        assert!(conflicts.is_empty());
+    }
+}
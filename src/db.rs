@@ -37,21 +37,637 @@ pub async fn run_migrations(pool: &DbPool) -> Result<()> {
         .await
         .ok();
 
+    sqlx::query(include_str!("../migrations/006_add_sync_history.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/007_add_sync_idempotency.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/008_add_course_attributes.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!(
+        "../migrations/009_add_timetable_snapshots.sql"
+    ))
+    .execute(pool)
+    .await
+    .ok();
+
+    sqlx::query(include_str!("../migrations/010_typed_schedule_weeks.sql"))
+        .execute(pool)
+        .await
+        .ok();
+    convert_legacy_weeks(pool).await.ok();
+
+    sqlx::query(include_str!("../migrations/011_add_semester_weeks.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/012_add_semesters.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!(
+        "../migrations/013_add_schedule_exceptions.sql"
+    ))
+    .execute(pool)
+    .await
+    .ok();
+
+    sqlx::query(include_str!("../migrations/014_add_calendar_feeds.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/015_add_server_origin.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/016_add_rooms.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!(
+        "../migrations/017_add_timetable_templates.sql"
+    ))
+    .execute(pool)
+    .await
+    .ok();
+
+    sqlx::query(include_str!("../migrations/018_add_bell_schedules.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/019_add_announcements.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/020_add_emergency_alerts.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/021_add_display_events.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/022_add_snapshot_origins.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!("../migrations/023_add_sync_request_hash.sql"))
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query(include_str!(
+        "../migrations/024_add_deleted_timetable_items.sql"
+    ))
+    .execute(pool)
+    .await
+    .ok();
+
     Ok(())
 }
 
+/// Parses week texts that migration 010 could not convert, such as "1-8,10".
+/// Rows that still fail keep their text in `weeks_legacy` and are reported.
+async fn convert_legacy_weeks(pool: &DbPool) -> Result<()> {
+    use sqlx::Row;
+
+    let rows =
+        sqlx::query("SELECT id, weeks_legacy FROM schedule_entries WHERE weeks_legacy IS NOT NULL")
+            .fetch_all(pool)
+            .await?;
+
+    let mut unparsed = 0;
+    for row in rows {
+        let id: i32 = row.get("id");
+        let legacy: String = row.get("weeks_legacy");
+        match crate::weeks::parse(&legacy) {
+            Ok(weeks) => {
+                sqlx::query(
+                    "UPDATE schedule_entries SET weeks = $1, weeks_legacy = NULL WHERE id = $2",
+                )
+                .bind(&weeks)
+                .bind(id)
+                .execute(pool)
+                .await?;
+            }
+            Err(_) => unparsed += 1,
+        }
+    }
+    if unparsed > 0 {
+        tracing::warn!(
+            "{} schedule entries have unreadable weeks in weeks_legacy and are left out of week-filtered schedules until their weeks are set",
+            unparsed
+        );
+    }
+
+    Ok(())
+}
+
+const COURSE_COLUMNS: &str =
+    "id, client_id, course_id_on_client, name, teacher, location, color, note, attributes, origin";
+
+fn course_from_row(row: &sqlx::postgres::PgRow) -> crate::models::Course {
+    use sqlx::Row;
+    crate::models::Course {
+        id: row.get("id"),
+        client_id: row.get("client_id"),
+        course_id_on_client: row.get("course_id_on_client"),
+        name: row.get("name"),
+        teacher: row.try_get("teacher").ok(),
+        location: row.try_get("location").ok(),
+        color: row.try_get("color").ok(),
+        note: row.try_get("note").ok(),
+        attributes: attributes_from_row(row, "attributes"),
+        origin: row.get("origin"),
+    }
+}
+
+/// Columns selected for `ScheduleEntry`, from `schedule_entries se JOIN courses c`.
+/// The room is matched by name, falling back to the client's room for entries without a location.
+const SCHEDULE_ENTRY_COLUMNS: &str = "se.id, se.client_id, se.entry_id_on_client, se.course_id,
+     c.name as course_name, c.teacher, c.location, c.color,
+     se.day_of_week, se.start_time, se.end_time, se.weeks, se.weeks_legacy,
+     se.note, se.room, se.attributes, se.origin, se.period_start, se.period_end,
+     COALESCE(
+         (SELECT r.id FROM rooms r WHERE r.name = COALESCE(se.room, c.location)),
+         (SELECT cl.room_id FROM clients cl
+          WHERE cl.id = se.client_id AND COALESCE(se.room, c.location) IS NULL)
+     ) AS room_id";
+
+/// Optional client ($1), week ($2) and weekday ($3) filters on `schedule_entries se`.
+/// Entries whose weeks could not be read (`weeks_legacy`) match no week.
+const SCHEDULE_FILTER_CONDITION: &str = "($1::INTEGER IS NULL OR se.client_id = $1)
+     AND ($2::INTEGER IS NULL OR (se.weeks IS NULL AND se.weeks_legacy IS NULL)
+          OR se.weeks @> ARRAY[$2::INTEGER])
+     AND ($3::INTEGER IS NULL OR se.day_of_week = $3)";
+
+fn schedule_entry_from_row(row: &sqlx::postgres::PgRow) -> crate::models::ScheduleEntry {
+    use sqlx::Row;
+    let weeks: Option<Vec<i32>> = row.try_get("weeks").ok().flatten();
+    crate::models::ScheduleEntry {
+        id: row.get("id"),
+        client_id: row.get("client_id"),
+        entry_id_on_client: row.get("entry_id_on_client"),
+        course_id: row.get("course_id"),
+        course_name: row.try_get("course_name").ok(),
+        teacher: row.try_get("teacher").ok(),
+        location: row.try_get("location").ok(),
+        color: row.try_get("color").ok(),
+        day_of_week: row.get("day_of_week"),
+        start_time: row.get("start_time"),
+        end_time: row.get("end_time"),
+        weeks: weeks.clone(),
+        weeks_text: weeks.as_deref().map(crate::weeks::format),
+        weeks_legacy: row.try_get("weeks_legacy").ok().flatten(),
+        note: row.try_get("note").ok(),
+        room: row.try_get("room").ok(),
+        attributes: attributes_from_row(row, "attributes"),
+        origin: row.get("origin"),
+        room_id: row.try_get("room_id").ok(),
+        period_start: row.try_get("period_start").ok(),
+        period_end: row.try_get("period_end").ok(),
+    }
+}
+
+const BELL_SCHEDULE_COLUMNS: &str = "id, name, is_default, periods, created_at, updated_at";
+
+fn bell_schedule_from_row(row: &sqlx::postgres::PgRow) -> crate::models::BellSchedule {
+    use sqlx::Row;
+    crate::models::BellSchedule {
+        id: row.get("id"),
+        name: row.get("name"),
+        is_default: row.get("is_default"),
+        periods: row
+            .try_get::<serde_json::Value, _>("periods")
+            .ok()
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+const ROOM_COLUMNS: &str = "id, name, building, floor, capacity, equipment, created_at";
+
+fn room_from_row(row: &sqlx::postgres::PgRow) -> crate::models::Room {
+    use sqlx::Row;
+    crate::models::Room {
+        id: row.get("id"),
+        name: row.get("name"),
+        building: row.try_get("building").ok(),
+        floor: row.try_get("floor").ok(),
+        capacity: row.try_get("capacity").ok(),
+        equipment: row
+            .try_get::<serde_json::Value, _>("equipment")
+            .ok()
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default(),
+        created_at: row.get("created_at"),
+    }
+}
+
+const CLIENT_GROUP_COLUMNS: &str = "id, name, description, created_at,
+     ARRAY(SELECT m.client_id FROM client_group_members m
+           WHERE m.group_id = client_groups.id ORDER BY m.client_id) AS client_ids";
+
+fn client_group_from_row(row: &sqlx::postgres::PgRow) -> crate::models::ClientGroup {
+    use sqlx::Row;
+    crate::models::ClientGroup {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.try_get("description").ok(),
+        client_ids: row.get("client_ids"),
+        created_at: row.get("created_at"),
+    }
+}
+
+const ANNOUNCEMENT_COLUMNS: &str = "id, title, body, priority, starts_at, ends_at,
+     client_ids, group_ids, created_at, updated_at";
+
+fn announcement_from_row(row: &sqlx::postgres::PgRow) -> crate::models::Announcement {
+    use sqlx::Row;
+    crate::models::Announcement {
+        id: row.get("id"),
+        title: row.get("title"),
+        body: row.get("body"),
+        priority: row.get("priority"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.try_get("ends_at").ok(),
+        client_ids: row.get("client_ids"),
+        group_ids: row.get("group_ids"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+const EMERGENCY_ALERT_COLUMNS: &str =
+    "id, kind, message, drill, client_ids, group_ids, started_at, cleared_at";
+
+fn emergency_alert_from_row(row: &sqlx::postgres::PgRow) -> crate::models::EmergencyAlert {
+    use sqlx::Row;
+    crate::models::EmergencyAlert {
+        id: row.get("id"),
+        kind: row.get("kind"),
+        message: row.get("message"),
+        drill: row.get("drill"),
+        client_ids: row.get("client_ids"),
+        group_ids: row.get("group_ids"),
+        started_at: row.get("started_at"),
+        cleared_at: row.try_get("cleared_at").ok(),
+    }
+}
+
+const DISPLAY_EVENT_COLUMNS: &str = "id, label, target_date, style, show_from,
+     client_ids, group_ids, created_at, updated_at";
+
+fn display_event_from_row(row: &sqlx::postgres::PgRow) -> crate::models::DisplayEvent {
+    use sqlx::Row;
+    crate::models::DisplayEvent {
+        id: row.get("id"),
+        label: row.get("label"),
+        target_date: row.get("target_date"),
+        style: row.get("style"),
+        show_from: row.try_get("show_from").ok(),
+        client_ids: row.get("client_ids"),
+        group_ids: row.get("group_ids"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+fn semester_from_row(row: &sqlx::postgres::PgRow) -> crate::models::Semester {
+    use sqlx::Row;
+    crate::models::Semester {
+        id: row.get("id"),
+        name: row.get("name"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        week_count: row.get("week_count"),
+        is_active: row.get("is_active"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Columns selected for `ScheduleException`; bell schedule switches carry that schedule's periods.
+const SCHEDULE_EXCEPTION_COLUMNS: &str =
+    "id, client_id, date, kind, follows_day_of_week, entry_id, new_date,
+     new_start_time, new_end_time, new_room, reason, created_at, bell_schedule_id,
+     (SELECT b.periods FROM bell_schedules b
+      WHERE b.id = schedule_exceptions.bell_schedule_id) AS bell_periods";
+
+fn schedule_exception_from_row(row: &sqlx::postgres::PgRow) -> crate::models::ScheduleException {
+    use sqlx::Row;
+    crate::models::ScheduleException {
+        id: row.get("id"),
+        client_id: row.try_get("client_id").ok(),
+        date: row.get("date"),
+        kind: row.get("kind"),
+        follows_day_of_week: row.try_get("follows_day_of_week").ok(),
+        bell_schedule_id: row.try_get("bell_schedule_id").ok(),
+        bell_periods: row
+            .try_get::<serde_json::Value, _>("bell_periods")
+            .ok()
+            .and_then(|value| serde_json::from_value(value).ok()),
+        entry_id: row.try_get("entry_id").ok(),
+        new_date: row.try_get("new_date").ok(),
+        new_start_time: row.try_get("new_start_time").ok(),
+        new_end_time: row.try_get("new_end_time").ok(),
+        new_room: row.try_get("new_room").ok(),
+        reason: row.try_get("reason").ok(),
+        created_at: row.get("created_at"),
+    }
+}
+
+const CALENDAR_FEED_COLUMNS: &str = "id, token, scope, target, name, created_at, last_accessed_at";
+
+fn calendar_feed_from_row(row: &sqlx::postgres::PgRow) -> crate::models::CalendarFeed {
+    use sqlx::Row;
+    let token: String = row.get("token");
+    crate::models::CalendarFeed {
+        id: row.get("id"),
+        url: crate::ical::feed_path(&token),
+        token,
+        scope: row.get("scope"),
+        target: row.get("target"),
+        name: row.try_get("name").ok(),
+        created_at: row.get("created_at"),
+        last_accessed_at: row.try_get("last_accessed_at").ok(),
+    }
+}
+
+/// Reads a JSONB attributes column, treating NULL or non-object values as empty
+fn attributes_from_row(row: &sqlx::postgres::PgRow, column: &str) -> crate::models::Attributes {
+    use sqlx::Row;
+    match row.try_get::<serde_json::Value, _>(column) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => crate::models::Attributes::new(),
+    }
+}
+
 // Repository for database operations
 pub mod repository {
     use super::*;
+    use crate::announcements;
+    use crate::bells;
+    use crate::calendar;
+    use crate::conflicts;
+    use crate::emergency;
     use crate::error::{AppError, AppResult};
+    use crate::events;
+    use crate::ical;
+    use crate::import;
     use crate::models::*;
-    use chrono::Utc;
-    use sqlx::Row;
+    use crate::printing::{self, TimetableGrid};
+    use crate::rooms;
+    use crate::semester;
+    use crate::settings;
+    use crate::spreadsheet;
+    use crate::templates;
+    use crate::timetable;
+    use crate::validation;
+    use crate::weeks;
+    use chrono::{Local, NaiveDate, NaiveTime, Utc};
+    use sqlx::{PgConnection, Row};
+    use std::collections::{BTreeSet, HashMap, HashSet};
 
     pub struct Repository {
         pool: DbPool,
     }
 
+    /// Clients within a scope of client and group IDs (every client when both are empty),
+    /// ordered by ID.
+    fn scoped_clients(
+        client_ids: &[i32],
+        group_ids: &[i32],
+        clients: &[Client],
+        groups: &[ClientGroup],
+    ) -> Vec<Client> {
+        let mut scoped: Vec<Client> = clients
+            .iter()
+            .filter(|c| announcements::in_scope(client_ids, group_ids, c.id, groups))
+            .cloned()
+            .collect();
+        scoped.sort_by_key(|c| c.id);
+        scoped
+    }
+
+    /// Courses of a client, read through `executor` so a transaction sees its own writes.
+    async fn fetch_client_courses<'e, E: sqlx::PgExecutor<'e>>(
+        executor: E,
+        client_id: i32,
+    ) -> AppResult<Vec<Course>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM courses WHERE client_id = $1 ORDER BY name",
+            COURSE_COLUMNS
+        ))
+        .bind(client_id)
+        .fetch_all(executor)
+        .await?;
+
+        Ok(rows.iter().map(course_from_row).collect())
+    }
+
+    async fn fetch_client_schedule<'e, E: sqlx::PgExecutor<'e>>(
+        executor: E,
+        client_id: i32,
+    ) -> AppResult<Vec<ScheduleEntry>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM schedule_entries se
+             JOIN courses c ON se.course_id = c.id
+             WHERE {}
+             ORDER BY se.day_of_week, se.start_time",
+            SCHEDULE_ENTRY_COLUMNS, SCHEDULE_FILTER_CONDITION
+        ))
+        .bind(client_id)
+        .bind(None::<i32>)
+        .bind(None::<i32>)
+        .fetch_all(executor)
+        .await?;
+
+        Ok(rows.iter().map(schedule_entry_from_row).collect())
+    }
+
+    /// Current courses and schedule entries of a client, in sync payload form.
+    async fn fetch_client_timetable(
+        conn: &mut PgConnection,
+        client_id: i32,
+    ) -> AppResult<(Vec<ClientCourse>, Vec<ClientScheduleEntry>)> {
+        let courses = fetch_client_courses(&mut *conn, client_id).await?;
+        let entries = fetch_client_schedule(&mut *conn, client_id).await?;
+
+        Ok((
+            timetable::sort_courses(timetable::to_client_courses(&courses)),
+            timetable::sort_entries(timetable::to_client_entries(&courses, &entries)),
+        ))
+    }
+
+    /// Locks the client row until the transaction ends. Every writer of a client's
+    /// timetable takes it first, so syncs, imports, template publishes, rollbacks,
+    /// central edits and snapshot versions of one client happen one at a time.
+    async fn lock_client(conn: &mut PgConnection, client_id: i32) -> AppResult<()> {
+        sqlx::query("SELECT id FROM clients WHERE id = $1 FOR UPDATE")
+            .bind(client_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Client not found".to_string()))?;
+
+        Ok(())
+    }
+
+    /// Marks courses and entries as maintained on the server, so client syncs leave them alone.
+    async fn mark_server_owned(
+        conn: &mut PgConnection,
+        client_id: i32,
+        course_ids: &[i32],
+        entry_ids: &[i32],
+    ) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE courses SET origin = $1
+             WHERE client_id = $2 AND course_id_on_client = ANY($3)",
+        )
+        .bind(validation::ORIGIN_SERVER)
+        .bind(client_id)
+        .bind(course_ids)
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(
+            "UPDATE schedule_entries SET origin = $1
+             WHERE client_id = $2 AND entry_id_on_client = ANY($3)",
+        )
+        .bind(validation::ORIGIN_SERVER)
+        .bind(client_id)
+        .bind(entry_ids)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records courses and entries deleted on the server, so stale client syncs do not add
+    /// them back.
+    async fn record_deleted(
+        conn: &mut PgConnection,
+        client_id: i32,
+        course_ids: &[i32],
+        entry_ids: &[i32],
+    ) -> AppResult<()> {
+        for (item_type, ids) in [
+            (templates::ITEM_COURSE, course_ids),
+            (templates::ITEM_ENTRY, entry_ids),
+        ] {
+            sqlx::query(
+                "INSERT INTO deleted_timetable_items (client_id, item_type, item_id)
+                 SELECT $1, $2, UNNEST($3::INTEGER[])
+                 ON CONFLICT (client_id, item_type, item_id)
+                 DO UPDATE SET deleted_at = CURRENT_TIMESTAMP",
+            )
+            .bind(client_id)
+            .bind(item_type)
+            .bind(ids)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stores the client's current timetable as its next version. Call inside a transaction.
+    async fn insert_timetable_snapshot(
+        conn: &mut PgConnection,
+        client_id: i32,
+        source: &str,
+        skip_if_unchanged: bool,
+    ) -> AppResult<Option<TimetableSnapshotSummary>> {
+        lock_client(&mut *conn, client_id).await?;
+        let existing_courses = fetch_client_courses(&mut *conn, client_id).await?;
+        let existing_entries = fetch_client_schedule(&mut *conn, client_id).await?;
+        let courses = timetable::sort_courses(timetable::to_client_courses(&existing_courses));
+        let entries = timetable::sort_entries(timetable::to_client_entries(
+            &existing_courses,
+            &existing_entries,
+        ));
+
+        let latest = sqlx::query(
+            "SELECT version, courses, schedule_entries FROM timetable_snapshots
+             WHERE client_id = $1 ORDER BY version DESC LIMIT 1",
+        )
+        .bind(client_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let mut version = 1;
+        if let Some(latest) = latest {
+            version = latest.get::<i32, _>("version") + 1;
+            if skip_if_unchanged {
+                let latest_courses: Vec<ClientCourse> =
+                    serde_json::from_value(latest.get("courses")).unwrap_or_default();
+                let latest_entries: Vec<ClientScheduleEntry> =
+                    serde_json::from_value(latest.get("schedule_entries")).unwrap_or_default();
+                if timetable::sort_courses(latest_courses) == courses
+                    && timetable::sort_entries(latest_entries) == entries
+                {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let server_course_ids: Vec<i32> = existing_courses
+            .iter()
+            .filter(|c| c.origin == validation::ORIGIN_SERVER)
+            .map(|c| c.course_id_on_client)
+            .collect();
+        let server_entry_ids: Vec<i32> = existing_entries
+            .iter()
+            .filter(|e| e.origin == validation::ORIGIN_SERVER)
+            .map(|e| e.entry_id_on_client)
+            .collect();
+
+        let row = sqlx::query(
+            "INSERT INTO timetable_snapshots
+                (client_id, version, source, courses, schedule_entries,
+                 server_course_ids, server_entry_ids)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, created_at",
+        )
+        .bind(client_id)
+        .bind(version)
+        .bind(source)
+        .bind(serde_json::to_value(&courses).unwrap_or_default())
+        .bind(serde_json::to_value(&entries).unwrap_or_default())
+        .bind(&server_course_ids)
+        .bind(&server_entry_ids)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        Ok(Some(TimetableSnapshotSummary {
+            id: row.get("id"),
+            client_id,
+            version,
+            source: source.to_string(),
+            courses_count: courses.len() as i32,
+            entries_count: entries.len() as i32,
+            created_at: row.get("created_at"),
+        }))
+    }
+
     impl Repository {
         pub fn new(pool: DbPool) -> Self {
             Self { pool }
@@ -61,7 +677,7 @@ pub mod repository {
         pub async fn get_all_clients(&self) -> AppResult<Vec<Client>> {
             let rows = sqlx::query(
                 "SELECT id, uuid, name, description, api_url, api_key,
-                        last_sync, status, created_at
+                        last_sync, status, created_at, room_id
                  FROM clients ORDER BY created_at DESC",
             )
             .fetch_all(&self.pool)
@@ -79,6 +695,7 @@ pub mod repository {
                     last_sync: row.try_get::<NaiveDateTime, _>("last_sync").ok(),
                     status: row.get("status"),
                     created_at: row.get("created_at"),
+                    room_id: row.try_get("room_id").ok(),
                 })
                 .collect();
 
@@ -88,7 +705,7 @@ pub mod repository {
         pub async fn get_client_by_id(&self, id: i32) -> AppResult<Client> {
             let row = sqlx::query(
                 "SELECT id, uuid, name, description, api_url, api_key,
-                        last_sync, status, created_at
+                        last_sync, status, created_at, room_id
                  FROM clients WHERE id = $1",
             )
             .bind(id)
@@ -106,6 +723,7 @@ pub mod repository {
                     last_sync: row.try_get::<NaiveDateTime, _>("last_sync").ok(),
                     status: row.get("status"),
                     created_at: row.get("created_at"),
+                    room_id: row.try_get("room_id").ok(),
                 }),
                 None => Err(AppError::NotFound("Client not found".to_string())),
             }
@@ -114,7 +732,7 @@ pub mod repository {
         pub async fn get_client_by_uuid(&self, uuid: &str) -> AppResult<Client> {
             let row = sqlx::query(
                 "SELECT id, uuid, name, description, api_url, api_key,
-                        last_sync, status, created_at
+                        last_sync, status, created_at, room_id
                  FROM clients WHERE uuid = $1",
             )
             .bind(uuid)
@@ -132,6 +750,7 @@ pub mod repository {
                     last_sync: row.try_get::<NaiveDateTime, _>("last_sync").ok(),
                     status: row.get("status"),
                     created_at: row.get("created_at"),
+                    room_id: row.try_get("room_id").ok(),
                 }),
                 None => Err(AppError::NotFound("Client not found".to_string())),
             }
@@ -141,7 +760,7 @@ pub mod repository {
             let row = sqlx::query(
                 "INSERT INTO clients (uuid, name, description, api_url, api_key, status)
                  VALUES ($1, $2, $3, $4, $5, 'offline')
-                 RETURNING id, uuid, name, description, api_url, api_key, last_sync, status, created_at"
+                 RETURNING id, uuid, name, description, api_url, api_key, last_sync, status, created_at, room_id"
             )
             .bind(&client.uuid)
             .bind(&client.name)
@@ -161,6 +780,7 @@ pub mod repository {
                 last_sync: None,
                 status: row.get("status"),
                 created_at: row.get("created_at"),
+                room_id: row.try_get("room_id").ok(),
             })
         }
 
@@ -211,254 +831,3990 @@ pub mod repository {
 
             let result = q.execute(&self.pool).await?;
 
-            if result.rows_affected() == 0 {
-                return Err(AppError::NotFound("Client not found".to_string()));
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound("Client not found".to_string()));
+            }
+
+            Ok(())
+        }
+
+        pub async fn delete_client(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM clients WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound("Client not found".to_string()));
+            }
+
+            Ok(())
+        }
+
+        pub async fn update_client_status(&self, id: i32, status: &str) -> AppResult<()> {
+            sqlx::query("UPDATE clients SET status = $1 WHERE id = $2")
+                .bind(status)
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            Ok(())
+        }
+
+        pub async fn update_client_last_sync(&self, id: i32) -> AppResult<()> {
+            sqlx::query("UPDATE clients SET last_sync = $1 WHERE id = $2")
+                .bind(Utc::now().naive_utc())
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            Ok(())
+        }
+
+        // Sync operations
+        pub async fn sync_client_data(
+            &self,
+            client_uuid: &str,
+            courses: Vec<ClientCourse>,
+            entries: Vec<ClientScheduleEntry>,
+            payload_bytes: i32,
+        ) -> AppResult<SyncResponse> {
+            let client = self.get_client_by_uuid(client_uuid).await?;
+            let client_id = client.id;
+
+            // Validation and all upserts commit together, or not at all
+            let result = async {
+                let mut tx = self.pool.begin().await?;
+                lock_client(&mut tx, client_id).await?;
+                let outcome = self
+                    .validate_and_apply_sync(
+                        &mut tx,
+                        client_id,
+                        courses,
+                        entries,
+                        validation::ORIGIN_CLIENT,
+                    )
+                    .await?;
+                tx.commit().await?;
+                Ok::<_, AppError>(outcome)
+            }
+            .await;
+            let (synced_courses, synced_entries, rejected, kept_server_version) = match result {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    // Record the failure so it shows up in the sync history
+                    self.insert_sync_log(
+                        client_id,
+                        "failed",
+                        0,
+                        0,
+                        Some(&e.to_string()),
+                        payload_bytes,
+                    )
+                    .await
+                    .ok();
+                    self.update_client_status(client_id, "error").await.ok();
+                    return Err(e);
+                }
+            };
+
+            // Update client last sync time and status
+            self.update_client_last_sync(client_id).await?;
+            self.update_client_status(client_id, "online").await?;
+
+            // Keep a version of the resulting timetable; the sync itself has already succeeded
+            if let Err(e) = self
+                .create_timetable_snapshot(client_id, "sync", true)
+                .await
+            {
+                tracing::warn!(
+                    "Failed to snapshot timetable of client {}: {}",
+                    client_id,
+                    e
+                );
+            }
+
+            let (status, error_message, message) = if rejected.is_empty() {
+                ("success", None, "Data synced successfully".to_string())
+            } else {
+                (
+                    "partial",
+                    Some(validation::describe(&rejected)),
+                    format!("Data synced with {} rejected items", rejected.len()),
+                )
+            };
+
+            // Log sync
+            self.insert_sync_log(
+                client_id,
+                status,
+                synced_courses,
+                synced_entries,
+                error_message.as_deref(),
+                payload_bytes,
+            )
+            .await?;
+
+            let semester = self
+                .get_active_semester()
+                .await?
+                .map(|s| semester::week_info(&s, Local::now().date_naive()));
+            let conflicts = self
+                .get_schedule_conflicts(&ConflictFilter {
+                    client_id: Some(client_id),
+                    kind: None,
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to check conflicts of client {}: {}", client_id, e);
+                    Vec::new()
+                });
+
+            Ok(SyncResponse {
+                success: true,
+                message,
+                synced_courses,
+                synced_entries,
+                rejected,
+                kept_server_version,
+                semester,
+                conflicts,
+            })
+        }
+
+        /// Validates the payload against the client's stored data and applies the valid part.
+        /// `origin` is who writes it: a client sync, or the server (imports), whose rows
+        /// client syncs no longer overwrite.
+        async fn validate_and_apply_sync(
+            &self,
+            conn: &mut PgConnection,
+            client_id: i32,
+            courses: Vec<ClientCourse>,
+            entries: Vec<ClientScheduleEntry>,
+            origin: &str,
+        ) -> AppResult<(i32, i32, Vec<SyncRejection>, Vec<KeptServerItem>)> {
+            let validated = self
+                .validate_sync(&mut *conn, client_id, courses, entries, origin)
+                .await?;
+            let mut rejected = validated.rejected;
+            let course_ids: Vec<i32> = validated.courses.iter().map(|c| c.id).collect();
+            let entry_ids: Vec<i32> = validated.entries.iter().map(|e| e.id).collect();
+
+            let (synced_courses, synced_entries, skipped_entries) = self
+                .apply_client_sync(&mut *conn, client_id, validated.courses, validated.entries)
+                .await?;
+            if origin == validation::ORIGIN_SERVER {
+                mark_server_owned(conn, client_id, &course_ids, &entry_ids).await?;
+            }
+            rejected.extend(skipped_entries.into_iter().map(|id| SyncRejection {
+                item_type: "schedule_entry".to_string(),
+                id,
+                field: "course_id".to_string(),
+                reason: "course not found".to_string(),
+            }));
+
+            Ok((
+                synced_courses,
+                synced_entries,
+                rejected,
+                validated.kept_server_version,
+            ))
+        }
+
+        async fn validate_sync(
+            &self,
+            conn: &mut PgConnection,
+            client_id: i32,
+            courses: Vec<ClientCourse>,
+            entries: Vec<ClientScheduleEntry>,
+            origin: &str,
+        ) -> AppResult<validation::ValidatedSync> {
+            let existing_courses = fetch_client_courses(&mut *conn, client_id).await?;
+            let existing_course_ids = existing_courses
+                .iter()
+                .map(|c| c.course_id_on_client)
+                .collect();
+            let existing_entries = fetch_client_schedule(&mut *conn, client_id).await?;
+            let max_week = self.get_semester_weeks().await;
+
+            let mut validated = validation::validate_sync_payload(
+                courses,
+                entries,
+                &existing_course_ids,
+                &existing_entries,
+                max_week,
+            );
+
+            if origin == validation::ORIGIN_SERVER {
+                return Ok(validated);
+            }
+
+            // Courses and entries maintained on the server win over the client's copy
+            let server_course_ids = existing_courses
+                .iter()
+                .filter(|c| c.origin == validation::ORIGIN_SERVER)
+                .map(|c| c.course_id_on_client)
+                .collect();
+            let server_entry_ids = existing_entries
+                .iter()
+                .filter(|e| e.origin == validation::ORIGIN_SERVER)
+                .map(|e| e.entry_id_on_client)
+                .collect();
+            validation::skip_server_owned(&mut validated, &server_course_ids, &server_entry_ids);
+
+            // Items deleted on the server stay deleted until the server adds them back
+            let existing_entry_ids: HashSet<i32> = existing_entries
+                .iter()
+                .map(|e| e.entry_id_on_client)
+                .collect();
+            let mut deleted_course_ids = HashSet::new();
+            let mut deleted_entry_ids = HashSet::new();
+            let rows = sqlx::query(
+                "SELECT item_type, item_id FROM deleted_timetable_items WHERE client_id = $1",
+            )
+            .bind(client_id)
+            .fetch_all(&mut *conn)
+            .await?;
+            for row in rows {
+                let id: i32 = row.get("item_id");
+                if row.get::<String, _>("item_type") == templates::ITEM_COURSE {
+                    if !existing_course_ids.contains(&id) {
+                        deleted_course_ids.insert(id);
+                    }
+                } else if !existing_entry_ids.contains(&id) {
+                    deleted_entry_ids.insert(id);
+                }
+            }
+            validation::skip_server_deleted(
+                &mut validated,
+                &deleted_course_ids,
+                &deleted_entry_ids,
+            );
+
+            Ok(validated)
+        }
+
+        /// Number of weeks in the active semester, or the `semester_weeks` setting without one.
+        pub async fn get_semester_weeks(&self) -> i32 {
+            if let Ok(Some(semester)) = self.get_active_semester().await {
+                return semester.week_count;
+            }
+            self.get_setting("semester_weeks")
+                .await
+                .ok()
+                .and_then(|s| s.value.parse().ok())
+                .filter(|weeks| (1..=validation::MAX_WEEK_NUMBER).contains(weeks))
+                .unwrap_or(weeks::DEFAULT_SEMESTER_WEEKS)
+        }
+
+        /// Works out what `sync_client_data` would write, without writing anything.
+        pub async fn preview_client_sync(
+            &self,
+            client_uuid: &str,
+            courses: Vec<ClientCourse>,
+            entries: Vec<ClientScheduleEntry>,
+            origin: &str,
+        ) -> AppResult<SyncPreview> {
+            let client = self.get_client_by_uuid(client_uuid).await?;
+            let mut conn = self.pool.acquire().await?;
+            let validated = self
+                .validate_sync(&mut conn, client.id, courses, entries, origin)
+                .await?;
+            let (existing_courses, existing_entries) =
+                fetch_client_timetable(&mut conn, client.id).await?;
+            let plan = timetable::plan_sync(
+                &existing_courses,
+                &existing_entries,
+                validated.courses,
+                validated.entries,
+            );
+
+            let mut course_preview = CourseSyncPreview::default();
+            for action in plan.courses {
+                match action {
+                    timetable::Upsert::Insert(course) => course_preview.inserted.push(course),
+                    timetable::Upsert::Update { before, after } if before == after => {
+                        course_preview.unchanged.push(after.id)
+                    }
+                    timetable::Upsert::Update { before, after } => {
+                        course_preview.updated.push(CourseChange { before, after })
+                    }
+                }
+            }
+
+            let mut entry_preview = ScheduleEntrySyncPreview {
+                skipped: plan.skipped_entries,
+                ..Default::default()
+            };
+            for action in plan.entries {
+                match action {
+                    timetable::Upsert::Insert(entry) => entry_preview.inserted.push(entry),
+                    timetable::Upsert::Update { before, after } if before == after => {
+                        entry_preview.unchanged.push(after.id)
+                    }
+                    timetable::Upsert::Update { before, after } => entry_preview
+                        .updated
+                        .push(ScheduleEntryChange { before, after }),
+                }
+            }
+
+            Ok(SyncPreview {
+                client_id: client.id,
+                courses: course_preview,
+                schedule_entries: entry_preview,
+                invalid: validated.rejected,
+                kept_server_version: validated.kept_server_version,
+            })
+        }
+
+        /// Upserts courses and schedule entries for a client.
+        /// Returns the synced counts and the client IDs of skipped entries.
+        async fn apply_client_sync(
+            &self,
+            conn: &mut PgConnection,
+            client_id: i32,
+            courses: Vec<ClientCourse>,
+            entries: Vec<ClientScheduleEntry>,
+        ) -> AppResult<(i32, i32, Vec<i32>)> {
+            let (existing_courses, existing_entries) =
+                fetch_client_timetable(&mut *conn, client_id).await?;
+            let plan = timetable::plan_sync(&existing_courses, &existing_entries, courses, entries);
+
+            let synced_courses = plan.courses.len() as i32;
+            let synced_entries = plan.entries.len() as i32;
+            let skipped_entries = plan.skipped_entries.iter().map(|e| e.id).collect();
+
+            // Sync courses
+            for action in plan.courses {
+                match action {
+                    timetable::Upsert::Update { after: course, .. } => {
+                        // Update existing course
+                        sqlx::query(
+                            "UPDATE courses SET name = $1, teacher = $2, location = $3, color = $4, note = $5,
+                                    attributes = $6, synced_at = $7
+                             WHERE client_id = $8 AND course_id_on_client = $9"
+                        )
+                        .bind(&course.name)
+                        .bind(&course.teacher)
+                        .bind(&course.location)
+                        .bind(&course.color)
+                        .bind(&course.note)
+                        .bind(serde_json::Value::Object(course.attributes))
+                        .bind(Utc::now().naive_utc())
+                        .bind(client_id)
+                        .bind(course.id)
+                        .execute(&mut *conn)
+                        .await?;
+                    }
+                    timetable::Upsert::Insert(course) => {
+                        // Insert new course
+                        sqlx::query(
+                            "INSERT INTO courses (client_id, course_id_on_client, name, teacher, location, color, note, attributes)
+                             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+                        )
+                        .bind(client_id)
+                        .bind(course.id)
+                        .bind(&course.name)
+                        .bind(&course.teacher)
+                        .bind(&course.location)
+                        .bind(&course.color)
+                        .bind(&course.note)
+                        .bind(serde_json::Value::Object(course.attributes))
+                        .execute(&mut *conn)
+                        .await?;
+                    }
+                }
+            }
+
+            // Map client course IDs to our database IDs
+            let course_ids: HashMap<i32, i32> = fetch_client_courses(&mut *conn, client_id)
+                .await?
+                .iter()
+                .map(|c| (c.course_id_on_client, c.id))
+                .collect();
+
+            // Sync schedule entries
+            for action in plan.entries {
+                let (entry, exists) = match action {
+                    timetable::Upsert::Update { after, .. } => (after, true),
+                    timetable::Upsert::Insert(entry) => (entry, false),
+                };
+                let course_id = course_ids.get(&entry.course_id).copied().ok_or_else(|| {
+                    AppError::Internal(format!("Course {} missing after sync", entry.course_id))
+                })?;
+
+                if exists {
+                    // Update existing entry; explicit new times end period-based scheduling
+                    sqlx::query(
+                        "UPDATE schedule_entries
+                         SET course_id = $1, day_of_week = $2, start_time = $3, end_time = $4, weeks = $5,
+                             weeks_legacy = NULL, note = $6, room = $7, attributes = $8, synced_at = $9,
+                             period_start = CASE WHEN start_time = $3 AND end_time = $4 THEN period_start END,
+                             period_end = CASE WHEN start_time = $3 AND end_time = $4 THEN period_end END
+                         WHERE client_id = $10 AND entry_id_on_client = $11"
+                    )
+                    .bind(course_id)
+                    .bind(entry.day_of_week)
+                    .bind(&entry.start_time)
+                    .bind(&entry.end_time)
+                    .bind(&entry.weeks)
+                    .bind(&entry.note)
+                    .bind(&entry.room)
+                    .bind(serde_json::Value::Object(entry.attributes))
+                    .bind(Utc::now().naive_utc())
+                    .bind(client_id)
+                    .bind(entry.id)
+                    .execute(&mut *conn)
+                    .await?;
+                } else {
+                    // Insert new entry
+                    sqlx::query(
+                        "INSERT INTO schedule_entries (client_id, entry_id_on_client, course_id, day_of_week, start_time, end_time, weeks, note, room, attributes)
+                         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"
+                    )
+                    .bind(client_id)
+                    .bind(entry.id)
+                    .bind(course_id)
+                    .bind(entry.day_of_week)
+                    .bind(&entry.start_time)
+                    .bind(&entry.end_time)
+                    .bind(&entry.weeks)
+                    .bind(&entry.note)
+                    .bind(&entry.room)
+                    .bind(serde_json::Value::Object(entry.attributes))
+                    .execute(&mut *conn)
+                    .await?;
+                }
+            }
+
+            Ok((synced_courses, synced_entries, skipped_entries))
+        }
+
+        async fn insert_sync_log(
+            &self,
+            client_id: i32,
+            status: &str,
+            courses_count: i32,
+            entries_count: i32,
+            error_message: Option<&str>,
+            payload_bytes: i32,
+        ) -> AppResult<()> {
+            sqlx::query(
+                "INSERT INTO sync_logs (client_id, sync_type, status, courses_count, entries_count, error_message, payload_bytes)
+                 VALUES ($1, 'full', $2, $3, $4, $5, $6)",
+            )
+            .bind(client_id)
+            .bind(status)
+            .bind(courses_count)
+            .bind(entries_count)
+            .bind(error_message)
+            .bind(payload_bytes)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        }
+
+        // Timetable snapshots
+        /// Current courses and schedule entries of a client, in sync payload form.
+        pub async fn get_client_timetable(
+            &self,
+            client_id: i32,
+        ) -> AppResult<(Vec<ClientCourse>, Vec<ClientScheduleEntry>)> {
+            let mut conn = self.pool.acquire().await?;
+            fetch_client_timetable(&mut conn, client_id).await
+        }
+
+        /// Stores the client's current timetable as a new version.
+        /// With `skip_if_unchanged`, nothing is stored when it equals the latest version.
+        pub async fn create_timetable_snapshot(
+            &self,
+            client_id: i32,
+            source: &str,
+            skip_if_unchanged: bool,
+        ) -> AppResult<Option<TimetableSnapshotSummary>> {
+            let mut tx = self.pool.begin().await?;
+            let summary =
+                insert_timetable_snapshot(&mut tx, client_id, source, skip_if_unchanged).await?;
+            tx.commit().await?;
+
+            Ok(summary)
+        }
+
+        pub async fn get_timetable_snapshots_paginated(
+            &self,
+            client_id: i32,
+            offset: i64,
+            limit: i64,
+        ) -> AppResult<(Vec<TimetableSnapshotSummary>, i64)> {
+            let count_row = sqlx::query(
+                "SELECT COUNT(*) as count FROM timetable_snapshots WHERE client_id = $1",
+            )
+            .bind(client_id)
+            .fetch_one(&self.pool)
+            .await?;
+            let total: i64 = count_row.get("count");
+
+            let rows = sqlx::query(
+                "SELECT id, client_id, version, source,
+                        jsonb_array_length(courses) as courses_count,
+                        jsonb_array_length(schedule_entries) as entries_count,
+                        created_at
+                 FROM timetable_snapshots
+                 WHERE client_id = $1
+                 ORDER BY version DESC
+                 LIMIT $2 OFFSET $3",
+            )
+            .bind(client_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let snapshots = rows
+                .iter()
+                .map(|row| TimetableSnapshotSummary {
+                    id: row.get("id"),
+                    client_id: row.get("client_id"),
+                    version: row.get("version"),
+                    source: row.get("source"),
+                    courses_count: row.get("courses_count"),
+                    entries_count: row.get("entries_count"),
+                    created_at: row.get("created_at"),
+                })
+                .collect();
+
+            Ok((snapshots, total))
+        }
+
+        pub async fn get_timetable_snapshot(
+            &self,
+            client_id: i32,
+            version: i32,
+        ) -> AppResult<TimetableSnapshot> {
+            let row = sqlx::query(
+                "SELECT id, client_id, version, source, courses, schedule_entries, created_at
+                 FROM timetable_snapshots
+                 WHERE client_id = $1 AND version = $2",
+            )
+            .bind(client_id)
+            .bind(version)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            match row {
+                Some(row) => Ok(TimetableSnapshot {
+                    id: row.get("id"),
+                    client_id: row.get("client_id"),
+                    version: row.get("version"),
+                    source: row.get("source"),
+                    courses: serde_json::from_value(row.get("courses")).map_err(|e| {
+                        AppError::Internal(format!("Corrupted snapshot courses: {}", e))
+                    })?,
+                    schedule_entries: serde_json::from_value(row.get("schedule_entries")).map_err(
+                        |e| AppError::Internal(format!("Corrupted snapshot entries: {}", e)),
+                    )?,
+                    created_at: row.get("created_at"),
+                }),
+                None => Err(AppError::NotFound(format!(
+                    "Snapshot version {} not found",
+                    version
+                ))),
+            }
+        }
+
+        /// Replaces the client's timetable with a stored version and records it as a new one.
+        pub async fn restore_timetable_snapshot(
+            &self,
+            client_id: i32,
+            version: i32,
+        ) -> AppResult<(TimetableSnapshot, TimetableSnapshotSummary)> {
+            let snapshot = self.get_timetable_snapshot(client_id, version).await?;
+
+            let course_ids: Vec<i32> = snapshot.courses.iter().map(|c| c.id).collect();
+            let entry_ids: Vec<i32> = snapshot.schedule_entries.iter().map(|e| e.id).collect();
+
+            let mut tx = self.pool.begin().await?;
+            lock_client(&mut tx, client_id).await?;
+
+            // Remove what the snapshot does not contain
+            let removed_entries: Vec<i32> = sqlx::query_scalar(
+                "DELETE FROM schedule_entries
+                 WHERE client_id = $1 AND NOT (entry_id_on_client = ANY($2))
+                 RETURNING entry_id_on_client",
+            )
+            .bind(client_id)
+            .bind(&entry_ids)
+            .fetch_all(&mut *tx)
+            .await?;
+            let removed_courses: Vec<i32> = sqlx::query_scalar(
+                "DELETE FROM courses
+                 WHERE client_id = $1 AND NOT (course_id_on_client = ANY($2))
+                 RETURNING course_id_on_client",
+            )
+            .bind(client_id)
+            .bind(&course_ids)
+            .fetch_all(&mut *tx)
+            .await?;
+            record_deleted(&mut tx, client_id, &removed_courses, &removed_entries).await?;
+
+            self.apply_client_sync(
+                &mut tx,
+                client_id,
+                snapshot.courses.clone(),
+                snapshot.schedule_entries.clone(),
+            )
+            .await?;
+
+            // Give items back the ownership they had; older snapshots did not record it
+            let origins = sqlx::query(
+                "SELECT server_course_ids, server_entry_ids FROM timetable_snapshots
+                 WHERE client_id = $1 AND version = $2",
+            )
+            .bind(client_id)
+            .bind(version)
+            .fetch_one(&mut *tx)
+            .await?;
+            let server_course_ids: Option<Vec<i32>> = origins.get("server_course_ids");
+            let server_entry_ids: Option<Vec<i32>> = origins.get("server_entry_ids");
+            if let (Some(server_course_ids), Some(server_entry_ids)) =
+                (server_course_ids, server_entry_ids)
+            {
+                sqlx::query(
+                    "UPDATE courses
+                     SET origin = CASE WHEN course_id_on_client = ANY($2) THEN $3 ELSE $4 END
+                     WHERE client_id = $1",
+                )
+                .bind(client_id)
+                .bind(&server_course_ids)
+                .bind(validation::ORIGIN_SERVER)
+                .bind(validation::ORIGIN_CLIENT)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query(
+                    "UPDATE schedule_entries
+                     SET origin = CASE WHEN entry_id_on_client = ANY($2) THEN $3 ELSE $4 END
+                     WHERE client_id = $1",
+                )
+                .bind(client_id)
+                .bind(&server_entry_ids)
+                .bind(validation::ORIGIN_SERVER)
+                .bind(validation::ORIGIN_CLIENT)
+                .execute(&mut *tx)
+                .await?;
+            }
+
+            let summary = insert_timetable_snapshot(&mut tx, client_id, "rollback", false)
+                .await?
+                .ok_or_else(|| AppError::Internal("Failed to record snapshot".to_string()))?;
+            tx.commit().await?;
+
+            Ok((snapshot, summary))
+        }
+
+        // Timetable import
+        /// Parses an .ics or CSV timetable, maps its classes onto clients and, unless
+        /// `dry_run`, upserts them the way a client sync would.
+        pub async fn import_timetable(
+            &self,
+            request: ImportRequest,
+            dry_run: bool,
+        ) -> AppResult<ImportReport> {
+            if !import::MAP_BY_OPTIONS.contains(&request.map_by.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "map_by must be one of {}",
+                    import::MAP_BY_OPTIONS.join(", ")
+                )));
+            }
+            let (classes, mut errors) = match request.format.as_str() {
+                import::FORMAT_ICS => {
+                    let semester = self.get_active_semester().await?.ok_or_else(|| {
+                        AppError::BadRequest(
+                            "An active semester is required to convert dates to weeks".to_string(),
+                        )
+                    })?;
+                    import::parse_ics(&request.content, &semester, &request.map_by)
+                }
+                import::FORMAT_CSV => import::parse_csv(&request.content, &request.map_by),
+                _ => {
+                    return Err(AppError::BadRequest(format!(
+                        "format must be one of {}",
+                        import::IMPORT_FORMATS.join(", ")
+                    )))
+                }
+            };
+
+            // Explicit mapping first, then a client named or described like the key
+            let clients = self.get_all_clients().await?;
+            let mut by_client: Vec<(Client, Vec<String>, Vec<import::ImportedClass>)> = Vec::new();
+            let mut unmapped = Vec::new();
+            let parsed = classes.len();
+            for class in classes {
+                let Some(key) = class.client_key.clone() else {
+                    unmapped.push(ImportIssue {
+                        source: class.source.clone(),
+                        reason: format!(
+                            "'{}' has no {} to map by",
+                            class.course_name, request.map_by
+                        ),
+                    });
+                    continue;
+                };
+                let client = match request.mapping.get(&key) {
+                    Some(id) => clients.iter().find(|c| c.id == *id),
+                    None => clients.iter().find(|c| {
+                        c.name.trim().eq_ignore_ascii_case(key.trim())
+                            || c.description
+                                .as_deref()
+                                .is_some_and(|d| d.trim().eq_ignore_ascii_case(key.trim()))
+                    }),
+                };
+                let Some(client) = client else {
+                    unmapped.push(ImportIssue {
+                        source: class.source.clone(),
+                        reason: format!("no client matches {} '{}'", request.map_by, key),
+                    });
+                    continue;
+                };
+
+                match by_client.iter_mut().find(|(c, _, _)| c.id == client.id) {
+                    Some((_, keys, classes)) => {
+                        if !keys.contains(&key) {
+                            keys.push(key);
+                        }
+                        classes.push(class);
+                    }
+                    None => by_client.push((client.clone(), vec![key], vec![class])),
+                }
+            }
+
+            let mut results = Vec::new();
+            for (client, keys, classes) in by_client {
+                // Hold the client until its import is written; a dry run just rolls back
+                let mut tx = self.pool.begin().await?;
+                lock_client(&mut tx, client.id).await?;
+                let (existing_courses, existing_entries) =
+                    fetch_client_timetable(&mut tx, client.id).await?;
+                let payload =
+                    import::to_sync_payload(&classes, &existing_courses, &existing_entries);
+                let (courses, entries) = (payload.courses.clone(), payload.entries.clone());
+                let preview = self
+                    .preview_client_sync(
+                        &client.uuid,
+                        courses.clone(),
+                        entries.clone(),
+                        validation::ORIGIN_SERVER,
+                    )
+                    .await?;
+
+                let (rejected, snapshot_version) = if dry_run {
+                    let validated = self
+                        .validate_sync(
+                            &mut tx,
+                            client.id,
+                            courses,
+                            entries,
+                            validation::ORIGIN_SERVER,
+                        )
+                        .await?;
+                    (validated.rejected, None)
+                } else {
+                    let (_, _, rejected, _) = self
+                        .validate_and_apply_sync(
+                            &mut tx,
+                            client.id,
+                            courses,
+                            entries,
+                            validation::ORIGIN_SERVER,
+                        )
+                        .await?;
+                    let snapshot =
+                        insert_timetable_snapshot(&mut tx, client.id, "import", true).await?;
+                    tx.commit().await?;
+                    (rejected, snapshot.map(|s| s.version))
+                };
+                errors.extend(rejected.iter().map(|r| payload.issue(r)));
+
+                results.push(ImportClientResult {
+                    client_id: client.id,
+                    client_name: client.name,
+                    keys,
+                    classes: classes.len(),
+                    preview,
+                    snapshot_version,
+                    pushed: false,
+                });
+            }
+
+            Ok(ImportReport {
+                dry_run,
+                parsed,
+                clients: results,
+                unmapped,
+                errors,
+            })
+        }
+
+        // Timetable templates
+        pub async fn get_templates(&self) -> AppResult<Vec<TimetableTemplate>> {
+            let rows = sqlx::query("SELECT id FROM timetable_templates ORDER BY name")
+                .fetch_all(&self.pool)
+                .await?;
+
+            let mut templates = Vec::new();
+            for row in rows {
+                templates.push(self.get_template_by_id(row.get("id")).await?);
+            }
+            Ok(templates)
+        }
+
+        pub async fn get_template_by_id(&self, id: i32) -> AppResult<TimetableTemplate> {
+            let row = sqlx::query(
+                "SELECT id, name, description, courses, schedule_entries, created_at, updated_at
+                 FROM timetable_templates WHERE id = $1",
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Template {} not found", id)))?;
+
+            let clients = sqlx::query(
+                "SELECT client_id, overrides, published_at FROM template_clients
+                 WHERE template_id = $1 ORDER BY client_id",
+            )
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|row| TemplateClient {
+                client_id: row.get("client_id"),
+                overrides: serde_json::from_value(row.get("overrides")).unwrap_or_default(),
+                published_at: row.try_get("published_at").ok(),
+            })
+            .collect();
+
+            Ok(TimetableTemplate {
+                id: row.get("id"),
+                name: row.get("name"),
+                description: row.try_get("description").ok(),
+                courses: serde_json::from_value(row.get("courses")).map_err(|e| {
+                    AppError::Internal(format!("Corrupted template courses: {}", e))
+                })?,
+                schedule_entries: serde_json::from_value(row.get("schedule_entries")).map_err(
+                    |e| AppError::Internal(format!("Corrupted template entries: {}", e)),
+                )?,
+                clients,
+                created_at: row.get("created_at"),
+                updated_at: row.get("updated_at"),
+            })
+        }
+
+        /// Checks the name and that the template would pass a client sync on its own.
+        async fn validate_template(
+            &self,
+            id: Option<i32>,
+            name: &str,
+            courses: &[ClientCourse],
+            entries: &[ClientScheduleEntry],
+        ) -> AppResult<()> {
+            if name.trim().is_empty() {
+                return Err(AppError::BadRequest("name: must not be empty".to_string()));
+            }
+            let duplicate = sqlx::query(
+                "SELECT id FROM timetable_templates WHERE name = $1 AND id IS DISTINCT FROM $2",
+            )
+            .bind(name)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+            if duplicate.is_some() {
+                return Err(AppError::BadRequest(format!(
+                    "name: template '{}' already exists",
+                    name
+                )));
+            }
+
+            let validated = validation::validate_sync_payload(
+                courses.to_vec(),
+                entries.to_vec(),
+                &Default::default(),
+                &[],
+                self.get_semester_weeks().await,
+            );
+            if !validated.rejected.is_empty() {
+                return Err(AppError::BadRequest(validation::describe(
+                    &validated.rejected,
+                )));
+            }
+            Ok(())
+        }
+
+        pub async fn create_template(
+            &self,
+            template: CreateTemplate,
+        ) -> AppResult<TimetableTemplate> {
+            let name = template.name.trim().to_string();
+            self.validate_template(None, &name, &template.courses, &template.schedule_entries)
+                .await?;
+
+            let row = sqlx::query(
+                "INSERT INTO timetable_templates (name, description, courses, schedule_entries)
+                 VALUES ($1, $2, $3, $4)
+                 RETURNING id",
+            )
+            .bind(&name)
+            .bind(&template.description)
+            .bind(serde_json::to_value(&template.courses).unwrap_or_default())
+            .bind(serde_json::to_value(&template.schedule_entries).unwrap_or_default())
+            .fetch_one(&self.pool)
+            .await?;
+
+            self.get_template_by_id(row.get("id")).await
+        }
+
+        /// Updates a template; linked clients change only when it is published.
+        pub async fn update_template(
+            &self,
+            id: i32,
+            update: UpdateTemplate,
+        ) -> AppResult<TimetableTemplate> {
+            let current = self.get_template_by_id(id).await?;
+            let name = update.name.map_or(current.name, |n| n.trim().to_string());
+            let courses = update.courses.unwrap_or(current.courses);
+            let entries = update.schedule_entries.unwrap_or(current.schedule_entries);
+            self.validate_template(Some(id), &name, &courses, &entries)
+                .await?;
+
+            sqlx::query(
+                "UPDATE timetable_templates
+                 SET name = $1, description = $2, courses = $3, schedule_entries = $4,
+                     updated_at = CURRENT_TIMESTAMP
+                 WHERE id = $5",
+            )
+            .bind(&name)
+            .bind(update.description.or(current.description))
+            .bind(serde_json::to_value(&courses).unwrap_or_default())
+            .bind(serde_json::to_value(&entries).unwrap_or_default())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+            self.get_template_by_id(id).await
+        }
+
+        /// Deletes a template; what it published stays on the clients as server-maintained data.
+        pub async fn delete_template(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM timetable_templates WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!("Template {} not found", id)));
+            }
+            Ok(())
+        }
+
+        /// Links a client to a template, or replaces the overrides of a linked client.
+        pub async fn link_template_client(
+            &self,
+            template_id: i32,
+            client_id: i32,
+            overrides: TemplateOverrides,
+        ) -> AppResult<TimetableTemplate> {
+            let template = self.get_template_by_id(template_id).await?;
+            self.get_client_by_id(client_id).await?;
+
+            let unknown_course = overrides
+                .courses
+                .iter()
+                .map(|o| o.id)
+                .find(|id| !template.courses.iter().any(|c| c.id == *id));
+            if let Some(id) = unknown_course {
+                return Err(AppError::BadRequest(format!(
+                    "courses: template has no course {}",
+                    id
+                )));
+            }
+            let unknown_entry = overrides
+                .schedule_entries
+                .iter()
+                .map(|o| o.id)
+                .chain(overrides.excluded_entries.iter().copied())
+                .find(|id| !template.schedule_entries.iter().any(|e| e.id == *id));
+            if let Some(id) = unknown_entry {
+                return Err(AppError::BadRequest(format!(
+                    "schedule_entries: template has no schedule entry {}",
+                    id
+                )));
+            }
+
+            sqlx::query(
+                "INSERT INTO template_clients (template_id, client_id, overrides)
+                 VALUES ($1, $2, $3)
+                 ON CONFLICT (template_id, client_id) DO UPDATE SET overrides = EXCLUDED.overrides",
+            )
+            .bind(template_id)
+            .bind(client_id)
+            .bind(serde_json::to_value(&overrides).unwrap_or_default())
+            .execute(&self.pool)
+            .await?;
+
+            self.get_template_by_id(template_id).await
+        }
+
+        /// Unlinks a client; what the template published stays on it as server-maintained data.
+        pub async fn unlink_template_client(
+            &self,
+            template_id: i32,
+            client_id: i32,
+        ) -> AppResult<()> {
+            let result = sqlx::query(
+                "DELETE FROM template_clients WHERE template_id = $1 AND client_id = $2",
+            )
+            .bind(template_id)
+            .bind(client_id)
+            .execute(&self.pool)
+            .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!(
+                    "Client {} is not linked to template {}",
+                    client_id, template_id
+                )));
+            }
+            Ok(())
+        }
+
+        async fn get_template_mapping(
+            &self,
+            template_id: i32,
+            client_id: i32,
+        ) -> AppResult<templates::ItemMapping> {
+            let rows = sqlx::query(
+                "SELECT item_type, template_item_id, client_item_id FROM template_items
+                 WHERE template_id = $1 AND client_id = $2",
+            )
+            .bind(template_id)
+            .bind(client_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let mut mapping = templates::ItemMapping::default();
+            for row in rows {
+                let item_type: String = row.get("item_type");
+                let ids = if item_type == templates::ITEM_COURSE {
+                    &mut mapping.courses
+                } else {
+                    &mut mapping.entries
+                };
+                ids.insert(row.get("template_item_id"), row.get("client_item_id"));
+            }
+            Ok(mapping)
+        }
+
+        /// Applies the template, with each client's overrides, to every linked client.
+        /// With `dry_run`, only reports the resulting per-client diff.
+        pub async fn publish_template(
+            &self,
+            id: i32,
+            dry_run: bool,
+        ) -> AppResult<TemplatePublishReport> {
+            let template = self.get_template_by_id(id).await?;
+            let max_week = self.get_semester_weeks().await;
+
+            let mut results = Vec::new();
+            for link in &template.clients {
+                let client = self.get_client_by_id(link.client_id).await?;
+                // Plan and write under the client lock; a dry run just rolls back
+                let mut tx = self.pool.begin().await?;
+                lock_client(&mut tx, client.id).await?;
+                let (existing_courses, existing_entries) =
+                    fetch_client_timetable(&mut tx, client.id).await?;
+                let previous = self.get_template_mapping(id, client.id).await?;
+
+                let (courses, entries) = templates::apply_overrides(
+                    &template.courses,
+                    &template.schedule_entries,
+                    &link.overrides,
+                );
+                let plan = templates::plan_publish(
+                    &existing_courses,
+                    &existing_entries,
+                    &previous,
+                    courses,
+                    entries,
+                );
+
+                // Validate against the rest of the client's timetable
+                let remaining_course_ids = existing_courses
+                    .iter()
+                    .map(|c| c.id)
+                    .filter(|id| !plan.removed_courses.contains(id))
+                    .collect();
+                let remaining_entries: Vec<ScheduleEntry> =
+                    fetch_client_schedule(&mut *tx, client.id)
+                        .await?
+                        .into_iter()
+                        .filter(|e| !plan.removed_entries.contains(&e.entry_id_on_client))
+                        .collect();
+                let validated = validation::validate_sync_payload(
+                    plan.courses,
+                    plan.entries,
+                    &remaining_course_ids,
+                    &remaining_entries,
+                    max_week,
+                );
+
+                let (after_courses, after_entries) = templates::published_timetable(
+                    &existing_courses,
+                    &existing_entries,
+                    &plan.removed_courses,
+                    &plan.removed_entries,
+                    &validated.courses,
+                    &validated.entries,
+                );
+
+                let snapshot_version = if dry_run {
+                    None
+                } else {
+                    let snapshot = self
+                        .apply_template_plan(
+                            &mut tx,
+                            id,
+                            client.id,
+                            (&previous, &plan.mapping),
+                            (&plan.removed_courses, &plan.removed_entries),
+                            (&validated.courses, &validated.entries),
+                        )
+                        .await?;
+                    tx.commit().await?;
+                    snapshot.map(|s| s.version)
+                };
+
+                results.push(TemplateClientResult {
+                    client_id: client.id,
+                    client_name: client.name,
+                    courses: timetable::diff_courses(&existing_courses, &after_courses),
+                    schedule_entries: timetable::diff_entries(&existing_entries, &after_entries),
+                    invalid: validated.rejected,
+                    snapshot_version,
+                    pushed: false,
+                });
+            }
+
+            Ok(TemplatePublishReport {
+                dry_run,
+                template_id: id,
+                clients: results,
+            })
+        }
+
+        /// Writes a validated template plan to one client, records the new item mapping and
+        /// snapshots the result, all within the caller's transaction.
+        async fn apply_template_plan(
+            &self,
+            conn: &mut PgConnection,
+            template_id: i32,
+            client_id: i32,
+            (previous, mapping): (&templates::ItemMapping, &templates::ItemMapping),
+            (removed_courses, removed_entries): (&[i32], &[i32]),
+            (courses, entries): (&[ClientCourse], &[ClientScheduleEntry]),
+        ) -> AppResult<Option<TimetableSnapshotSummary>> {
+            sqlx::query(
+                "DELETE FROM schedule_entries
+                 WHERE client_id = $1 AND entry_id_on_client = ANY($2)",
+            )
+            .bind(client_id)
+            .bind(removed_entries)
+            .execute(&mut *conn)
+            .await?;
+            sqlx::query(
+                "DELETE FROM courses
+                 WHERE client_id = $1 AND course_id_on_client = ANY($2)",
+            )
+            .bind(client_id)
+            .bind(removed_courses)
+            .execute(&mut *conn)
+            .await?;
+            record_deleted(&mut *conn, client_id, removed_courses, removed_entries).await?;
+
+            self.apply_client_sync(&mut *conn, client_id, courses.to_vec(), entries.to_vec())
+                .await?;
+
+            // Template items are maintained on the server, client syncs leave them alone
+            let course_ids: Vec<i32> = courses.iter().map(|c| c.id).collect();
+            let entry_ids: Vec<i32> = entries.iter().map(|e| e.id).collect();
+            mark_server_owned(&mut *conn, client_id, &course_ids, &entry_ids).await?;
+
+            // Rejected new items are not on the client; rejected updates keep their old version
+            sqlx::query("DELETE FROM template_items WHERE template_id = $1 AND client_id = $2")
+                .bind(template_id)
+                .bind(client_id)
+                .execute(&mut *conn)
+                .await?;
+            let items = mapping
+                .courses
+                .iter()
+                .filter(|(t, c)| course_ids.contains(c) || previous.courses.contains_key(t))
+                .map(|(t, c)| (templates::ITEM_COURSE, *t, *c))
+                .chain(
+                    mapping
+                        .entries
+                        .iter()
+                        .filter(|(t, c)| entry_ids.contains(c) || previous.entries.contains_key(t))
+                        .map(|(t, c)| (templates::ITEM_ENTRY, *t, *c)),
+                );
+            for (item_type, template_item_id, client_item_id) in items {
+                sqlx::query(
+                    "INSERT INTO template_items
+                        (template_id, client_id, item_type, template_item_id, client_item_id)
+                     VALUES ($1, $2, $3, $4, $5)",
+                )
+                .bind(template_id)
+                .bind(client_id)
+                .bind(item_type)
+                .bind(template_item_id)
+                .bind(client_item_id)
+                .execute(&mut *conn)
+                .await?;
+            }
+            sqlx::query(
+                "UPDATE template_clients SET published_at = CURRENT_TIMESTAMP
+                 WHERE template_id = $1 AND client_id = $2",
+            )
+            .bind(template_id)
+            .bind(client_id)
+            .execute(&mut *conn)
+            .await?;
+            let snapshot =
+                insert_timetable_snapshot(&mut *conn, client_id, "template", true).await?;
+
+            Ok(snapshot)
+        }
+
+        // Sync idempotency
+        /// The cached response for a repeated key. `request` is the canonical request
+        /// content; reusing a key for different content is rejected.
+        pub async fn get_idempotent_sync_response(
+            &self,
+            client_uuid: &str,
+            idempotency_key: &str,
+            request: &[u8],
+            retention_hours: i64,
+        ) -> AppResult<Option<SyncResponse>> {
+            let row = sqlx::query(
+                "SELECT sr.response,
+                        COALESCE(sr.request_hash = encode(sha256($4), 'hex'), TRUE) AS same_request
+                 FROM sync_requests sr
+                 JOIN clients c ON sr.client_id = c.id
+                 WHERE c.uuid = $1 AND sr.idempotency_key = $2
+                   AND sr.created_at > NOW() - make_interval(hours => $3::INTEGER)",
+            )
+            .bind(client_uuid)
+            .bind(idempotency_key)
+            .bind(retention_hours as i32)
+            .bind(request)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            match row {
+                Some(row) => {
+                    if !row.get::<bool, _>("same_request") {
+                        return Err(AppError::UnprocessableEntity(format!(
+                            "Idempotency key '{}' was already used for a different request",
+                            idempotency_key
+                        )));
+                    }
+                    let response: serde_json::Value = row.get("response");
+                    serde_json::from_value(response).map(Some).map_err(|e| {
+                        AppError::Internal(format!("Corrupted cached sync response: {}", e))
+                    })
+                }
+                None => Ok(None),
+            }
+        }
+
+        pub async fn save_idempotent_sync_response(
+            &self,
+            client_uuid: &str,
+            idempotency_key: &str,
+            request: &[u8],
+            response: &SyncResponse,
+            retention_hours: i64,
+        ) -> AppResult<()> {
+            let client = self.get_client_by_uuid(client_uuid).await?;
+            let response = serde_json::to_value(response).map_err(|e| {
+                AppError::Internal(format!("Failed to encode sync response: {}", e))
+            })?;
+
+            // Drop expired keys so they can be reused
+            sqlx::query(
+                "DELETE FROM sync_requests
+                 WHERE created_at <= NOW() - make_interval(hours => $1::INTEGER)",
+            )
+            .bind(retention_hours as i32)
+            .execute(&self.pool)
+            .await?;
+
+            sqlx::query(
+                "INSERT INTO sync_requests (client_id, idempotency_key, response, request_hash)
+                 VALUES ($1, $2, $3, encode(sha256($4), 'hex'))
+                 ON CONFLICT (client_id, idempotency_key)
+                 DO UPDATE SET response = $3, request_hash = EXCLUDED.request_hash,
+                               created_at = CURRENT_TIMESTAMP",
+            )
+            .bind(client.id)
+            .bind(idempotency_key)
+            .bind(response)
+            .bind(request)
+            .execute(&self.pool)
+            .await?;
+
+            Ok(())
+        }
+
+        // Sync history
+        pub async fn get_sync_logs_paginated(
+            &self,
+            client_id: Option<i32>,
+            status: Option<&str>,
+            offset: i64,
+            limit: i64,
+        ) -> AppResult<(Vec<SyncLog>, i64)> {
+            // Get total count
+            let count_row = sqlx::query(
+                "SELECT COUNT(*) as count FROM sync_logs
+                 WHERE ($1::INTEGER IS NULL OR client_id = $1)
+                   AND ($2::VARCHAR IS NULL OR status = $2)",
+            )
+            .bind(client_id)
+            .bind(status)
+            .fetch_one(&self.pool)
+            .await?;
+            let total: i64 = count_row.get("count");
+
+            // Get paginated results
+            let rows = sqlx::query(
+                "SELECT sl.id, sl.client_id, c.name as client_name, sl.sync_type, sl.status,
+                        sl.courses_count, sl.entries_count, sl.error_message, sl.payload_bytes,
+                        sl.created_at
+                 FROM sync_logs sl
+                 JOIN clients c ON sl.client_id = c.id
+                 WHERE ($1::INTEGER IS NULL OR sl.client_id = $1)
+                   AND ($2::VARCHAR IS NULL OR sl.status = $2)
+                 ORDER BY sl.created_at DESC, sl.id DESC
+                 LIMIT $3 OFFSET $4",
+            )
+            .bind(client_id)
+            .bind(status)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let logs = rows
+                .iter()
+                .map(|row| SyncLog {
+                    id: row.get("id"),
+                    client_id: row.get("client_id"),
+                    client_name: row.get("client_name"),
+                    sync_type: row.get("sync_type"),
+                    status: row.get("status"),
+                    courses_count: row.try_get("courses_count").unwrap_or(0),
+                    entries_count: row.try_get("entries_count").unwrap_or(0),
+                    error_message: row.try_get("error_message").ok().flatten(),
+                    payload_bytes: row.try_get("payload_bytes").ok().flatten(),
+                    created_at: row.get("created_at"),
+                })
+                .collect();
+
+            Ok((logs, total))
+        }
+
+        pub async fn get_sync_health(&self, client_id: Option<i32>) -> AppResult<Vec<SyncHealth>> {
+            let rows = sqlx::query(
+                "SELECT c.id, c.name,
+                        (SELECT MAX(created_at) FROM sync_logs
+                         WHERE client_id = c.id AND status = 'success') as last_success,
+                        (SELECT MAX(created_at) FROM sync_logs
+                         WHERE client_id = c.id AND status = 'failed') as last_failure,
+                        (SELECT error_message FROM sync_logs
+                         WHERE client_id = c.id AND status = 'failed'
+                         ORDER BY created_at DESC, id DESC LIMIT 1) as last_error,
+                        (SELECT COUNT(*) FROM sync_logs
+                         WHERE client_id = c.id AND status = 'failed'
+                           AND id > COALESCE((SELECT MAX(id) FROM sync_logs
+                                              WHERE client_id = c.id AND status <> 'failed'), 0)
+                        ) as consecutive_failures,
+                        (SELECT COUNT(*) FROM sync_logs WHERE client_id = c.id) as total_syncs,
+                        (SELECT AVG(payload_bytes)::FLOAT8 FROM sync_logs
+                         WHERE client_id = c.id) as average_payload_bytes
+                 FROM clients c
+                 WHERE ($1::INTEGER IS NULL OR c.id = $1)
+                 ORDER BY c.name",
+            )
+            .bind(client_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let health = rows
+                .iter()
+                .map(|row| SyncHealth {
+                    client_id: row.get("id"),
+                    client_name: row.get("name"),
+                    last_success: row
+                        .try_get::<Option<NaiveDateTime>, _>("last_success")
+                        .ok()
+                        .flatten(),
+                    last_failure: row
+                        .try_get::<Option<NaiveDateTime>, _>("last_failure")
+                        .ok()
+                        .flatten(),
+                    last_error: row.try_get("last_error").ok().flatten(),
+                    consecutive_failures: row.get("consecutive_failures"),
+                    total_syncs: row.get("total_syncs"),
+                    average_payload_bytes: row.try_get("average_payload_bytes").ok().flatten(),
+                })
+                .collect();
+
+            Ok(health)
+        }
+
+        // Get client data
+        pub async fn get_client_courses(&self, client_id: i32) -> AppResult<Vec<Course>> {
+            fetch_client_courses(&self.pool, client_id).await
+        }
+
+        pub async fn get_client_schedule(&self, client_id: i32) -> AppResult<Vec<ScheduleEntry>> {
+            fetch_client_schedule(&self.pool, client_id).await
+        }
+
+        /// Schedule entries matching the filter; entries without weeks run every week.
+        pub async fn get_schedule(&self, filter: &ScheduleFilter) -> AppResult<Vec<ScheduleEntry>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM schedule_entries se
+                 JOIN courses c ON se.course_id = c.id
+                 WHERE {}
+                 ORDER BY se.day_of_week, se.start_time",
+                SCHEDULE_ENTRY_COLUMNS, SCHEDULE_FILTER_CONDITION
+            ))
+            .bind(filter.client_id)
+            .bind(filter.week)
+            .bind(filter.day_of_week)
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(schedule_entry_from_row).collect())
+        }
+
+        pub async fn get_schedule_paginated(
+            &self,
+            filter: &ScheduleFilter,
+            offset: i64,
+            limit: i64,
+        ) -> AppResult<(Vec<ScheduleEntry>, i64)> {
+            let count_row = sqlx::query(&format!(
+                "SELECT COUNT(*) as count FROM schedule_entries se WHERE {}",
+                SCHEDULE_FILTER_CONDITION
+            ))
+            .bind(filter.client_id)
+            .bind(filter.week)
+            .bind(filter.day_of_week)
+            .fetch_one(&self.pool)
+            .await?;
+            let total: i64 = count_row.get("count");
+
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM schedule_entries se
+                 JOIN courses c ON se.course_id = c.id
+                 WHERE {}
+                 ORDER BY se.client_id, se.day_of_week, se.start_time
+                 LIMIT $4 OFFSET $5",
+                SCHEDULE_ENTRY_COLUMNS, SCHEDULE_FILTER_CONDITION
+            ))
+            .bind(filter.client_id)
+            .bind(filter.week)
+            .bind(filter.day_of_week)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok((rows.iter().map(schedule_entry_from_row).collect(), total))
+        }
+
+        // Central course and schedule management
+        pub async fn get_course_by_id(&self, id: i32) -> AppResult<Course> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM courses WHERE id = $1",
+                COURSE_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            row.as_ref()
+                .map(course_from_row)
+                .ok_or_else(|| AppError::NotFound(format!("Course {} not found", id)))
+        }
+
+        /// Creates a server-maintained course for a client.
+        pub async fn create_course(
+            &self,
+            client_id: i32,
+            course: CreateCourse,
+        ) -> AppResult<Course> {
+            let mut tx = self.pool.begin().await?;
+            lock_client(&mut tx, client_id).await?;
+            let existing = fetch_client_courses(&mut *tx, client_id).await?;
+            let candidate = ClientCourse {
+                id: timetable::next_server_id(existing.iter().map(|c| c.course_id_on_client)),
+                name: course.name,
+                teacher: course.teacher,
+                location: course.location,
+                color: course.color,
+                note: course.note,
+                attributes: course.attributes,
+            };
+            validation::check(validation::validate_course(&candidate))
+                .map_err(AppError::BadRequest)?;
+
+            let row = sqlx::query(&format!(
+                "INSERT INTO courses
+                    (client_id, course_id_on_client, name, teacher, location, color, note, attributes, origin)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                 RETURNING {}",
+                COURSE_COLUMNS
+            ))
+            .bind(client_id)
+            .bind(candidate.id)
+            .bind(&candidate.name)
+            .bind(&candidate.teacher)
+            .bind(&candidate.location)
+            .bind(&candidate.color)
+            .bind(&candidate.note)
+            .bind(serde_json::Value::Object(candidate.attributes))
+            .bind(validation::ORIGIN_SERVER)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            Ok(course_from_row(&row))
+        }
+
+        /// Updates a course and marks it as server-maintained, so client syncs no longer overwrite it.
+        pub async fn update_course(&self, id: i32, update: UpdateCourse) -> AppResult<Course> {
+            let client_id = self.get_course_by_id(id).await?.client_id;
+            let mut tx = self.pool.begin().await?;
+            lock_client(&mut tx, client_id).await?;
+            let current = self.get_course_by_id(id).await?;
+            let candidate = ClientCourse {
+                id: current.course_id_on_client,
+                name: update.name.unwrap_or(current.name),
+                teacher: update.teacher.unwrap_or(current.teacher),
+                location: update.location.unwrap_or(current.location),
+                color: update.color.unwrap_or(current.color),
+                note: update.note.unwrap_or(current.note),
+                attributes: update.attributes.unwrap_or(current.attributes),
+            };
+            validation::check(validation::validate_course(&candidate))
+                .map_err(AppError::BadRequest)?;
+
+            let row = sqlx::query(&format!(
+                "UPDATE courses
+                 SET name = $1, teacher = $2, location = $3, color = $4, note = $5,
+                     attributes = $6, origin = $7
+                 WHERE id = $8
+                 RETURNING {}",
+                COURSE_COLUMNS
+            ))
+            .bind(&candidate.name)
+            .bind(&candidate.teacher)
+            .bind(&candidate.location)
+            .bind(&candidate.color)
+            .bind(&candidate.note)
+            .bind(serde_json::Value::Object(candidate.attributes))
+            .bind(validation::ORIGIN_SERVER)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            Ok(course_from_row(&row))
+        }
+
+        /// Deletes a course together with its schedule entries.
+        pub async fn delete_course(&self, id: i32) -> AppResult<Course> {
+            let client_id = self.get_course_by_id(id).await?.client_id;
+            let mut tx = self.pool.begin().await?;
+            lock_client(&mut tx, client_id).await?;
+            let row = sqlx::query(&format!(
+                "DELETE FROM courses WHERE id = $1 RETURNING {}",
+                COURSE_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let course = row
+                .as_ref()
+                .map(course_from_row)
+                .ok_or_else(|| AppError::NotFound(format!("Course {} not found", id)))?;
+            // Its entries are deleted with it and skipped by syncs along with the course
+            record_deleted(&mut tx, client_id, &[course.course_id_on_client], &[]).await?;
+            tx.commit().await?;
+
+            Ok(course)
+        }
+
+        pub async fn get_schedule_entry_by_id(&self, id: i32) -> AppResult<ScheduleEntry> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM schedule_entries se
+                 JOIN courses c ON se.course_id = c.id
+                 WHERE se.id = $1",
+                SCHEDULE_ENTRY_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            row.as_ref()
+                .map(schedule_entry_from_row)
+                .ok_or_else(|| AppError::NotFound(format!("Schedule entry {} not found", id)))
+        }
+
+        /// The course an entry is moved to, which must belong to the entry's client.
+        async fn get_client_course(&self, client_id: i32, course_id: i32) -> AppResult<Course> {
+            let course = self.get_course_by_id(course_id).await?;
+            if course.client_id != client_id {
+                return Err(AppError::BadRequest(format!(
+                    "course_id: course {} belongs to another client",
+                    course_id
+                )));
+            }
+            Ok(course)
+        }
+
+        /// Creates a server-maintained schedule entry, rejecting overlaps with the client's other entries.
+        pub async fn create_schedule_entry(
+            &self,
+            client_id: i32,
+            entry: CreateScheduleEntry,
+        ) -> AppResult<ScheduleEntry> {
+            let mut tx = self.pool.begin().await?;
+            lock_client(&mut tx, client_id).await?;
+            let course = self.get_client_course(client_id, entry.course_id).await?;
+            let existing = fetch_client_schedule(&mut *tx, client_id).await?;
+            let period_end = entry.period_end.or(entry.period_start);
+            let (start_time, end_time) = self
+                .resolve_entry_times(
+                    entry.period_start,
+                    period_end,
+                    entry.start_time,
+                    entry.end_time,
+                )
+                .await?;
+            let candidate = ClientScheduleEntry {
+                id: timetable::next_server_id(existing.iter().map(|e| e.entry_id_on_client)),
+                course_id: course.course_id_on_client,
+                day_of_week: entry.day_of_week,
+                start_time,
+                end_time,
+                weeks: entry.weeks,
+                invalid_weeks: None,
+                note: entry.note,
+                room: entry.room,
+                attributes: entry.attributes,
+            };
+            let max_week = self.get_semester_weeks().await;
+            validation::check(validation::validate_entry_change(
+                &candidate, &existing, max_week,
+            ))
+            .map_err(AppError::BadRequest)?;
+
+            let row = sqlx::query(
+                "INSERT INTO schedule_entries
+                    (client_id, entry_id_on_client, course_id, day_of_week, start_time, end_time,
+                     weeks, note, room, attributes, origin, period_start, period_end)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                 RETURNING id",
+            )
+            .bind(client_id)
+            .bind(candidate.id)
+            .bind(course.id)
+            .bind(candidate.day_of_week)
+            .bind(&candidate.start_time)
+            .bind(&candidate.end_time)
+            .bind(&candidate.weeks)
+            .bind(&candidate.note)
+            .bind(&candidate.room)
+            .bind(serde_json::Value::Object(candidate.attributes))
+            .bind(validation::ORIGIN_SERVER)
+            .bind(entry.period_start)
+            .bind(period_end)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            self.get_schedule_entry_by_id(row.get("id")).await
+        }
+
+        /// Updates a schedule entry and marks it as server-maintained.
+        pub async fn update_schedule_entry(
+            &self,
+            id: i32,
+            update: UpdateScheduleEntry,
+        ) -> AppResult<ScheduleEntry> {
+            let client_id = self.get_schedule_entry_by_id(id).await?.client_id;
+            let mut tx = self.pool.begin().await?;
+            lock_client(&mut tx, client_id).await?;
+            let current = self.get_schedule_entry_by_id(id).await?;
+            let course = self
+                .get_client_course(
+                    current.client_id,
+                    update.course_id.unwrap_or(current.course_id),
+                )
+                .await?;
+            let existing = fetch_client_schedule(&mut *tx, current.client_id).await?;
+            let (start_time, end_time, period_start, period_end) =
+                if update.period_start.is_some() || update.period_end.is_some() {
+                    let period_start = update.period_start.or(current.period_start);
+                    let period_end = match update.period_start {
+                        Some(start) => update.period_end.unwrap_or(start),
+                        None => update.period_end.unwrap_or_default(),
+                    };
+                    let (start_time, end_time) = self
+                        .resolve_entry_times(
+                            period_start,
+                            Some(period_end),
+                            update.start_time,
+                            update.end_time,
+                        )
+                        .await?;
+                    (start_time, end_time, period_start, Some(period_end))
+                } else if update.start_time.is_some() || update.end_time.is_some() {
+                    (
+                        update.start_time.unwrap_or(current.start_time),
+                        update.end_time.unwrap_or(current.end_time),
+                        None,
+                        None,
+                    )
+                } else {
+                    (
+                        current.start_time,
+                        current.end_time,
+                        current.period_start,
+                        current.period_end,
+                    )
+                };
+            // Setting the weeks replaces any week text the migration could not read
+            let weeks_legacy = match update.weeks {
+                Some(_) => None,
+                None => current.weeks_legacy,
+            };
+            let candidate = ClientScheduleEntry {
+                id: current.entry_id_on_client,
+                course_id: course.course_id_on_client,
+                day_of_week: update.day_of_week.unwrap_or(current.day_of_week),
+                start_time,
+                end_time,
+                weeks: update.weeks.unwrap_or(current.weeks),
+                invalid_weeks: None,
+                note: update.note.unwrap_or(current.note),
+                room: update.room.unwrap_or(current.room),
+                attributes: update.attributes.unwrap_or(current.attributes),
+            };
+            let max_week = self.get_semester_weeks().await;
+            validation::check(validation::validate_entry_change(
+                &candidate, &existing, max_week,
+            ))
+            .map_err(AppError::BadRequest)?;
+
+            sqlx::query(
+                "UPDATE schedule_entries
+                 SET course_id = $1, day_of_week = $2, start_time = $3, end_time = $4, weeks = $5,
+                     note = $6, room = $7, attributes = $8, origin = $9,
+                     period_start = $10, period_end = $11, weeks_legacy = $12
+                 WHERE id = $13",
+            )
+            .bind(course.id)
+            .bind(candidate.day_of_week)
+            .bind(&candidate.start_time)
+            .bind(&candidate.end_time)
+            .bind(&candidate.weeks)
+            .bind(&candidate.note)
+            .bind(&candidate.room)
+            .bind(serde_json::Value::Object(candidate.attributes))
+            .bind(validation::ORIGIN_SERVER)
+            .bind(period_start)
+            .bind(period_end)
+            .bind(&weeks_legacy)
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            self.get_schedule_entry_by_id(id).await
+        }
+
+        pub async fn delete_schedule_entry(&self, id: i32) -> AppResult<ScheduleEntry> {
+            let entry = self.get_schedule_entry_by_id(id).await?;
+            let mut tx = self.pool.begin().await?;
+            lock_client(&mut tx, entry.client_id).await?;
+            sqlx::query("DELETE FROM schedule_entries WHERE id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            record_deleted(&mut tx, entry.client_id, &[], &[entry.entry_id_on_client]).await?;
+            tx.commit().await?;
+
+            Ok(entry)
+        }
+
+        /// Teacher and location conflicts across all clients.
+        pub async fn get_schedule_conflicts(
+            &self,
+            filter: &ConflictFilter,
+        ) -> AppResult<Vec<ScheduleConflict>> {
+            if let Some(kind) = &filter.kind {
+                if !conflicts::CONFLICT_KINDS.contains(&kind.as_str()) {
+                    return Err(AppError::BadRequest(format!(
+                        "kind must be one of {}",
+                        conflicts::CONFLICT_KINDS.join(", ")
+                    )));
+                }
+            }
+
+            let entries = self.get_schedule(&ScheduleFilter::default()).await?;
+            Ok(conflicts::find_conflicts(&entries)
+                .into_iter()
+                .filter(|c| filter.kind.as_ref().is_none_or(|kind| &c.kind == kind))
+                .filter(|c| {
+                    filter
+                        .client_id
+                        .is_none_or(|id| c.entries.iter().any(|e| e.client_id == id))
+                })
+                .collect())
+        }
+
+        // Rooms
+        pub async fn get_rooms(&self) -> AppResult<Vec<Room>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM rooms ORDER BY building NULLS LAST, name",
+                ROOM_COLUMNS
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(room_from_row).collect())
+        }
+
+        pub async fn get_room_by_id(&self, id: i32) -> AppResult<Room> {
+            let row = sqlx::query(&format!("SELECT {} FROM rooms WHERE id = $1", ROOM_COLUMNS))
+                .bind(id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+            row.as_ref()
+                .map(room_from_row)
+                .ok_or_else(|| AppError::NotFound(format!("Room {} not found", id)))
+        }
+
+        /// Rejects a room name already used by another room.
+        async fn check_room_name(&self, name: &str, id: Option<i32>) -> AppResult<()> {
+            let row =
+                sqlx::query("SELECT id FROM rooms WHERE name = $1 AND id IS DISTINCT FROM $2")
+                    .bind(name)
+                    .bind(id)
+                    .fetch_optional(&self.pool)
+                    .await?;
+
+            if row.is_some() {
+                return Err(AppError::BadRequest(format!(
+                    "name: room '{}' already exists",
+                    name
+                )));
+            }
+            Ok(())
+        }
+
+        pub async fn create_room(&self, room: CreateRoom) -> AppResult<Room> {
+            let name = room.name.trim().to_string();
+            rooms::validate_room(&name, room.capacity, &room.equipment)
+                .map_err(AppError::BadRequest)?;
+            self.check_room_name(&name, None).await?;
+
+            let row = sqlx::query(&format!(
+                "INSERT INTO rooms (name, building, floor, capacity, equipment)
+                 VALUES ($1, $2, $3, $4, $5)
+                 RETURNING {}",
+                ROOM_COLUMNS
+            ))
+            .bind(&name)
+            .bind(&room.building)
+            .bind(room.floor)
+            .bind(room.capacity)
+            .bind(serde_json::json!(room.equipment))
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(room_from_row(&row))
+        }
+
+        pub async fn update_room(&self, id: i32, update: UpdateRoom) -> AppResult<Room> {
+            let current = self.get_room_by_id(id).await?;
+            let name = update.name.map_or(current.name, |n| n.trim().to_string());
+            let capacity = update.capacity.or(current.capacity);
+            let equipment = update.equipment.unwrap_or(current.equipment);
+            rooms::validate_room(&name, capacity, &equipment).map_err(AppError::BadRequest)?;
+            self.check_room_name(&name, Some(id)).await?;
+
+            let row = sqlx::query(&format!(
+                "UPDATE rooms SET name = $1, building = $2, floor = $3, capacity = $4, equipment = $5
+                 WHERE id = $6
+                 RETURNING {}",
+                ROOM_COLUMNS
+            ))
+            .bind(&name)
+            .bind(update.building.or(current.building))
+            .bind(update.floor.or(current.floor))
+            .bind(capacity)
+            .bind(serde_json::json!(equipment))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(room_from_row(&row))
+        }
+
+        /// Deletes a room; clients installed in it are left without a room.
+        pub async fn delete_room(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM rooms WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!("Room {} not found", id)));
+            }
+            Ok(())
+        }
+
+        /// Sets the room a client's display is installed in, or clears it.
+        pub async fn assign_client_room(
+            &self,
+            client_id: i32,
+            room_id: Option<i32>,
+        ) -> AppResult<Client> {
+            self.get_client_by_id(client_id).await?;
+            if let Some(room_id) = room_id {
+                self.get_room_by_id(room_id).await?;
+            }
+
+            sqlx::query("UPDATE clients SET room_id = $1 WHERE id = $2")
+                .bind(room_id)
+                .bind(client_id)
+                .execute(&self.pool)
+                .await?;
+
+            self.get_client_by_id(client_id).await
+        }
+
+        /// The given week, or the current week of the active semester while it runs.
+        async fn resolve_week(&self, week: Option<i32>) -> AppResult<Option<i32>> {
+            if week.is_some() {
+                return Ok(week);
+            }
+            let today = Local::now().date_naive();
+            Ok(self
+                .get_active_semester()
+                .await?
+                .map(|s| semester::week_info(&s, today))
+                .filter(|info| info.in_semester)
+                .map(|info| info.week))
+        }
+
+        /// Classes held in a room in one week, optionally on one weekday.
+        pub async fn get_room_occupancy(
+            &self,
+            id: i32,
+            query: &OccupancyQuery,
+        ) -> AppResult<RoomOccupancy> {
+            let room = self.get_room_by_id(id).await?;
+            let week = self.resolve_week(query.week).await?;
+            let entries = self
+                .get_schedule(&ScheduleFilter {
+                    client_id: None,
+                    week,
+                    day_of_week: query.day_of_week,
+                })
+                .await?
+                .into_iter()
+                .filter(|e| e.room_id == Some(id))
+                .collect();
+
+            Ok(RoomOccupancy {
+                room,
+                week,
+                day_of_week: query.day_of_week,
+                entries,
+            })
+        }
+
+        /// Rooms without a class in the time slot of the given (or current) week.
+        pub async fn find_free_rooms(&self, query: FreeRoomQuery) -> AppResult<Vec<Room>> {
+            rooms::parse_slot(&query).map_err(AppError::BadRequest)?;
+            let query = FreeRoomQuery {
+                week: self.resolve_week(query.week).await?,
+                ..query
+            };
+            let entries = self
+                .get_schedule(&ScheduleFilter {
+                    client_id: None,
+                    week: query.week,
+                    day_of_week: Some(query.day_of_week),
+                })
+                .await?;
+
+            Ok(rooms::free_rooms(self.get_rooms().await?, &entries, &query))
+        }
+
+        // Bell schedules
+        pub async fn get_bell_schedules(&self) -> AppResult<Vec<BellSchedule>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM bell_schedules ORDER BY is_default DESC, name",
+                BELL_SCHEDULE_COLUMNS
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(bell_schedule_from_row).collect())
+        }
+
+        pub async fn get_bell_schedule_by_id(&self, id: i32) -> AppResult<BellSchedule> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM bell_schedules WHERE id = $1",
+                BELL_SCHEDULE_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            row.as_ref()
+                .map(bell_schedule_from_row)
+                .ok_or_else(|| AppError::NotFound(format!("Bell schedule {} not found", id)))
+        }
+
+        pub async fn get_default_bell_schedule(&self) -> AppResult<Option<BellSchedule>> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM bell_schedules WHERE is_default",
+                BELL_SCHEDULE_COLUMNS
+            ))
+            .fetch_optional(&self.pool)
+            .await?;
+
+            Ok(row.as_ref().map(bell_schedule_from_row))
+        }
+
+        /// An entry's times: resolved from its periods on the default bell schedule, or as given.
+        async fn resolve_entry_times(
+            &self,
+            period_start: Option<i32>,
+            period_end: Option<i32>,
+            start_time: Option<String>,
+            end_time: Option<String>,
+        ) -> AppResult<(String, String)> {
+            let default = match period_start {
+                Some(_) => self.get_default_bell_schedule().await?,
+                None => None,
+            };
+            bells::entry_times(
+                default.as_ref().map(|b| b.periods.as_slice()),
+                period_start,
+                period_end,
+                start_time,
+                end_time,
+            )
+            .map_err(AppError::BadRequest)
+        }
+
+        /// Rejects a bell schedule name already used by another schedule.
+        async fn check_bell_schedule_name(&self, name: &str, id: Option<i32>) -> AppResult<()> {
+            let row = sqlx::query(
+                "SELECT id FROM bell_schedules WHERE name = $1 AND id IS DISTINCT FROM $2",
+            )
+            .bind(name)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            if row.is_some() {
+                return Err(AppError::BadRequest(format!(
+                    "name: bell schedule '{}' already exists",
+                    name
+                )));
+            }
+            Ok(())
+        }
+
+        /// Rejects default periods that would leave period-based entries without times.
+        async fn check_period_usage(&self, periods: &[BellPeriod]) -> AppResult<()> {
+            let rows = sqlx::query(
+                "SELECT id, period_start, COALESCE(period_end, period_start) AS period_end
+                 FROM schedule_entries WHERE period_start IS NOT NULL ORDER BY id",
+            )
+            .fetch_all(&self.pool)
+            .await?;
+
+            for row in rows {
+                let (start, end): (i32, i32) = (row.get("period_start"), row.get("period_end"));
+                if bells::period_times(periods, start, end).is_err() {
+                    return Err(AppError::BadRequest(format!(
+                        "periods: schedule entry {} is scheduled in periods {}-{}, which are not all defined",
+                        row.get::<i32, _>("id"),
+                        start,
+                        end
+                    )));
+                }
+            }
+            Ok(())
+        }
+
+        /// Creates a bell schedule; a new default replaces the previous one.
+        pub async fn create_bell_schedule(
+            &self,
+            schedule: CreateBellSchedule,
+        ) -> AppResult<BellSchedule> {
+            let name = schedule.name.trim().to_string();
+            bells::validate_bell_schedule(&name, &schedule.periods)
+                .map_err(AppError::BadRequest)?;
+            self.check_bell_schedule_name(&name, None).await?;
+            if schedule.is_default {
+                self.check_period_usage(&schedule.periods).await?;
+            }
+
+            let mut tx = self.pool.begin().await?;
+            if schedule.is_default {
+                sqlx::query("UPDATE bell_schedules SET is_default = FALSE WHERE is_default")
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            let row = sqlx::query(&format!(
+                "INSERT INTO bell_schedules (name, is_default, periods)
+                 VALUES ($1, $2, $3)
+                 RETURNING {}",
+                BELL_SCHEDULE_COLUMNS
+            ))
+            .bind(&name)
+            .bind(schedule.is_default)
+            .bind(serde_json::json!(schedule.periods))
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            Ok(bell_schedule_from_row(&row))
+        }
+
+        /// Updates a bell schedule. Period-based entries follow the default through
+        /// [`Repository::retime_period_entries`].
+        pub async fn update_bell_schedule(
+            &self,
+            id: i32,
+            update: UpdateBellSchedule,
+        ) -> AppResult<BellSchedule> {
+            let current = self.get_bell_schedule_by_id(id).await?;
+            let name = update.name.map_or(current.name, |n| n.trim().to_string());
+            let is_default = update.is_default.unwrap_or(current.is_default);
+            let periods = update.periods.unwrap_or(current.periods);
+            bells::validate_bell_schedule(&name, &periods).map_err(AppError::BadRequest)?;
+            self.check_bell_schedule_name(&name, Some(id)).await?;
+            if is_default {
+                self.check_period_usage(&periods).await?;
+            }
+
+            let mut tx = self.pool.begin().await?;
+            if is_default {
+                sqlx::query(
+                    "UPDATE bell_schedules SET is_default = FALSE WHERE is_default AND id <> $1",
+                )
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            let row = sqlx::query(&format!(
+                "UPDATE bell_schedules
+                 SET name = $1, is_default = $2, periods = $3, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = $4
+                 RETURNING {}",
+                BELL_SCHEDULE_COLUMNS
+            ))
+            .bind(&name)
+            .bind(is_default)
+            .bind(serde_json::json!(periods))
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+            tx.commit().await?;
+
+            Ok(bell_schedule_from_row(&row))
+        }
+
+        /// Deletes a bell schedule with its date switches. The default cannot be deleted
+        /// while entries are scheduled by period.
+        pub async fn delete_bell_schedule(&self, id: i32) -> AppResult<()> {
+            let schedule = self.get_bell_schedule_by_id(id).await?;
+            if schedule.is_default {
+                let in_use = sqlx::query(
+                    "SELECT EXISTS (SELECT 1 FROM schedule_entries WHERE period_start IS NOT NULL)",
+                )
+                .fetch_one(&self.pool)
+                .await?
+                .get::<bool, _>(0);
+                if in_use {
+                    return Err(AppError::BadRequest(format!(
+                        "Bell schedule {} is the default and schedule entries are scheduled by period",
+                        id
+                    )));
+                }
+            }
+
+            sqlx::query("DELETE FROM bell_schedules WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        }
+
+        /// Re-resolves period-based entries on the default bell schedule; returns the
+        /// clients whose entries moved, and the entries left at their old times because
+        /// their periods are missing or the new times are invalid (by schedule entry ID).
+        pub async fn retime_period_entries(&self) -> AppResult<(Vec<i32>, Vec<SyncRejection>)> {
+            let Some(default) = self.get_default_bell_schedule().await? else {
+                return Ok((Vec::new(), Vec::new()));
+            };
+            let client_ids: Vec<i32> = sqlx::query_scalar(
+                "SELECT DISTINCT client_id FROM schedule_entries
+                 WHERE period_start IS NOT NULL ORDER BY client_id",
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            let max_week = self.get_semester_weeks().await;
+
+            let mut clients = Vec::new();
+            let mut not_retimed = Vec::new();
+            for client_id in client_ids {
+                let mut tx = self.pool.begin().await?;
+                lock_client(&mut tx, client_id).await?;
+                let courses = fetch_client_courses(&mut *tx, client_id).await?;
+                let stored = fetch_client_schedule(&mut *tx, client_id).await?;
+
+                let mut retimed: HashMap<i32, (String, String)> = HashMap::new();
+                for entry in &stored {
+                    let Some(period_start) = entry.period_start else {
+                        continue;
+                    };
+                    let period_end = entry.period_end.unwrap_or(period_start);
+                    match bells::period_times(&default.periods, period_start, period_end) {
+                        Ok((start_time, end_time))
+                            if start_time != entry.start_time || end_time != entry.end_time =>
+                        {
+                            retimed.insert(entry.id, (start_time, end_time));
+                        }
+                        Ok(_) => {}
+                        Err(reason) => not_retimed.push(SyncRejection {
+                            item_type: "schedule_entry".to_string(),
+                            id: entry.id,
+                            field: "periods".to_string(),
+                            reason,
+                        }),
+                    }
+                }
+
+                // Check each new time against the others' new times; entries that fail keep
+                // their old times, so check the rest again until nothing else fails
+                loop {
+                    let proposed: Vec<ScheduleEntry> = stored
+                        .iter()
+                        .map(|e| match retimed.get(&e.id) {
+                            Some((start_time, end_time)) => ScheduleEntry {
+                                start_time: start_time.clone(),
+                                end_time: end_time.clone(),
+                                ..e.clone()
+                            },
+                            None => e.clone(),
+                        })
+                        .collect();
+                    let mut failed = false;
+                    for entry in proposed.iter().filter(|e| retimed.contains_key(&e.id)) {
+                        let candidates =
+                            timetable::to_client_entries(&courses, std::slice::from_ref(entry));
+                        let errors: Vec<SyncRejection> = candidates
+                            .iter()
+                            .flat_map(|c| validation::validate_entry_change(c, &proposed, max_week))
+                            .collect();
+                        if !errors.is_empty() {
+                            not_retimed.extend(
+                                errors
+                                    .into_iter()
+                                    .map(|r| SyncRejection { id: entry.id, ..r }),
+                            );
+                            failed = true;
+                        }
+                    }
+                    if !failed {
+                        break;
+                    }
+                    let rejected: HashSet<i32> = not_retimed.iter().map(|r| r.id).collect();
+                    retimed.retain(|id, _| !rejected.contains(id));
+                }
+
+                for (id, (start_time, end_time)) in &retimed {
+                    sqlx::query(
+                        "UPDATE schedule_entries SET start_time = $1, end_time = $2 WHERE id = $3",
+                    )
+                    .bind(start_time)
+                    .bind(end_time)
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                tx.commit().await?;
+                if !retimed.is_empty() {
+                    clients.push(client_id);
+                }
+            }
+
+            Ok((clients, not_retimed))
+        }
+
+        // Semesters
+        pub async fn get_semesters(&self) -> AppResult<Vec<Semester>> {
+            let rows = sqlx::query(
+                "SELECT id, name, start_date, end_date, week_count, is_active, created_at
+                 FROM semesters ORDER BY start_date DESC",
+            )
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(semester_from_row).collect())
+        }
+
+        pub async fn get_semester_by_id(&self, id: i32) -> AppResult<Semester> {
+            let row = sqlx::query(
+                "SELECT id, name, start_date, end_date, week_count, is_active, created_at
+                 FROM semesters WHERE id = $1",
+            )
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            row.as_ref()
+                .map(semester_from_row)
+                .ok_or_else(|| AppError::NotFound(format!("Semester {} not found", id)))
+        }
+
+        pub async fn get_active_semester(&self) -> AppResult<Option<Semester>> {
+            let row = sqlx::query(
+                "SELECT id, name, start_date, end_date, week_count, is_active, created_at
+                 FROM semesters WHERE is_active",
+            )
+            .fetch_optional(&self.pool)
+            .await?;
+
+            Ok(row.as_ref().map(semester_from_row))
+        }
+
+        pub async fn create_semester(&self, semester: CreateSemester) -> AppResult<Semester> {
+            let (end_date, week_count) = semester::resolve_dates(
+                semester.start_date,
+                semester.end_date,
+                semester.week_count,
+            )
+            .map_err(AppError::BadRequest)?;
+
+            let row = sqlx::query(
+                "INSERT INTO semesters (name, start_date, end_date, week_count)
+                 VALUES ($1, $2, $3, $4)
+                 RETURNING id",
+            )
+            .bind(&semester.name)
+            .bind(semester.start_date)
+            .bind(end_date)
+            .bind(week_count)
+            .fetch_one(&self.pool)
+            .await?;
+            let id: i32 = row.get("id");
+
+            if semester.is_active {
+                self.activate_semester(id).await?;
+            }
+            self.get_semester_by_id(id).await
+        }
+
+        pub async fn update_semester(
+            &self,
+            id: i32,
+            update: UpdateSemester,
+        ) -> AppResult<Semester> {
+            let current = self.get_semester_by_id(id).await?;
+            let start_date = update.start_date.unwrap_or(current.start_date);
+
+            // Keep the week count when only the start moves, and vice versa
+            let (end_date, week_count) = match (update.end_date, update.week_count) {
+                (None, None) => (None, Some(current.week_count)),
+                (end_date, week_count) => (end_date, week_count),
+            };
+            let (end_date, week_count) = semester::resolve_dates(start_date, end_date, week_count)
+                .map_err(AppError::BadRequest)?;
+
+            sqlx::query(
+                "UPDATE semesters SET name = $1, start_date = $2, end_date = $3, week_count = $4
+                 WHERE id = $5",
+            )
+            .bind(update.name.unwrap_or(current.name))
+            .bind(start_date)
+            .bind(end_date)
+            .bind(week_count)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+            self.get_semester_by_id(id).await
+        }
+
+        /// Moves the start of the active semester, keeping its week count.
+        pub async fn set_active_semester_start(
+            &self,
+            start_date: NaiveDate,
+        ) -> AppResult<Semester> {
+            let semester = self
+                .get_active_semester()
+                .await?
+                .ok_or_else(|| AppError::NotFound("No active semester".to_string()))?;
+
+            self.update_semester(
+                semester.id,
+                UpdateSemester {
+                    name: None,
+                    start_date: Some(start_date),
+                    end_date: None,
+                    week_count: None,
+                },
+            )
+            .await
+        }
+
+        pub async fn activate_semester(&self, id: i32) -> AppResult<()> {
+            self.get_semester_by_id(id).await?;
+
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("UPDATE semesters SET is_active = FALSE WHERE is_active AND id <> $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("UPDATE semesters SET is_active = TRUE WHERE id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            Ok(())
+        }
+
+        pub async fn delete_semester(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM semesters WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!("Semester {} not found", id)));
+            }
+            Ok(())
+        }
+
+        /// Week of the active semester that contains `date`.
+        pub async fn get_week_info(&self, date: NaiveDate) -> AppResult<WeekInfo> {
+            let semester = self
+                .get_active_semester()
+                .await?
+                .ok_or_else(|| AppError::NotFound("No active semester".to_string()))?;
+
+            Ok(semester::week_info(&semester, date))
+        }
+
+        // Schedule exceptions
+        pub async fn create_schedule_exception(
+            &self,
+            exception: CreateScheduleException,
+        ) -> AppResult<ScheduleException> {
+            calendar::validate_exception(&exception).map_err(AppError::BadRequest)?;
+
+            // Entry exceptions always belong to the entry's client
+            let client_id = match exception.entry_id {
+                Some(entry_id) => {
+                    let row = sqlx::query("SELECT client_id FROM schedule_entries WHERE id = $1")
+                        .bind(entry_id)
+                        .fetch_optional(&self.pool)
+                        .await?
+                        .ok_or_else(|| {
+                            AppError::NotFound(format!("Schedule entry {} not found", entry_id))
+                        })?;
+                    Some(row.get::<i32, _>("client_id"))
+                }
+                None => {
+                    if let Some(client_id) = exception.client_id {
+                        self.get_client_by_id(client_id).await?;
+                    }
+                    exception.client_id
+                }
+            };
+            if let Some(bell_schedule_id) = exception.bell_schedule_id {
+                self.get_bell_schedule_by_id(bell_schedule_id).await?;
+            }
+
+            let row = sqlx::query(&format!(
+                "INSERT INTO schedule_exceptions
+                    (client_id, date, kind, follows_day_of_week, entry_id, new_date,
+                     new_start_time, new_end_time, new_room, reason, bell_schedule_id)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                 RETURNING {}",
+                SCHEDULE_EXCEPTION_COLUMNS
+            ))
+            .bind(client_id)
+            .bind(exception.date)
+            .bind(&exception.kind)
+            .bind(exception.follows_day_of_week)
+            .bind(exception.entry_id)
+            .bind(exception.new_date)
+            .bind(&exception.new_start_time)
+            .bind(&exception.new_end_time)
+            .bind(&exception.new_room)
+            .bind(&exception.reason)
+            .bind(exception.bell_schedule_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(schedule_exception_from_row(&row))
+        }
+
+        /// Exceptions on or moving classes into the date range; a client filter includes global ones.
+        pub async fn get_schedule_exceptions(
+            &self,
+            filter: &ExceptionFilter,
+        ) -> AppResult<Vec<ScheduleException>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM schedule_exceptions
+                 WHERE ($1::INTEGER IS NULL OR client_id IS NULL OR client_id = $1)
+                   AND (date BETWEEN COALESCE($2::DATE, date) AND COALESCE($3::DATE, date)
+                        OR new_date BETWEEN COALESCE($2::DATE, new_date) AND COALESCE($3::DATE, new_date))
+                 ORDER BY date, id",
+                SCHEDULE_EXCEPTION_COLUMNS
+            ))
+            .bind(filter.client_id)
+            .bind(filter.from)
+            .bind(filter.to)
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(schedule_exception_from_row).collect())
+        }
+
+        /// Exceptions for computing schedules in the date range: those of
+        /// `get_schedule_exceptions`, plus the remapped and cancelled days on the original
+        /// dates of classes moved into the range, which decide whether those classes were due.
+        async fn get_calendar_exceptions(
+            &self,
+            filter: &ExceptionFilter,
+        ) -> AppResult<Vec<ScheduleException>> {
+            let mut exceptions = self.get_schedule_exceptions(filter).await?;
+            let in_range = |date: NaiveDate| {
+                filter.from.is_none_or(|from| from <= date) && filter.to.is_none_or(|to| date <= to)
+            };
+            let original_dates: Vec<NaiveDate> = exceptions
+                .iter()
+                .filter(|e| e.kind == calendar::RELOCATE_ENTRY && !in_range(e.date))
+                .map(|e| e.date)
+                .collect();
+            if original_dates.is_empty() {
+                return Ok(exceptions);
+            }
+
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM schedule_exceptions
+                 WHERE ($1::INTEGER IS NULL OR client_id IS NULL OR client_id = $1)
+                   AND kind = ANY($2) AND date = ANY($3)
+                 ORDER BY date, id",
+                SCHEDULE_EXCEPTION_COLUMNS
+            ))
+            .bind(filter.client_id)
+            .bind(&[calendar::CANCEL_DAY, calendar::REMAP_DAY][..])
+            .bind(&original_dates)
+            .fetch_all(&self.pool)
+            .await?;
+            exceptions.extend(rows.iter().map(schedule_exception_from_row));
+
+            Ok(exceptions)
+        }
+
+        pub async fn delete_schedule_exception(&self, id: i32) -> AppResult<ScheduleException> {
+            let row = sqlx::query(&format!(
+                "DELETE FROM schedule_exceptions WHERE id = $1 RETURNING {}",
+                SCHEDULE_EXCEPTION_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+            row.as_ref()
+                .map(schedule_exception_from_row)
+                .ok_or_else(|| AppError::NotFound(format!("Schedule exception {} not found", id)))
+        }
+
+        /// The client's classes on a date, after applying the semester week and exceptions.
+        pub async fn get_effective_schedule(
+            &self,
+            client_id: i32,
+            date: NaiveDate,
+        ) -> AppResult<DaySchedule> {
+            self.get_client_by_id(client_id).await?;
+
+            let semester = self.get_active_semester().await?;
+            let week = semester
+                .as_ref()
+                .map(|s| semester::week_number(s.start_date, date));
+            let in_semester = semester
+                .as_ref()
+                .is_none_or(|s| s.start_date <= date && date <= s.end_date);
+
+            let filter = ExceptionFilter {
+                client_id: Some(client_id),
+                from: Some(date),
+                to: Some(date),
+            };
+            let exceptions = self.get_schedule_exceptions(&filter).await?;
+            let calendar_exceptions = self.get_calendar_exceptions(&filter).await?;
+            let all_entries = self.get_client_schedule(client_id).await?;
+            let entries =
+                calendar::day_entries(date, semester.as_ref(), &all_entries, &calendar_exceptions);
+
+            Ok(DaySchedule {
+                client_id,
+                date: date.format("%Y-%m-%d").to_string(),
+                week,
+                in_semester,
+                day_of_week: calendar::effective_day_of_week(date, &exceptions),
+                exceptions,
+                entries,
+            })
+        }
+
+        /// Current and next class of one client, or of every client when `client_id` is `None`.
+        pub async fn get_now_next(
+            &self,
+            client_id: Option<i32>,
+            at: NaiveDateTime,
+        ) -> AppResult<Vec<NowNext>> {
+            let clients = match client_id {
+                Some(id) => vec![self.get_client_by_id(id).await?],
+                None => self.get_all_clients().await?,
+            };
+
+            let semester = self.get_active_semester().await?;
+            let week = semester
+                .as_ref()
+                .map(|s| semester::week_number(s.start_date, at.date()));
+            let exceptions = self
+                .get_calendar_exceptions(&ExceptionFilter {
+                    client_id,
+                    from: Some(at.date()),
+                    to: Some(
+                        at.date() + chrono::Duration::days(calendar::NEXT_CLASS_LOOKAHEAD_DAYS),
+                    ),
+                })
+                .await?;
+            let entries = self
+                .get_schedule(&ScheduleFilter {
+                    client_id,
+                    ..Default::default()
+                })
+                .await?;
+
+            let mut entries_by_client: HashMap<i32, Vec<ScheduleEntry>> = HashMap::new();
+            for entry in entries {
+                entries_by_client
+                    .entry(entry.client_id)
+                    .or_default()
+                    .push(entry);
+            }
+
+            let results = clients
+                .into_iter()
+                .map(|client| {
+                    let client_exceptions: Vec<ScheduleException> = exceptions
+                        .iter()
+                        .filter(|e| e.client_id.is_none_or(|id| id == client.id))
+                        .cloned()
+                        .collect();
+                    let client_entries = entries_by_client
+                        .get(&client.id)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+
+                    let (current, next) = calendar::now_and_next(
+                        at,
+                        semester.as_ref(),
+                        client_entries,
+                        &client_exceptions,
+                    );
+                    let minutes_until_next = next.as_ref().and_then(|(date, e)| {
+                        let time = NaiveTime::parse_from_str(&e.entry.start_time, "%H:%M").ok()?;
+                        Some((date.and_time(time) - at).num_minutes())
+                    });
+
+                    NowNext {
+                        client_id: client.id,
+                        client_name: client.name,
+                        at: at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                        week,
+                        current,
+                        next_date: next
+                            .as_ref()
+                            .map(|(date, _)| date.format("%Y-%m-%d").to_string()),
+                        minutes_until_next,
+                        next: next.map(|(_, e)| e),
+                    }
+                })
+                .collect();
+
+            Ok(results)
+        }
+
+        // Calendar feeds
+        pub async fn get_calendar_feeds(&self) -> AppResult<Vec<CalendarFeed>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM calendar_feeds ORDER BY id",
+                CALENDAR_FEED_COLUMNS
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(calendar_feed_from_row).collect())
+        }
+
+        pub async fn create_calendar_feed(
+            &self,
+            feed: CreateCalendarFeed,
+            token: &str,
+        ) -> AppResult<CalendarFeed> {
+            if !ical::FEED_SCOPES.contains(&feed.scope.as_str()) {
+                return Err(AppError::BadRequest(format!(
+                    "scope must be one of {}",
+                    ical::FEED_SCOPES.join(", ")
+                )));
+            }
+            let mut target = feed.target.trim().to_string();
+            if target.is_empty() || target.chars().count() > validation::MAX_LOCATION_LEN {
+                return Err(AppError::BadRequest(format!(
+                    "target must be 1 to {} characters",
+                    validation::MAX_LOCATION_LEN
+                )));
+            }
+            if feed.scope == ical::SCOPE_CLIENT {
+                let client_id = target.parse::<i32>().map_err(|_| {
+                    AppError::BadRequest("target must be a client ID for client feeds".to_string())
+                })?;
+                self.get_client_by_id(client_id).await?;
+                // Store the ID as the client lookup formats it, e.g. "7" for "007"
+                target = client_id.to_string();
+            }
+
+            let row = sqlx::query(&format!(
+                "INSERT INTO calendar_feeds (token, scope, target, name)
+                 VALUES ($1, $2, $3, $4)
+                 RETURNING {}",
+                CALENDAR_FEED_COLUMNS
+            ))
+            .bind(token)
+            .bind(&feed.scope)
+            .bind(&target)
+            .bind(&feed.name)
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(calendar_feed_from_row(&row))
+        }
+
+        pub async fn delete_calendar_feed(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM calendar_feeds WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!(
+                    "Calendar feed {} not found",
+                    id
+                )));
+            }
+            Ok(())
+        }
+
+        /// Renders the feed behind a subscription token as iCalendar text.
+        pub async fn render_calendar_feed(&self, token: &str) -> AppResult<String> {
+            let row = sqlx::query(&format!(
+                "UPDATE calendar_feeds SET last_accessed_at = CURRENT_TIMESTAMP
+                 WHERE token = $1
+                 RETURNING {}",
+                CALENDAR_FEED_COLUMNS
+            ))
+            .bind(token)
+            .fetch_optional(&self.pool)
+            .await?;
+            let feed = row
+                .as_ref()
+                .map(calendar_feed_from_row)
+                .ok_or_else(|| AppError::NotFound("Calendar feed not found".to_string()))?;
+
+            let entries = self.get_scoped_entries(&feed.scope, &feed.target).await?;
+            let name = match &feed.name {
+                Some(name) => name.clone(),
+                None => self.scope_name(&feed.scope, &feed.target).await?,
+            };
+
+            let today = Local::now().date_naive();
+            let semester = self.get_active_semester().await?;
+            let from = match &semester {
+                Some(s) => s.start_date,
+                None => semester::week_start(today),
+            };
+            let exceptions = self
+                .get_calendar_exceptions(&ExceptionFilter {
+                    client_id: None,
+                    from: Some(from),
+                    to: semester.as_ref().map(|s| s.end_date),
+                })
+                .await?;
+
+            Ok(ical::render_calendar(
+                &name,
+                semester.as_ref(),
+                &entries,
+                &exceptions,
+                Utc::now().naive_utc(),
+                today,
+            ))
+        }
+
+        /// Entries of a client (by ID), a teacher, or a location (entry room before course location).
+        async fn get_scoped_entries(
+            &self,
+            scope: &str,
+            target: &str,
+        ) -> AppResult<Vec<ScheduleEntry>> {
+            let condition = match scope {
+                ical::SCOPE_CLIENT => "se.client_id = $1::TEXT::INTEGER",
+                ical::SCOPE_TEACHER => "c.teacher = $1",
+                _ => "COALESCE(se.room, c.location) = $1",
+            };
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM schedule_entries se
+                 JOIN courses c ON se.course_id = c.id
+                 WHERE {}
+                 ORDER BY se.client_id, se.day_of_week, se.start_time",
+                SCHEDULE_ENTRY_COLUMNS, condition
+            ))
+            .bind(target)
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(schedule_entry_from_row).collect())
+        }
+
+        /// Display name of a scope target: the client's name, otherwise the target itself.
+        async fn scope_name(&self, scope: &str, target: &str) -> AppResult<String> {
+            match (scope, target.parse::<i32>()) {
+                (ical::SCOPE_CLIENT, Ok(id)) => Ok(self.get_client_by_id(id).await?.name),
+                _ => Ok(target.to_string()),
+            }
+        }
+
+        /// Subtitle of printed timetables: the semester and the printed week, if any.
+        async fn print_subtitle(&self, week: Option<i32>) -> AppResult<String> {
+            if let Some(week) = week {
+                if week < 1 || week > self.get_semester_weeks().await {
+                    return Err(AppError::BadRequest(format!(
+                        "week {} is outside the semester",
+                        week
+                    )));
+                }
+            }
+            let semester = self.get_active_semester().await?.map(|s| s.name);
+            let week = week.map(|w| format!("第 {} 周", w));
+            Ok([semester, week]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join(" · "))
+        }
+
+        /// Weekly grid of a client's, teacher's or location's timetable for printing.
+        pub async fn get_printable_timetable(
+            &self,
+            scope: &str,
+            target: &str,
+            week: Option<i32>,
+        ) -> AppResult<TimetableGrid> {
+            if !ical::FEED_SCOPES.contains(&scope) {
+                return Err(AppError::BadRequest(format!(
+                    "scope must be one of {}",
+                    ical::FEED_SCOPES.join(", ")
+                )));
+            }
+            let subtitle = self.print_subtitle(week).await?;
+            let entries = match (scope, target.parse::<i32>()) {
+                (ical::SCOPE_CLIENT, Ok(id)) => self.get_client_schedule(id).await?,
+                (ical::SCOPE_CLIENT, Err(_)) => {
+                    return Err(AppError::BadRequest(format!(
+                        "'{}' is not a client ID",
+                        target
+                    )))
+                }
+                _ => self.get_scoped_entries(scope, target).await?,
+            };
+            let title = self.scope_name(scope, target).await?;
+
+            Ok(printing::build_grid(
+                &title, &subtitle, scope, week, &entries,
+            ))
+        }
+
+        /// Weekly grids of every registered room, with entries matched as for room occupancy.
+        pub async fn get_room_timetables(
+            &self,
+            week: Option<i32>,
+        ) -> AppResult<Vec<TimetableGrid>> {
+            let subtitle = self.print_subtitle(week).await?;
+            let entries = self.get_schedule(&ScheduleFilter::default()).await?;
+
+            Ok(self
+                .get_rooms()
+                .await?
+                .into_iter()
+                .map(|room| {
+                    let in_room: Vec<ScheduleEntry> = entries
+                        .iter()
+                        .filter(|e| e.room_id == Some(room.id))
+                        .cloned()
+                        .collect();
+                    printing::build_grid(
+                        &room.name,
+                        &subtitle,
+                        ical::SCOPE_LOCATION,
+                        week,
+                        &in_room,
+                    )
+                })
+                .collect())
+        }
+
+        // Spreadsheets
+        /// Courses, entries and weekly grids of the selected clients; every client by default.
+        pub async fn get_spreadsheet_clients(
+            &self,
+            query: &SpreadsheetExportQuery,
+        ) -> AppResult<Vec<spreadsheet::ClientSheet>> {
+            let mut selected: Option<BTreeSet<i32>> = None;
+            if let Some(ids) = query.client_ids.as_deref() {
+                for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+                    let id = id.parse::<i32>().map_err(|_| {
+                        AppError::BadRequest(format!("client_ids: '{}' is not a client ID", id))
+                    })?;
+                    self.get_client_by_id(id).await?;
+                    selected.get_or_insert_with(BTreeSet::new).insert(id);
+                }
+            }
+            if let Some(template_id) = query.template_id {
+                let template = self.get_template_by_id(template_id).await?;
+                selected
+                    .get_or_insert_with(BTreeSet::new)
+                    .extend(template.clients.iter().map(|c| c.client_id));
+            }
+
+            let subtitle = self.print_subtitle(None).await?;
+            let mut clients = self.get_all_clients().await?;
+            clients.sort_by_key(|c| c.id);
+            let mut sheets = Vec::new();
+            for client in clients {
+                if selected
+                    .as_ref()
+                    .is_some_and(|ids| !ids.contains(&client.id))
+                {
+                    continue;
+                }
+                let (courses, entries) = self.get_client_timetable(client.id).await?;
+                let schedule = self.get_client_schedule(client.id).await?;
+                sheets.push(spreadsheet::ClientSheet {
+                    client_id: client.id,
+                    grid: printing::build_grid(
+                        &client.name,
+                        &subtitle,
+                        ical::SCOPE_CLIENT,
+                        None,
+                        &schedule,
+                    ),
+                    client_name: client.name,
+                    courses,
+                    entries,
+                });
+            }
+            Ok(sheets)
+        }
+
+        /// Imports the flat sheet of an XLSX workbook through the same validation and upsert as
+        /// client sync, reporting problems by row.
+        pub async fn import_spreadsheet(
+            &self,
+            data: &[u8],
+            dry_run: bool,
+        ) -> AppResult<ImportReport> {
+            let rows = spreadsheet::read_sheet(data, spreadsheet::FLAT_SHEET)
+                .map_err(AppError::BadRequest)?;
+            let (parsed, mut errors) = spreadsheet::parse_flat(&rows);
+
+            let clients = self.get_all_clients().await?;
+            let mut unmapped = Vec::new();
+            let mut results = Vec::new();
+            for flat in &parsed {
+                let Some(client) = clients.iter().find(|c| c.id == flat.client_id) else {
+                    let rows: BTreeSet<usize> = flat
+                        .course_rows
+                        .values()
+                        .chain(flat.entry_rows.values())
+                        .copied()
+                        .collect();
+                    unmapped.extend(rows.into_iter().map(|row| ImportIssue {
+                        source: format!("row {}", row),
+                        reason: format!("no client with ID {}", flat.client_id),
+                    }));
+                    continue;
+                };
+
+                let mut tx = self.pool.begin().await?;
+                lock_client(&mut tx, client.id).await?;
+
+                // The sheet has no attribute columns, so stored attributes are kept
+                let (existing_courses, existing_entries) =
+                    fetch_client_timetable(&mut tx, client.id).await?;
+                let mut courses = flat.courses.clone();
+                for course in courses.iter_mut().filter(|c| c.attributes.is_empty()) {
+                    if let Some(existing) = existing_courses.iter().find(|c| c.id == course.id) {
+                        course.attributes = existing.attributes.clone();
+                    }
+                }
+                let mut entries = flat.entries.clone();
+                for entry in entries.iter_mut().filter(|e| e.attributes.is_empty()) {
+                    if let Some(existing) = existing_entries.iter().find(|e| e.id == entry.id) {
+                        entry.attributes = existing.attributes.clone();
+                    }
+                }
+
+                let preview = self
+                    .preview_client_sync(
+                        &client.uuid,
+                        courses.clone(),
+                        entries.clone(),
+                        validation::ORIGIN_CLIENT,
+                    )
+                    .await?;
+                let (rejected, snapshot_version) = if dry_run {
+                    let validated = self
+                        .validate_sync(
+                            &mut tx,
+                            client.id,
+                            courses,
+                            entries,
+                            validation::ORIGIN_CLIENT,
+                        )
+                        .await?;
+                    (validated.rejected, None)
+                } else {
+                    let (_, _, rejected, _) = self
+                        .validate_and_apply_sync(
+                            &mut tx,
+                            client.id,
+                            courses,
+                            entries,
+                            validation::ORIGIN_CLIENT,
+                        )
+                        .await?;
+                    let snapshot =
+                        insert_timetable_snapshot(&mut tx, client.id, "import", true).await?;
+                    tx.commit().await?;
+                    (rejected, snapshot.map(|s| s.version))
+                };
+
+                errors.extend(rejected.into_iter().map(|r| {
+                    let rows = if r.item_type == "course" {
+                        &flat.course_rows
+                    } else {
+                        &flat.entry_rows
+                    };
+                    ImportIssue {
+                        source: rows.get(&r.id).map_or_else(
+                            || format!("{} {}", r.item_type, r.id),
+                            |row| format!("row {}", row),
+                        ),
+                        reason: format!("{}: {}", r.field, r.reason),
+                    }
+                }));
+
+                results.push(ImportClientResult {
+                    client_id: client.id,
+                    client_name: client.name.clone(),
+                    keys: vec![client.id.to_string()],
+                    classes: flat.rows,
+                    preview,
+                    snapshot_version,
+                    pushed: false,
+                });
+            }
+
+            Ok(ImportReport {
+                dry_run,
+                parsed: parsed.iter().map(|c| c.rows).sum(),
+                clients: results,
+                unmapped,
+                errors,
+            })
+        }
+
+        // Client groups
+        pub async fn get_client_groups(&self) -> AppResult<Vec<ClientGroup>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM client_groups ORDER BY name",
+                CLIENT_GROUP_COLUMNS
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(client_group_from_row).collect())
+        }
+
+        pub async fn get_client_group_by_id(&self, id: i32) -> AppResult<ClientGroup> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM client_groups WHERE id = $1",
+                CLIENT_GROUP_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Client group {} not found", id)))?;
+
+            Ok(client_group_from_row(&row))
+        }
+
+        /// Rejects an empty or duplicate group name and unknown clients.
+        async fn validate_client_group(
+            &self,
+            id: Option<i32>,
+            name: &str,
+            client_ids: &[i32],
+        ) -> AppResult<()> {
+            if name.is_empty() {
+                return Err(AppError::BadRequest("name: must not be empty".to_string()));
+            }
+            let taken = sqlx::query("SELECT id FROM client_groups WHERE name = $1 AND id <> $2")
+                .bind(name)
+                .bind(id.unwrap_or(0))
+                .fetch_optional(&self.pool)
+                .await?;
+            if taken.is_some() {
+                return Err(AppError::BadRequest(format!(
+                    "name: a client group named '{}' already exists",
+                    name
+                )));
+            }
+            for client_id in client_ids {
+                self.get_client_by_id(*client_id).await?;
+            }
+            Ok(())
+        }
+
+        async fn set_client_group_members(&self, id: i32, client_ids: &[i32]) -> AppResult<()> {
+            let mut tx = self.pool.begin().await?;
+            sqlx::query("DELETE FROM client_group_members WHERE group_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query(
+                "INSERT INTO client_group_members (group_id, client_id)
+                 SELECT $1, UNNEST($2::INTEGER[])
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(id)
+            .bind(client_ids)
+            .execute(&mut *tx)
+            .await?;
+            tx.commit().await?;
+            Ok(())
+        }
+
+        pub async fn create_client_group(
+            &self,
+            group: CreateClientGroup,
+        ) -> AppResult<ClientGroup> {
+            let name = group.name.trim().to_string();
+            self.validate_client_group(None, &name, &group.client_ids)
+                .await?;
+
+            let row = sqlx::query(
+                "INSERT INTO client_groups (name, description) VALUES ($1, $2) RETURNING id",
+            )
+            .bind(&name)
+            .bind(&group.description)
+            .fetch_one(&self.pool)
+            .await?;
+            let id: i32 = row.get("id");
+            self.set_client_group_members(id, &group.client_ids).await?;
+
+            self.get_client_group_by_id(id).await
+        }
+
+        pub async fn update_client_group(
+            &self,
+            id: i32,
+            update: UpdateClientGroup,
+        ) -> AppResult<ClientGroup> {
+            let current = self.get_client_group_by_id(id).await?;
+            let name = update.name.map_or(current.name, |n| n.trim().to_string());
+            let client_ids = update.client_ids.unwrap_or(current.client_ids);
+            self.validate_client_group(Some(id), &name, &client_ids)
+                .await?;
+
+            sqlx::query("UPDATE client_groups SET name = $1, description = $2 WHERE id = $3")
+                .bind(&name)
+                .bind(update.description.or(current.description))
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+            self.set_client_group_members(id, &client_ids).await?;
+
+            self.get_client_group_by_id(id).await
+        }
+
+        /// Deletes a group; announcements addressed to it keep their other targets.
+        pub async fn delete_client_group(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM client_groups WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!("Client group {} not found", id)));
+            }
+            Ok(())
+        }
+
+        // Announcements
+        pub async fn get_announcements(&self) -> AppResult<Vec<Announcement>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM announcements ORDER BY starts_at DESC, id DESC",
+                ANNOUNCEMENT_COLUMNS
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows.iter().map(announcement_from_row).collect())
+        }
+
+        pub async fn get_announcement_by_id(&self, id: i32) -> AppResult<Announcement> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM announcements WHERE id = $1",
+                ANNOUNCEMENT_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Announcement {} not found", id)))?;
+
+            Ok(announcement_from_row(&row))
+        }
+
+        /// Rejects unknown clients and groups in a target scope.
+        async fn check_scope(&self, client_ids: &[i32], group_ids: &[i32]) -> AppResult<()> {
+            for client_id in client_ids {
+                self.get_client_by_id(*client_id).await?;
+            }
+            for group_id in group_ids {
+                self.get_client_group_by_id(*group_id).await?;
+            }
+            Ok(())
+        }
+
+        pub async fn create_announcement(
+            &self,
+            announcement: CreateAnnouncement,
+        ) -> AppResult<Announcement> {
+            let title = announcement.title.trim().to_string();
+            let priority = announcement
+                .priority
+                .unwrap_or_else(|| announcements::PRIORITY_NORMAL.to_string());
+            let starts_at = announcement
+                .starts_at
+                .unwrap_or_else(|| Local::now().naive_local());
+            announcements::validate_announcement(
+                &title,
+                &priority,
+                starts_at,
+                announcement.ends_at,
+            )
+            .map_err(AppError::BadRequest)?;
+            self.check_scope(&announcement.client_ids, &announcement.group_ids)
+                .await?;
+
+            let row = sqlx::query(&format!(
+                "INSERT INTO announcements
+                     (title, body, priority, starts_at, ends_at, client_ids, group_ids)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 RETURNING {}",
+                ANNOUNCEMENT_COLUMNS
+            ))
+            .bind(&title)
+            .bind(&announcement.body)
+            .bind(&priority)
+            .bind(starts_at)
+            .bind(announcement.ends_at)
+            .bind(&announcement.client_ids)
+            .bind(&announcement.group_ids)
+            .fetch_one(&self.pool)
+            .await?;
+
+            Ok(announcement_from_row(&row))
+        }
+
+        /// Updates an announcement and clears its deliveries, so displays receive it again.
+        pub async fn update_announcement(
+            &self,
+            id: i32,
+            update: UpdateAnnouncement,
+        ) -> AppResult<Announcement> {
+            let current = self.get_announcement_by_id(id).await?;
+            let title = update.title.map_or(current.title, |t| t.trim().to_string());
+            let priority = update.priority.unwrap_or(current.priority);
+            let starts_at = update.starts_at.unwrap_or(current.starts_at);
+            let ends_at = update.ends_at.or(current.ends_at);
+            let client_ids = update.client_ids.unwrap_or(current.client_ids);
+            let group_ids = update.group_ids.unwrap_or(current.group_ids);
+            announcements::validate_announcement(&title, &priority, starts_at, ends_at)
+                .map_err(AppError::BadRequest)?;
+            self.check_scope(&client_ids, &group_ids).await?;
+
+            let mut tx = self.pool.begin().await?;
+            let row = sqlx::query(&format!(
+                "UPDATE announcements
+                 SET title = $1, body = $2, priority = $3, starts_at = $4, ends_at = $5,
+                     client_ids = $6, group_ids = $7, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = $8
+                 RETURNING {}",
+                ANNOUNCEMENT_COLUMNS
+            ))
+            .bind(&title)
+            .bind(update.body.unwrap_or(current.body))
+            .bind(&priority)
+            .bind(starts_at)
+            .bind(ends_at)
+            .bind(&client_ids)
+            .bind(&group_ids)
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+            sqlx::query("DELETE FROM announcement_deliveries WHERE announcement_id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+
+            Ok(announcement_from_row(&row))
+        }
+
+        pub async fn delete_announcement(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM announcements WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!("Announcement {} not found", id)));
+            }
+            Ok(())
+        }
+
+        /// Delivery and acknowledgement state of every client an announcement is addressed to.
+        pub async fn get_announcement_deliveries(
+            &self,
+            id: i32,
+        ) -> AppResult<Vec<AnnouncementDelivery>> {
+            let announcement = self.get_announcement_by_id(id).await?;
+            let clients = self.get_all_clients().await?;
+            let groups = self.get_client_groups().await?;
+            let rows = sqlx::query(
+                "SELECT client_id, delivered_at, acknowledged_at
+                 FROM announcement_deliveries WHERE announcement_id = $1",
+            )
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(scoped_clients(
+                &announcement.client_ids,
+                &announcement.group_ids,
+                &clients,
+                &groups,
+            )
+            .into_iter()
+            .map(|client| {
+                let row = rows
+                    .iter()
+                    .find(|r| r.get::<i32, _>("client_id") == client.id);
+                AnnouncementDelivery {
+                    client_id: client.id,
+                    client_name: client.name,
+                    delivered_at: row.and_then(|r| r.try_get("delivered_at").ok()),
+                    acknowledged_at: row.and_then(|r| r.try_get("acknowledged_at").ok()),
+                }
+            })
+            .collect())
+        }
+
+        /// Clients that have received an announcement.
+        pub async fn get_announcement_recipients(&self, id: i32) -> AppResult<Vec<Client>> {
+            let rows = sqlx::query(
+                "SELECT client_id FROM announcement_deliveries
+                 WHERE announcement_id = $1 AND delivered_at IS NOT NULL",
+            )
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let mut clients = Vec::new();
+            for row in rows {
+                clients.push(self.get_client_by_id(row.get("client_id")).await?);
             }
-
-            Ok(())
+            Ok(clients)
         }
 
-        pub async fn delete_client(&self, id: i32) -> AppResult<()> {
-            let result = sqlx::query("DELETE FROM clients WHERE id = $1")
-                .bind(id)
-                .execute(&self.pool)
-                .await?;
-
-            if result.rows_affected() == 0 {
-                return Err(AppError::NotFound("Client not found".to_string()));
+        /// Active announcements with the addressed clients that have not received them yet.
+        pub async fn get_pending_announcements(
+            &self,
+        ) -> AppResult<Vec<(Announcement, Vec<Client>)>> {
+            let now = Local::now().naive_local();
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM announcements
+                 WHERE starts_at <= $1 AND (ends_at IS NULL OR ends_at > $1)
+                 ORDER BY starts_at, id",
+                ANNOUNCEMENT_COLUMNS
+            ))
+            .bind(now)
+            .fetch_all(&self.pool)
+            .await?;
+            if rows.is_empty() {
+                return Ok(Vec::new());
             }
 
-            Ok(())
+            let clients = self.get_all_clients().await?;
+            let groups = self.get_client_groups().await?;
+            let delivered: Vec<(i32, i32)> = sqlx::query(
+                "SELECT announcement_id, client_id FROM announcement_deliveries
+                 WHERE delivered_at IS NOT NULL",
+            )
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|r| (r.get("announcement_id"), r.get("client_id")))
+            .collect();
+
+            let mut pending = Vec::new();
+            for announcement in rows.iter().map(announcement_from_row) {
+                let targets: Vec<Client> = scoped_clients(
+                    &announcement.client_ids,
+                    &announcement.group_ids,
+                    &clients,
+                    &groups,
+                )
+                .into_iter()
+                .filter(|c| !delivered.contains(&(announcement.id, c.id)))
+                .collect();
+                if !targets.is_empty() {
+                    pending.push((announcement, targets));
+                }
+            }
+            Ok(pending)
         }
 
-        pub async fn update_client_status(&self, id: i32, status: &str) -> AppResult<()> {
-            sqlx::query("UPDATE clients SET status = $1 WHERE id = $2")
-                .bind(status)
-                .bind(id)
-                .execute(&self.pool)
-                .await?;
-
+        pub async fn mark_announcement_delivered(
+            &self,
+            announcement_id: i32,
+            client_id: i32,
+        ) -> AppResult<()> {
+            sqlx::query(
+                "INSERT INTO announcement_deliveries (announcement_id, client_id, delivered_at)
+                 VALUES ($1, $2, CURRENT_TIMESTAMP)
+                 ON CONFLICT (announcement_id, client_id) DO UPDATE
+                 SET delivered_at = COALESCE(announcement_deliveries.delivered_at, CURRENT_TIMESTAMP)",
+            )
+            .bind(announcement_id)
+            .bind(client_id)
+            .execute(&self.pool)
+            .await?;
             Ok(())
         }
 
-        pub async fn update_client_last_sync(&self, id: i32) -> AppResult<()> {
-            let now = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-
-            sqlx::query("UPDATE clients SET last_sync = $1 WHERE id = $2")
-                .bind(&now)
-                .bind(id)
-                .execute(&self.pool)
-                .await?;
+        /// Active announcements addressed to a client, e.g. fetched after reconnecting; they
+        /// count as delivered.
+        pub async fn get_client_announcements(
+            &self,
+            client_uuid: &str,
+        ) -> AppResult<Vec<Announcement>> {
+            let client = self.get_client_by_uuid(client_uuid).await?;
+            let now = Local::now().naive_local();
+            let groups = self.get_client_groups().await?;
+            let active: Vec<Announcement> = self
+                .get_announcements()
+                .await?
+                .into_iter()
+                .filter(|a| announcements::is_active(a, now))
+                .filter(|a| announcements::targets(a, client.id, &groups))
+                .collect();
 
-            Ok(())
+            for announcement in &active {
+                self.mark_announcement_delivered(announcement.id, client.id)
+                    .await?;
+            }
+            Ok(active)
         }
 
-        // Sync operations
-        pub async fn sync_client_data(
+        /// Records that a client's display has shown an announcement.
+        pub async fn acknowledge_announcement(
             &self,
+            id: i32,
             client_uuid: &str,
-            courses: Vec<ClientCourse>,
-            entries: Vec<ClientScheduleEntry>,
-        ) -> AppResult<SyncResponse> {
+        ) -> AppResult<AnnouncementDelivery> {
+            let announcement = self.get_announcement_by_id(id).await?;
             let client = self.get_client_by_uuid(client_uuid).await?;
-            let client_id = client.id;
+            let groups = self.get_client_groups().await?;
+            if !announcements::targets(&announcement, client.id, &groups) {
+                return Err(AppError::BadRequest(format!(
+                    "Announcement {} is not addressed to client {}",
+                    id, client.id
+                )));
+            }
 
-            let mut synced_courses = 0;
-            let mut synced_entries = 0;
+            let row = sqlx::query(
+                "INSERT INTO announcement_deliveries
+                     (announcement_id, client_id, delivered_at, acknowledged_at)
+                 VALUES ($1, $2, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+                 ON CONFLICT (announcement_id, client_id) DO UPDATE
+                 SET delivered_at = COALESCE(announcement_deliveries.delivered_at, CURRENT_TIMESTAMP),
+                     acknowledged_at = CURRENT_TIMESTAMP
+                 RETURNING delivered_at, acknowledged_at",
+            )
+            .bind(id)
+            .bind(client.id)
+            .fetch_one(&self.pool)
+            .await?;
 
-            // Sync courses
-            for course in courses {
-                let exists = sqlx::query(
-                    "SELECT id FROM courses WHERE client_id = $1 AND course_id_on_client = $2",
-                )
-                .bind(client_id)
-                .bind(course.id)
-                .fetch_optional(&self.pool)
-                .await?;
+            Ok(AnnouncementDelivery {
+                client_id: client.id,
+                client_name: client.name,
+                delivered_at: row.try_get("delivered_at").ok(),
+                acknowledged_at: row.try_get("acknowledged_at").ok(),
+            })
+        }
 
-                if exists.is_some() {
-                    // Update existing course
-                    sqlx::query(
-                        "UPDATE courses SET name = $1, teacher = $2, color = $3, note = $4, synced_at = $5
-                         WHERE client_id = $6 AND course_id_on_client = $7"
-                    )
-                    .bind(&course.name)
-                    .bind(&course.teacher)
-                    .bind(&course.color)
-                    .bind(&course.note)
-                    .bind(Utc::now().format("%Y-%m-%d %H:%M:%S").to_string())
-                    .bind(client_id)
-                    .bind(course.id)
-                    .execute(&self.pool)
-                    .await?;
-                } else {
-                    // Insert new course
-                    sqlx::query(
-                        "INSERT INTO courses (client_id, course_id_on_client, name, teacher, color, note)
-                         VALUES ($1, $2, $3, $4, $5, $6)"
-                    )
-                    .bind(client_id)
-                    .bind(course.id)
-                    .bind(&course.name)
-                    .bind(&course.teacher)
-                    .bind(&course.color)
-                    .bind(&course.note)
-                    .execute(&self.pool)
-                    .await?;
-                }
-                synced_courses += 1;
-            }
+        // Emergency alerts
+        pub async fn get_emergency_alerts(&self) -> AppResult<Vec<EmergencyAlert>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM emergency_alerts ORDER BY started_at DESC, id DESC",
+                EMERGENCY_ALERT_COLUMNS
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.iter().map(emergency_alert_from_row).collect())
+        }
 
-            // Sync schedule entries
-            for entry in entries {
-                // Find the course_id in our database
-                let course_row = sqlx::query(
-                    "SELECT id FROM courses WHERE client_id = $1 AND course_id_on_client = $2",
-                )
-                .bind(client_id)
-                .bind(entry.course_id)
-                .fetch_optional(&self.pool)
-                .await?;
+        pub async fn get_active_emergency_alerts(&self) -> AppResult<Vec<EmergencyAlert>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM emergency_alerts WHERE cleared_at IS NULL
+                 ORDER BY started_at, id",
+                EMERGENCY_ALERT_COLUMNS
+            ))
+            .fetch_all(&self.pool)
+            .await?;
+            Ok(rows.iter().map(emergency_alert_from_row).collect())
+        }
 
-                if let Some(course_row) = course_row {
-                    let course_id: i32 = course_row.get("id");
-                    let weeks_json = entry.weeks.map(|w| serde_json::to_string(&w).unwrap());
+        pub async fn get_emergency_alert_by_id(&self, id: i32) -> AppResult<EmergencyAlert> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM emergency_alerts WHERE id = $1",
+                EMERGENCY_ALERT_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Emergency alert {} not found", id)))?;
+            Ok(emergency_alert_from_row(&row))
+        }
 
-                    let exists = sqlx::query(
-                        "SELECT id FROM schedule_entries WHERE client_id = $1 AND entry_id_on_client = $2"
-                    )
-                    .bind(client_id)
-                    .bind(entry.id)
-                    .fetch_optional(&self.pool)
-                    .await?;
+        pub async fn create_emergency_alert(
+            &self,
+            alert: CreateEmergencyAlert,
+        ) -> AppResult<EmergencyAlert> {
+            let message = alert.message.trim().to_string();
+            emergency::validate_alert(&alert.kind, &message).map_err(AppError::BadRequest)?;
+            self.check_scope(&alert.client_ids, &alert.group_ids)
+                .await?;
 
-                    if exists.is_some() {
-                        // Update existing entry
-                        sqlx::query(
-                            "UPDATE schedule_entries
-                             SET course_id = $1, day_of_week = $2, start_time = $3, end_time = $4, weeks = $5, synced_at = $6
-                             WHERE client_id = $7 AND entry_id_on_client = $8"
-                        )
-                        .bind(course_id)
-                        .bind(entry.day_of_week)
-                        .bind(&entry.start_time)
-                        .bind(&entry.end_time)
-                        .bind(&weeks_json)
-                        .bind(Utc::now().format("%Y-%m-%d %H:%M:%S").to_string())
-                        .bind(client_id)
-                        .bind(entry.id)
-                        .execute(&self.pool)
-                        .await?;
-                    } else {
-                        // Insert new entry
-                        sqlx::query(
-                            "INSERT INTO schedule_entries (client_id, entry_id_on_client, course_id, day_of_week, start_time, end_time, weeks)
-                             VALUES ($1, $2, $3, $4, $5, $6, $7)"
-                        )
-                        .bind(client_id)
-                        .bind(entry.id)
-                        .bind(course_id)
-                        .bind(entry.day_of_week)
-                        .bind(&entry.start_time)
-                        .bind(&entry.end_time)
-                        .bind(&weeks_json)
-                        .execute(&self.pool)
-                        .await?;
-                    }
-                    synced_entries += 1;
-                }
+            let row = sqlx::query(&format!(
+                "INSERT INTO emergency_alerts (kind, message, drill, client_ids, group_ids)
+                 VALUES ($1, $2, $3, $4, $5)
+                 RETURNING {}",
+                EMERGENCY_ALERT_COLUMNS
+            ))
+            .bind(&alert.kind)
+            .bind(&message)
+            .bind(alert.drill)
+            .bind(&alert.client_ids)
+            .bind(&alert.group_ids)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(emergency_alert_from_row(&row))
+        }
+
+        /// Ends an alert; displays are told to return to their normal content.
+        pub async fn clear_emergency_alert(&self, id: i32) -> AppResult<EmergencyAlert> {
+            let alert = self.get_emergency_alert_by_id(id).await?;
+            if alert.cleared_at.is_some() {
+                return Err(AppError::BadRequest(format!(
+                    "Emergency alert {} is already cleared",
+                    id
+                )));
             }
+            let row = sqlx::query(&format!(
+                "UPDATE emergency_alerts SET cleared_at = CURRENT_TIMESTAMP
+                 WHERE id = $1 RETURNING {}",
+                EMERGENCY_ALERT_COLUMNS
+            ))
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(emergency_alert_from_row(&row))
+        }
 
-            // Update client last sync time and status
-            self.update_client_last_sync(client_id).await?;
-            self.update_client_status(client_id, "online").await?;
+        /// Clients an alert is addressed to, ordered by ID.
+        pub async fn get_emergency_targets(
+            &self,
+            alert: &EmergencyAlert,
+        ) -> AppResult<Vec<Client>> {
+            let clients = self.get_all_clients().await?;
+            let groups = self.get_client_groups().await?;
+            Ok(scoped_clients(
+                &alert.client_ids,
+                &alert.group_ids,
+                &clients,
+                &groups,
+            ))
+        }
 
-            // Log sync
+        /// Target clients of an active alert that have not acknowledged it yet.
+        pub async fn get_unacknowledged_emergency_targets(
+            &self,
+            alert: &EmergencyAlert,
+        ) -> AppResult<Vec<Client>> {
+            let acknowledged: Vec<i32> = sqlx::query(
+                "SELECT client_id FROM emergency_deliveries
+                 WHERE alert_id = $1 AND acknowledged_at IS NOT NULL",
+            )
+            .bind(alert.id)
+            .fetch_all(&self.pool)
+            .await?
+            .iter()
+            .map(|r| r.get("client_id"))
+            .collect();
+
+            Ok(self
+                .get_emergency_targets(alert)
+                .await?
+                .into_iter()
+                .filter(|c| !acknowledged.contains(&c.id))
+                .collect())
+        }
+
+        pub async fn record_emergency_sent(&self, alert_id: i32, client_id: i32) -> AppResult<()> {
             sqlx::query(
-                "INSERT INTO sync_logs (client_id, sync_type, status, courses_count, entries_count)
-                 VALUES ($1, 'full', 'success', $2, $3)",
+                "INSERT INTO emergency_deliveries (alert_id, client_id, attempts, last_sent_at)
+                 VALUES ($1, $2, 1, CURRENT_TIMESTAMP)
+                 ON CONFLICT (alert_id, client_id) DO UPDATE
+                 SET attempts = emergency_deliveries.attempts + 1,
+                     last_sent_at = CURRENT_TIMESTAMP",
             )
+            .bind(alert_id)
             .bind(client_id)
-            .bind(synced_courses)
-            .bind(synced_entries)
             .execute(&self.pool)
             .await?;
+            Ok(())
+        }
 
-            Ok(SyncResponse {
-                success: true,
-                message: "Data synced successfully".to_string(),
-                synced_courses,
-                synced_entries,
-            })
+        /// Records that a client's display has shown an alert; repeated acknowledgements keep
+        /// the first time.
+        pub async fn acknowledge_emergency(
+            &self,
+            alert_id: i32,
+            client_uuid: &str,
+        ) -> AppResult<()> {
+            let alert = self.get_emergency_alert_by_id(alert_id).await?;
+            let client = self.get_client_by_uuid(client_uuid).await?;
+            let groups = self.get_client_groups().await?;
+            if !announcements::in_scope(&alert.client_ids, &alert.group_ids, client.id, &groups) {
+                return Err(AppError::BadRequest(format!(
+                    "Emergency alert {} is not addressed to client {}",
+                    alert_id, client.id
+                )));
+            }
+
+            sqlx::query(
+                "INSERT INTO emergency_deliveries (alert_id, client_id, acknowledged_at)
+                 VALUES ($1, $2, CURRENT_TIMESTAMP)
+                 ON CONFLICT (alert_id, client_id) DO UPDATE
+                 SET acknowledged_at = COALESCE(emergency_deliveries.acknowledged_at, CURRENT_TIMESTAMP)",
+            )
+            .bind(alert_id)
+            .bind(client.id)
+            .execute(&self.pool)
+            .await?;
+            Ok(())
         }
 
-        // Get client data
-        pub async fn get_client_courses(&self, client_id: i32) -> AppResult<Vec<Course>> {
+        /// Acknowledgement dashboard of an alert; `online` lists the UUIDs of connected clients.
+        pub async fn get_emergency_status(
+            &self,
+            alert: EmergencyAlert,
+            online: &[uuid::Uuid],
+        ) -> AppResult<EmergencyStatus> {
+            let targets = self.get_emergency_targets(&alert).await?;
+            let rooms = self.get_rooms().await?;
             let rows = sqlx::query(
-                "SELECT id, client_id, course_id_on_client, name, teacher, color, note
-                 FROM courses WHERE client_id = $1 ORDER BY name",
+                "SELECT client_id, attempts, last_sent_at, acknowledged_at
+                 FROM emergency_deliveries WHERE alert_id = $1",
             )
-            .bind(client_id)
+            .bind(alert.id)
             .fetch_all(&self.pool)
             .await?;
 
-            let courses = rows
-                .iter()
-                .map(|row| Course {
-                    id: row.get("id"),
-                    client_id: row.get("client_id"),
-                    course_id_on_client: row.get("course_id_on_client"),
-                    name: row.get("name"),
-                    teacher: row.try_get("teacher").ok(),
-                    location: None,
-                    color: row.try_get("color").ok(),
-                    note: row.try_get("note").ok(),
+            let clients = targets
+                .into_iter()
+                .map(|client| {
+                    let row = rows
+                        .iter()
+                        .find(|r| r.get::<i32, _>("client_id") == client.id);
+                    EmergencyClientStatus {
+                        room: client
+                            .room_id
+                            .and_then(|id| rooms.iter().find(|r| r.id == id))
+                            .map(|r| r.name.clone()),
+                        connected: uuid::Uuid::parse_str(&client.uuid)
+                            .is_ok_and(|uuid| online.contains(&uuid)),
+                        attempts: row.map_or(0, |r| r.get("attempts")),
+                        last_sent_at: row.and_then(|r| r.try_get("last_sent_at").ok()),
+                        acknowledged_at: row.and_then(|r| r.try_get("acknowledged_at").ok()),
+                        client_id: client.id,
+                        client_name: client.name,
+                    }
                 })
                 .collect();
-
-            Ok(courses)
+            Ok(emergency::summarize(alert, clients))
         }
 
-        pub async fn get_client_schedule(&self, client_id: i32) -> AppResult<Vec<ScheduleEntry>> {
-            let rows = sqlx::query(
-                "SELECT se.id, se.client_id, se.entry_id_on_client, se.course_id,
-                        c.name as course_name, c.teacher, c.color,
-                        se.day_of_week, se.start_time, se.end_time, se.weeks
-                 FROM schedule_entries se
-                 JOIN courses c ON se.course_id = c.id
-                 WHERE se.client_id = $1
-                 ORDER BY se.day_of_week, se.start_time",
-            )
-            .bind(client_id)
+        // Display events
+        pub async fn get_display_events(&self) -> AppResult<Vec<DisplayEvent>> {
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM display_events ORDER BY target_date, id",
+                DISPLAY_EVENT_COLUMNS
+            ))
             .fetch_all(&self.pool)
             .await?;
+            Ok(rows.iter().map(display_event_from_row).collect())
+        }
 
-            let entries = rows
-                .iter()
-                .map(|row| {
-                    let weeks_str: Option<String> = row.try_get("weeks").ok();
-                    let weeks: Option<Vec<i32>> =
-                        weeks_str.and_then(|s| serde_json::from_str(&s).ok());
-
-                    ScheduleEntry {
-                        id: row.get("id"),
-                        client_id: row.get("client_id"),
-                        entry_id_on_client: row.get("entry_id_on_client"),
-                        course_id: row.get("course_id"),
-                        course_name: row.try_get("course_name").ok(),
-                        teacher: row.try_get("teacher").ok(),
-                        location: None,
-                        color: row.try_get("color").ok(),
-                        day_of_week: row.get("day_of_week"),
-                        start_time: row.get("start_time"),
-                        end_time: row.get("end_time"),
-                        weeks,
-                        note: None,
-                    }
+        pub async fn get_display_event_by_id(&self, id: i32) -> AppResult<DisplayEvent> {
+            let row = sqlx::query(&format!(
+                "SELECT {} FROM display_events WHERE id = $1",
+                DISPLAY_EVENT_COLUMNS
+            ))
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Display event {} not found", id)))?;
+            Ok(display_event_from_row(&row))
+        }
+
+        pub async fn create_display_event(
+            &self,
+            event: CreateDisplayEvent,
+        ) -> AppResult<DisplayEvent> {
+            let label = event.label.trim().to_string();
+            let style = event
+                .style
+                .unwrap_or_else(|| events::STYLE_DEFAULT.to_string());
+            events::validate_event(&label, &style, event.target_date, event.show_from)
+                .map_err(AppError::BadRequest)?;
+            self.check_scope(&event.client_ids, &event.group_ids)
+                .await?;
+
+            let row = sqlx::query(&format!(
+                "INSERT INTO display_events
+                     (label, target_date, style, show_from, client_ids, group_ids)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 RETURNING {}",
+                DISPLAY_EVENT_COLUMNS
+            ))
+            .bind(&label)
+            .bind(event.target_date)
+            .bind(&style)
+            .bind(event.show_from)
+            .bind(&event.client_ids)
+            .bind(&event.group_ids)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(display_event_from_row(&row))
+        }
+
+        pub async fn update_display_event(
+            &self,
+            id: i32,
+            update: UpdateDisplayEvent,
+        ) -> AppResult<DisplayEvent> {
+            let current = self.get_display_event_by_id(id).await?;
+            let label = update.label.map_or(current.label, |l| l.trim().to_string());
+            let target_date = update.target_date.unwrap_or(current.target_date);
+            let style = update.style.unwrap_or(current.style);
+            let show_from = update.show_from.or(current.show_from);
+            let client_ids = update.client_ids.unwrap_or(current.client_ids);
+            let group_ids = update.group_ids.unwrap_or(current.group_ids);
+            events::validate_event(&label, &style, target_date, show_from)
+                .map_err(AppError::BadRequest)?;
+            self.check_scope(&client_ids, &group_ids).await?;
+
+            let row = sqlx::query(&format!(
+                "UPDATE display_events
+                 SET label = $1, target_date = $2, style = $3, show_from = $4,
+                     client_ids = $5, group_ids = $6, updated_at = CURRENT_TIMESTAMP
+                 WHERE id = $7
+                 RETURNING {}",
+                DISPLAY_EVENT_COLUMNS
+            ))
+            .bind(&label)
+            .bind(target_date)
+            .bind(&style)
+            .bind(show_from)
+            .bind(&client_ids)
+            .bind(&group_ids)
+            .bind(id)
+            .fetch_one(&self.pool)
+            .await?;
+            Ok(display_event_from_row(&row))
+        }
+
+        pub async fn delete_display_event(&self, id: i32) -> AppResult<()> {
+            let result = sqlx::query("DELETE FROM display_events WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+
+            if result.rows_affected() == 0 {
+                return Err(AppError::NotFound(format!(
+                    "Display event {} not found",
+                    id
+                )));
+            }
+            Ok(())
+        }
+
+        /// Clients a display event is shown on, ordered by ID.
+        pub async fn get_display_event_targets(
+            &self,
+            event: &DisplayEvent,
+        ) -> AppResult<Vec<Client>> {
+            let clients = self.get_all_clients().await?;
+            let groups = self.get_client_groups().await?;
+            Ok(scoped_clients(
+                &event.client_ids,
+                &event.group_ids,
+                &clients,
+                &groups,
+            ))
+        }
+
+        pub async fn get_client_countdowns(
+            &self,
+            client_id: i32,
+            today: NaiveDate,
+        ) -> AppResult<Vec<Countdown>> {
+            let display_events = self.get_display_events().await?;
+            let groups = self.get_client_groups().await?;
+            Ok(events::countdowns(
+                &display_events,
+                client_id,
+                &groups,
+                today,
+            ))
+        }
+
+        /// Everything a classroom display renders next to its timetable at `at`.
+        pub async fn get_display_content(
+            &self,
+            client_id: i32,
+            at: NaiveDateTime,
+        ) -> AppResult<DisplayContent> {
+            let client = self.get_client_by_id(client_id).await?;
+            let now_next = self
+                .get_now_next(Some(client.id), at)
+                .await?
+                .into_iter()
+                .next()
+                .ok_or_else(|| AppError::NotFound("Client not found".to_string()))?;
+            let groups = self.get_client_groups().await?;
+            let countdowns = events::countdowns(
+                &self.get_display_events().await?,
+                client.id,
+                &groups,
+                at.date(),
+            );
+            let announcements: Vec<Announcement> = self
+                .get_announcements()
+                .await?
+                .into_iter()
+                .filter(|a| announcements::is_active(a, at))
+                .filter(|a| announcements::targets(a, client.id, &groups))
+                .collect();
+            let emergency_alerts: Vec<EmergencyAlert> = self
+                .get_active_emergency_alerts()
+                .await?
+                .into_iter()
+                .filter(|e| {
+                    announcements::in_scope(&e.client_ids, &e.group_ids, client.id, &groups)
                 })
                 .collect();
 
-            Ok(entries)
+            Ok(DisplayContent {
+                client_id: client.id,
+                client_name: client.name,
+                date: at.date(),
+                now_next,
+                countdowns,
+                announcements,
+                emergency_alerts,
+            })
         }
 
         // Statistics
@@ -528,7 +4884,11 @@ pub mod repository {
                 .fetch_all(&self.pool)
                 .await?;
 
-            let mut settings = std::collections::HashMap::new();
+            // Registered settings that were never stored report their default
+            let mut settings: std::collections::HashMap<String, String> = settings::SETTINGS
+                .iter()
+                .map(|s| (s.key.to_string(), s.default_value()))
+                .collect();
             for row in rows {
                 let key: String = row.get("key");
                 let value: String = row.get("value");
@@ -549,20 +4909,48 @@ pub mod repository {
                     key: row.get("key"),
                     value: row.get("value"),
                 }),
-                None => Err(AppError::NotFound("Setting not found".to_string())),
+                None => match settings::find(key) {
+                    Some(definition) => Ok(Setting {
+                        key: key.to_string(),
+                        value: definition.default_value(),
+                    }),
+                    None => Err(AppError::NotFound("Setting not found".to_string())),
+                },
             }
         }
 
+        /// Validates a value against the settings registry and stores it normalized.
         pub async fn update_setting(&self, key: &str, value: &str) -> AppResult<()> {
+            let value = settings::validate(key, value).map_err(AppError::BadRequest)?;
             sqlx::query("INSERT INTO settings (key, value) VALUES ($1, $2) ON CONFLICT (key) DO UPDATE SET value = $2")
                 .bind(key)
-                .bind(value)
+                .bind(&value)
                 .execute(&self.pool)
                 .await?;
 
             Ok(())
         }
 
+        /// Restores every registered setting except `exclude` to its default.
+        pub async fn reset_settings(
+            &self,
+            exclude: &[String],
+        ) -> AppResult<std::collections::HashMap<String, String>> {
+            let targets = settings::reset_targets(exclude).map_err(AppError::BadRequest)?;
+
+            let mut tx = self.pool.begin().await?;
+            for definition in targets {
+                sqlx::query("INSERT INTO settings (key, value) VALUES ($1, $2) ON CONFLICT (key) DO UPDATE SET value = $2")
+                    .bind(definition.key)
+                    .bind(definition.default_value())
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            tx.commit().await?;
+
+            self.get_all_settings().await
+        }
+
         // LMS Instance operations
         pub async fn register_lms(
             &self,
@@ -723,7 +5111,7 @@ pub mod repository {
         pub async fn get_clients_by_lms(&self, lms_id: &str) -> AppResult<Vec<Client>> {
             let rows = sqlx::query(
                 "SELECT c.id, c.uuid, c.name, c.description, c.api_url, c.api_key,
-                        c.last_sync, c.status, c.created_at
+                        c.last_sync, c.status, c.created_at, c.room_id
                  FROM clients c
                  INNER JOIN lms_client_mapping lcm ON c.id = lcm.client_id
                  WHERE lcm.lms_id = $1
@@ -745,6 +5133,7 @@ pub mod repository {
                     last_sync: row.try_get::<NaiveDateTime, _>("last_sync").ok(),
                     status: row.get("status"),
                     created_at: row.get("created_at"),
+                    room_id: row.try_get("room_id").ok(),
                 })
                 .collect();
 
@@ -1137,7 +5526,7 @@ pub mod repository {
             // Get paginated results
             let rows = sqlx::query(
                 "SELECT id, uuid, name, description, api_url, api_key,
-                        last_sync, status, created_at
+                        last_sync, status, created_at, room_id
                  FROM clients ORDER BY created_at DESC
                  LIMIT $1 OFFSET $2",
             )
@@ -1158,6 +5547,7 @@ pub mod repository {
                     last_sync: row.try_get::<NaiveDateTime, _>("last_sync").ok(),
                     status: row.get("status"),
                     created_at: row.get("created_at"),
+                    room_id: row.try_get("room_id").ok(),
                 })
                 .collect();
 
@@ -1177,29 +5567,75 @@ pub mod repository {
             let total: i64 = count_row.get("count");
 
             // Get paginated results
-            let rows = sqlx::query(
-                "SELECT id, client_id, course_id_on_client, name, teacher, location, color, note
-                 FROM courses ORDER BY id DESC
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM courses ORDER BY id DESC
                  LIMIT $1 OFFSET $2",
+                COURSE_COLUMNS
+            ))
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+
+            let courses = rows.iter().map(course_from_row).collect();
+
+            Ok((courses, total))
+        }
+
+        pub async fn search_courses(
+            &self,
+            params: &CourseSearchParams,
+            offset: i64,
+            limit: i64,
+        ) -> AppResult<(Vec<Course>, i64)> {
+            // Match on a single attribute through the GIN index (attributes @> {key: value})
+            let attribute_filter = match (&params.attribute, &params.value) {
+                (Some(key), Some(value)) => {
+                    let value = serde_json::from_str(value)
+                        .unwrap_or_else(|_| serde_json::Value::String(value.clone()));
+                    let mut filter = Attributes::new();
+                    filter.insert(key.clone(), value);
+                    Some(serde_json::Value::Object(filter))
+                }
+                (Some(_), None) | (None, Some(_)) => {
+                    return Err(AppError::BadRequest(
+                        "attribute and value must be given together".to_string(),
+                    ))
+                }
+                (None, None) => None,
+            };
+
+            let count_row = sqlx::query(
+                "SELECT COUNT(*) as count FROM courses
+                 WHERE ($1::INTEGER IS NULL OR client_id = $1)
+                   AND ($2::VARCHAR IS NULL OR location = $2)
+                   AND ($3::JSONB IS NULL OR attributes @> $3)",
             )
+            .bind(params.client_id)
+            .bind(&params.location)
+            .bind(&attribute_filter)
+            .fetch_one(&self.pool)
+            .await?;
+            let total: i64 = count_row.get("count");
+
+            let rows = sqlx::query(&format!(
+                "SELECT {} FROM courses
+                 WHERE ($1::INTEGER IS NULL OR client_id = $1)
+                   AND ($2::VARCHAR IS NULL OR location = $2)
+                   AND ($3::JSONB IS NULL OR attributes @> $3)
+                 ORDER BY id DESC
+                 LIMIT $4 OFFSET $5",
+                COURSE_COLUMNS
+            ))
+            .bind(params.client_id)
+            .bind(&params.location)
+            .bind(&attribute_filter)
             .bind(limit)
             .bind(offset)
             .fetch_all(&self.pool)
             .await?;
 
-            let courses = rows
-                .iter()
-                .map(|row| Course {
-                    id: row.get("id"),
-                    client_id: row.get("client_id"),
-                    course_id_on_client: row.get("course_id_on_client"),
-                    name: row.get("name"),
-                    teacher: row.try_get("teacher").ok(),
-                    location: row.try_get("location").ok(),
-                    color: row.try_get("color").ok(),
-                    note: row.try_get("note").ok(),
-                })
-                .collect();
+            let courses = rows.iter().map(course_from_row).collect();
 
             Ok((courses, total))
         }
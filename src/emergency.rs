@@ -0,0 +1,146 @@
+use crate::models::{EmergencyAlert, EmergencyClientStatus, EmergencyStatus};
+
+/// 警报类型
+pub const KIND_FIRE: &str = "fire";
+pub const KIND_LOCKDOWN: &str = "lockdown";
+pub const KIND_EVACUATION: &str = "evacuation";
+pub const KIND_SHELTER: &str = "shelter"; // 就地避险，如恶劣天气
+pub const KIND_OTHER: &str = "other";
+
+pub const ALERT_KINDS: &[&str] = &[
+    KIND_FIRE,
+    KIND_LOCKDOWN,
+    KIND_EVACUATION,
+    KIND_SHELTER,
+    KIND_OTHER,
+];
+
+pub const MAX_MESSAGE_LEN: usize = 500;
+
+/// 向未确认的客户端重发警报的间隔
+pub const REPEAT_INTERVAL_SECS: u64 = 5;
+
+/// 校验警报字段，返回 `字段: 原因` 形式的错误
+pub fn validate_alert(kind: &str, message: &str) -> Result<(), String> {
+    if !ALERT_KINDS.contains(&kind) {
+        return Err(format!("kind: must be one of {}", ALERT_KINDS.join(", ")));
+    }
+    if message.trim().is_empty() {
+        return Err("message: must not be empty".to_string());
+    }
+    if message.chars().count() > MAX_MESSAGE_LEN {
+        return Err(format!(
+            "message: must be at most {} characters",
+            MAX_MESSAGE_LEN
+        ));
+    }
+    Ok(())
+}
+
+/// 汇总确认情况：未确认的客户端排在前面（其中离线的在前），并列出未确认的教室
+pub fn summarize(
+    alert: EmergencyAlert,
+    mut clients: Vec<EmergencyClientStatus>,
+) -> EmergencyStatus {
+    clients.sort_by(|a, b| {
+        (
+            a.acknowledged_at.is_some(),
+            a.connected,
+            &a.room,
+            a.client_id,
+        )
+            .cmp(&(
+                b.acknowledged_at.is_some(),
+                b.connected,
+                &b.room,
+                b.client_id,
+            ))
+    });
+    let mut unconfirmed_rooms: Vec<String> = clients
+        .iter()
+        .filter(|c| c.acknowledged_at.is_none())
+        .map(|c| c.room.clone().unwrap_or_else(|| c.client_name.clone()))
+        .collect();
+    unconfirmed_rooms.sort();
+    unconfirmed_rooms.dedup();
+
+    EmergencyStatus {
+        total: clients.len(),
+        acknowledged: clients
+            .iter()
+            .filter(|c| c.acknowledged_at.is_some())
+            .count(),
+        unconfirmed_rooms,
+        alert,
+        clients,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn client(
+        id: i32,
+        room: Option<&str>,
+        connected: bool,
+        acknowledged: bool,
+    ) -> EmergencyClientStatus {
+        let at = NaiveDate::from_ymd_opt(2024, 10, 28)
+            .unwrap()
+            .and_hms_opt(10, 0, 5)
+            .unwrap();
+        EmergencyClientStatus {
+            client_id: id,
+            client_name: format!("Display {}", id),
+            room: room.map(str::to_string),
+            connected,
+            attempts: 1,
+            last_sent_at: Some(at),
+            acknowledged_at: acknowledged.then_some(at),
+        }
+    }
+
+    #[test]
+    fn test_validate_alert() {
+        assert!(validate_alert(KIND_FIRE, "Leave the building by the nearest exit").is_ok());
+        assert_eq!(
+            validate_alert("flood", "Move upstairs"),
+            Err("kind: must be one of fire, lockdown, evacuation, shelter, other".to_string())
+        );
+        assert!(validate_alert(KIND_LOCKDOWN, "  ").is_err());
+    }
+
+    #[test]
+    fn test_summarize_lists_unconfirmed_rooms() {
+        let alert = EmergencyAlert {
+            id: 1,
+            kind: KIND_FIRE.to_string(),
+            message: "Drill".to_string(),
+            drill: true,
+            client_ids: Vec::new(),
+            group_ids: Vec::new(),
+            started_at: NaiveDate::from_ymd_opt(2024, 10, 28)
+                .unwrap()
+                .and_hms_opt(10, 0, 0)
+                .unwrap(),
+            cleared_at: None,
+        };
+        let status = summarize(
+            alert,
+            vec![
+                client(1, Some("A101"), true, true),
+                client(2, Some("B201"), true, false),
+                client(3, None, false, false),
+                client(4, Some("B201"), false, false),
+            ],
+        );
+
+        assert_eq!(status.total, 4);
+        assert_eq!(status.acknowledged, 1);
+        assert_eq!(status.unconfirmed_rooms, vec!["B201", "Display 3"]);
+        let order: Vec<i32> = status.clients.iter().map(|c| c.client_id).collect();
+        assert_eq!(order, vec![3, 4, 2, 1]);
+    }
+}
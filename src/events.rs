@@ -0,0 +1,137 @@
+use crate::announcements;
+use crate::models::{ClientGroup, Countdown, DisplayEvent};
+use chrono::NaiveDate;
+
+/// 倒计时的显示样式
+pub const STYLE_DEFAULT: &str = "default";
+pub const STYLE_EXAM: &str = "exam";
+pub const STYLE_SPORT: &str = "sport";
+pub const STYLE_HOLIDAY: &str = "holiday";
+pub const STYLE_CELEBRATION: &str = "celebration";
+
+pub const STYLES: &[&str] = &[
+    STYLE_DEFAULT,
+    STYLE_EXAM,
+    STYLE_SPORT,
+    STYLE_HOLIDAY,
+    STYLE_CELEBRATION,
+];
+
+pub const MAX_LABEL_LEN: usize = 100;
+
+/// 倒计时变化时推送给显示屏的命令，参数为该客户端当前的全部倒计时
+pub const COMMAND_UPDATE: &str = "update_countdowns";
+
+/// 校验倒计时字段，返回 `字段: 原因` 形式的错误
+pub fn validate_event(
+    label: &str,
+    style: &str,
+    target_date: NaiveDate,
+    show_from: Option<NaiveDate>,
+) -> Result<(), String> {
+    if label.trim().is_empty() {
+        return Err("label: must not be empty".to_string());
+    }
+    if label.chars().count() > MAX_LABEL_LEN {
+        return Err(format!(
+            "label: must be at most {} characters",
+            MAX_LABEL_LEN
+        ));
+    }
+    if !STYLES.contains(&style) {
+        return Err(format!("style: must be one of {}", STYLES.join(", ")));
+    }
+    if show_from.is_some_and(|from| from > target_date) {
+        return Err("show_from: must not be after target_date".to_string());
+    }
+    Ok(())
+}
+
+/// 倒计时在 `today` 是否显示：从 show_from 起到目标日期当天
+pub fn is_visible(event: &DisplayEvent, today: NaiveDate) -> bool {
+    event.show_from.is_none_or(|from| from <= today) && today <= event.target_date
+}
+
+/// 客户端在 `today` 显示的倒计时，按目标日期排序
+pub fn countdowns(
+    events: &[DisplayEvent],
+    client_id: i32,
+    groups: &[ClientGroup],
+    today: NaiveDate,
+) -> Vec<Countdown> {
+    let mut visible: Vec<&DisplayEvent> = events
+        .iter()
+        .filter(|e| is_visible(e, today))
+        .filter(|e| announcements::in_scope(&e.client_ids, &e.group_ids, client_id, groups))
+        .collect();
+    visible.sort_by_key(|e| (e.target_date, e.id));
+    visible
+        .into_iter()
+        .map(|e| Countdown {
+            event_id: e.id,
+            label: e.label.clone(),
+            style: e.style.clone(),
+            target_date: e.target_date,
+            days_remaining: (e.target_date - today).num_days(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn event(id: i32, target_date: NaiveDate, client_ids: Vec<i32>) -> DisplayEvent {
+        let created = date(1, 1).and_hms_opt(0, 0, 0).unwrap();
+        DisplayEvent {
+            id,
+            label: format!("Event {}", id),
+            target_date,
+            style: STYLE_DEFAULT.to_string(),
+            show_from: None,
+            client_ids,
+            group_ids: Vec::new(),
+            created_at: created,
+            updated_at: created,
+        }
+    }
+
+    #[test]
+    fn test_validate_event() {
+        assert!(validate_event("Final exams", STYLE_EXAM, date(6, 7), Some(date(5, 1))).is_ok());
+        assert!(validate_event(" ", STYLE_EXAM, date(6, 7), None).is_err());
+        assert_eq!(
+            validate_event("Sports day", "neon", date(6, 7), None),
+            Err("style: must be one of default, exam, sport, holiday, celebration".to_string())
+        );
+        assert_eq!(
+            validate_event("Sports day", STYLE_SPORT, date(6, 7), Some(date(6, 8))),
+            Err("show_from: must not be after target_date".to_string())
+        );
+    }
+
+    #[test]
+    fn test_countdowns() {
+        let mut later = event(1, date(7, 10), Vec::new());
+        later.show_from = Some(date(6, 15));
+        let events = vec![
+            later,
+            event(2, date(6, 7), Vec::new()),
+            event(3, date(6, 3), vec![9]),
+            event(4, date(5, 31), Vec::new()),
+        ];
+
+        let shown = countdowns(&events, 1, &[], date(6, 1));
+        let ids: Vec<i32> = shown.iter().map(|c| c.event_id).collect();
+        assert_eq!(ids, vec![2]);
+        assert_eq!(shown[0].days_remaining, 6);
+
+        let on_the_day = countdowns(&events, 9, &[], date(6, 3));
+        assert_eq!(on_the_day[0].event_id, 3);
+        assert_eq!(on_the_day[0].days_remaining, 0);
+    }
+}
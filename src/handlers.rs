@@ -1,8 +1,25 @@
+use crate::announcements;
+use crate::codec;
 use crate::db::{repository::Repository, DbPool};
-use crate::error::AppResult;
+use crate::error::{AppError, AppResult};
+use crate::events;
 use crate::models::*;
-use actix_web::{web, HttpResponse};
-use chrono::Utc;
+use crate::printing;
+use crate::semester;
+use crate::settings;
+use crate::spreadsheet;
+use crate::sync::{
+    SyncLockManager, DEFAULT_IDEMPOTENCY_RETENTION_HOURS, IDEMPOTENCY_KEY_HEADER,
+    IDEMPOTENT_REPLAY_HEADER, MAX_IDEMPOTENCY_KEY_LEN,
+};
+use crate::timetable;
+use crate::validation;
+use crate::websocket::{WSConnectionManager, WSMessage};
+use actix_web::http::header::{
+    Charset, ContentDisposition, DispositionParam, DispositionType, ExtendedValue,
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{Local, Utc};
 
 // Health check handler
 #[utoipa::path(
@@ -127,63 +144,2567 @@ pub async fn delete_client(pool: web::Data<DbPool>, id: web::Path<i32>) -> AppRe
         ("id" = i32, Path, description = "Client ID")
     ),
     responses(
-        (status = 200, description = "List of client courses", body = ApiResponse<Vec<Course>>)
+        (status = 200, description = "List of client courses; encoded per Accept (JSON, MessagePack or CBOR)", body = ApiResponse<Vec<Course>>)
     ),
     tag = "Clients"
 )]
 pub async fn get_client_courses(
+    http_req: HttpRequest,
     pool: web::Data<DbPool>,
     id: web::Path<i32>,
 ) -> AppResult<HttpResponse> {
     let repo = Repository::new(pool.get_ref().clone());
     let courses = repo.get_client_courses(*id).await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::new(courses)))
+    codec::respond(&http_req, HttpResponse::Ok(), &ApiResponse::new(courses))
 }
 
 #[utoipa::path(
     get,
     path = "/api/clients/{id}/schedule",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("week" = Option<i32>, Query, description = "Only entries held in this week"),
+        ("day_of_week" = Option<i32>, Query, description = "Only entries on this weekday (1-7)")
+    ),
+    responses(
+        (status = 200, description = "List of client schedule entries; encoded per Accept (JSON, MessagePack or CBOR)", body = ApiResponse<Vec<ScheduleEntry>>),
+        (status = 400, description = "Invalid week or weekday")
+    ),
+    tag = "Clients"
+)]
+pub async fn get_client_schedule(
+    http_req: HttpRequest,
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    filter: web::Query<ScheduleFilter>,
+) -> AppResult<HttpResponse> {
+    let mut filter = filter.into_inner();
+    validate_schedule_filter(&filter)?;
+    filter.client_id = Some(*id);
+
+    let repo = Repository::new(pool.get_ref().clone());
+    let schedule = repo.get_schedule(&filter).await?;
+    codec::respond(&http_req, HttpResponse::Ok(), &ApiResponse::new(schedule))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/schedule",
+    params(
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20)"),
+        ("client_id" = Option<i32>, Query, description = "Filter by client"),
+        ("week" = Option<i32>, Query, description = "Only entries held in this week"),
+        ("day_of_week" = Option<i32>, Query, description = "Only entries on this weekday (1-7)")
+    ),
+    responses(
+        (status = 200, description = "Paginated schedule entries of all clients", body = ApiResponse<PaginatedResponse<ScheduleEntry>>),
+        (status = 400, description = "Invalid week or weekday")
+    ),
+    tag = "Schedule"
+)]
+pub async fn get_schedule_paginated(
+    pool: web::Data<DbPool>,
+    params: web::Query<PaginationParams>,
+    filter: web::Query<ScheduleFilter>,
+) -> AppResult<HttpResponse> {
+    validate_schedule_filter(&filter)?;
+
+    let repo = Repository::new(pool.get_ref().clone());
+    let (entries, total) = repo
+        .get_schedule_paginated(&filter, params.offset(), params.limit())
+        .await?;
+
+    let response = PaginatedResponse {
+        data: entries,
+        pagination: PaginationInfo::new(params.page, params.page_size, total),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(response)))
+}
+
+fn validate_schedule_filter(filter: &ScheduleFilter) -> AppResult<()> {
+    if let Some(week) = filter.week {
+        if !(1..=validation::MAX_WEEK_NUMBER).contains(&week) {
+            return Err(AppError::BadRequest(format!(
+                "week must be between 1 and {}",
+                validation::MAX_WEEK_NUMBER
+            )));
+        }
+    }
+    if let Some(day) = filter.day_of_week {
+        if !(1..=7).contains(&day) {
+            return Err(AppError::BadRequest(
+                "day_of_week must be between 1 and 7".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/conflicts",
+    params(
+        ("client_id" = Option<i32>, Query, description = "Only conflicts involving this client"),
+        ("kind" = Option<String>, Query, description = "teacher or location")
+    ),
+    responses(
+        (status = 200, description = "Overlapping classes of one teacher or in one room, across all clients", body = ApiResponse<Vec<ScheduleConflict>>),
+        (status = 400, description = "Unknown conflict kind")
+    ),
+    tag = "Schedule"
+)]
+pub async fn get_schedule_conflicts(
+    pool: web::Data<DbPool>,
+    filter: web::Query<ConflictFilter>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let conflicts = repo.get_schedule_conflicts(&filter).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(conflicts)))
+}
+
+// Central course and schedule handlers
+#[utoipa::path(
+    post,
+    path = "/api/clients/{id}/courses",
+    params(
+        ("id" = i32, Path, description = "Client ID")
+    ),
+    request_body = CreateCourse,
+    responses(
+        (status = 200, description = "Course created and the timetable pushed to the client", body = ApiResponse<Course>),
+        (status = 400, description = "Invalid fields"),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Courses"
+)]
+pub async fn create_course(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+    course: web::Json<CreateCourse>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_course(*id, course.into_inner()).await?;
+    publish_timetable_change(&repo, &ws_manager, created.client_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/courses/{id}",
+    params(
+        ("id" = i32, Path, description = "Course ID")
+    ),
+    request_body = UpdateCourse,
+    responses(
+        (status = 200, description = "Course updated; client syncs no longer overwrite it", body = ApiResponse<Course>),
+        (status = 400, description = "Invalid fields"),
+        (status = 404, description = "Course not found")
+    ),
+    tag = "Courses"
+)]
+pub async fn update_course(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+    course: web::Json<UpdateCourse>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let updated = repo.update_course(*id, course.into_inner()).await?;
+    publish_timetable_change(&repo, &ws_manager, updated.client_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/courses/{id}",
+    params(
+        ("id" = i32, Path, description = "Course ID")
+    ),
+    responses(
+        (status = 200, description = "Course and its schedule entries deleted", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Course not found")
+    ),
+    tag = "Courses"
+)]
+pub async fn delete_course(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let deleted = repo.delete_course(*id).await?;
+    publish_timetable_change(&repo, &ws_manager, deleted.client_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Course deleted".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clients/{id}/schedule",
+    params(
+        ("id" = i32, Path, description = "Client ID")
+    ),
+    request_body = CreateScheduleEntry,
+    responses(
+        (status = 200, description = "Schedule entry created and the timetable pushed to the client", body = ApiResponse<ScheduleEntry>),
+        (status = 400, description = "Invalid fields or periods, a course of another client or an overlapping entry"),
+        (status = 404, description = "Client or course not found")
+    ),
+    tag = "Schedule"
+)]
+pub async fn create_schedule_entry(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+    entry: web::Json<CreateScheduleEntry>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_schedule_entry(*id, entry.into_inner()).await?;
+    publish_timetable_change(&repo, &ws_manager, created.client_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/schedule/{id}",
+    params(
+        ("id" = i32, Path, description = "Schedule entry ID")
+    ),
+    request_body = UpdateScheduleEntry,
+    responses(
+        (status = 200, description = "Schedule entry updated; client syncs no longer overwrite it", body = ApiResponse<ScheduleEntry>),
+        (status = 400, description = "Invalid fields, a course of another client or an overlapping entry"),
+        (status = 404, description = "Schedule entry or course not found")
+    ),
+    tag = "Schedule"
+)]
+pub async fn update_schedule_entry(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+    entry: web::Json<UpdateScheduleEntry>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let updated = repo.update_schedule_entry(*id, entry.into_inner()).await?;
+    publish_timetable_change(&repo, &ws_manager, updated.client_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/schedule/{id}",
+    params(
+        ("id" = i32, Path, description = "Schedule entry ID")
+    ),
+    responses(
+        (status = 200, description = "Schedule entry deleted", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Schedule entry not found")
+    ),
+    tag = "Schedule"
+)]
+pub async fn delete_schedule_entry(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let deleted = repo.delete_schedule_entry(*id).await?;
+    publish_timetable_change(&repo, &ws_manager, deleted.client_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Schedule entry deleted".to_string(),
+    })))
+}
+
+/// Records a timetable version after a change on the server and pushes it to the client.
+async fn publish_timetable_change(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+    client_id: i32,
+) -> AppResult<()> {
+    let Some(snapshot) = repo
+        .create_timetable_snapshot(client_id, "admin", true)
+        .await?
+    else {
+        return Ok(());
+    };
+
+    push_timetable(repo, ws_manager, client_id, snapshot.version).await?;
+    Ok(())
+}
+
+/// Sends a client its current timetable; returns whether it was connected.
+async fn push_timetable(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+    client_id: i32,
+    version: i32,
+) -> AppResult<bool> {
+    let client = repo.get_client_by_id(client_id).await?;
+    let (courses, schedule_entries) = repo.get_client_timetable(client_id).await?;
+    let targets: Vec<uuid::Uuid> = uuid::Uuid::parse_str(&client.uuid).into_iter().collect();
+    Ok(ws_manager.send_command(
+        &targets,
+        "apply_timetable",
+        serde_json::json!({
+            "version": version,
+            "courses": courses,
+            "schedule_entries": schedule_entries,
+        }),
+    ) > 0)
+}
+
+// Sync handler
+#[utoipa::path(
+    post,
+    path = "/api/sync",
+    request_body(
+        content = SyncRequest,
+        description = "Sync payload as application/json (default), application/msgpack or application/cbor; gzip, zstd or br Content-Encoding is accepted"
+    ),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Idempotency key; repeating a key with the same request returns the cached response")
+    ),
+    responses(
+        (status = 200, description = "Data synced successfully; encoded per Accept (JSON, MessagePack or CBOR)", body = SyncResponse),
+        (status = 400, description = "Bad request"),
+        (status = 415, description = "Unsupported Content-Type"),
+        (status = 422, description = "Idempotency key reused for a different request"),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Sync"
+)]
+pub async fn sync_data(
+    http_req: HttpRequest,
+    pool: web::Data<DbPool>,
+    sync_locks: web::Data<SyncLockManager>,
+    body: web::Bytes,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let req: SyncRequest = codec::decode_body(&http_req, &body)?;
+
+    let idempotency_key = match http_req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => Some(
+            value
+                .to_str()
+                .map_err(|_| AppError::BadRequest("Invalid Idempotency-Key header".to_string()))?
+                .to_string(),
+        ),
+        None => req.request_id.clone(),
+    };
+    if let Some(key) = &idempotency_key {
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err(AppError::BadRequest(format!(
+                "Idempotency key must be 1-{} characters",
+                MAX_IDEMPOTENCY_KEY_LEN
+            )));
+        }
+    }
+
+    // The decoded request, so the same sync sent in another format or encoding still matches
+    let fingerprint = serde_json::to_vec(&req)
+        .map_err(|e| AppError::Internal(format!("Failed to encode sync request: {}", e)))?;
+
+    // Concurrent syncs of the same client run one after another
+    let _guard = sync_locks.lock(&req.client_uuid).await;
+
+    let retention_hours = repo
+        .get_setting("sync_idempotency_retention_hours")
+        .await
+        .ok()
+        .and_then(|s| s.value.parse().ok())
+        .unwrap_or(DEFAULT_IDEMPOTENCY_RETENTION_HOURS);
+
+    if let Some(key) = &idempotency_key {
+        if let Some(cached) = repo
+            .get_idempotent_sync_response(&req.client_uuid, key, &fingerprint, retention_hours)
+            .await?
+        {
+            let mut builder = HttpResponse::Ok();
+            builder.insert_header((IDEMPOTENT_REPLAY_HEADER, "true"));
+            return codec::respond(&http_req, builder, &cached);
+        }
+    }
+
+    let response = repo
+        .sync_client_data(
+            &req.client_uuid,
+            req.courses,
+            req.schedule_entries,
+            body.len() as i32,
+        )
+        .await?;
+
+    // Only successful outcomes are cached; failed syncs may be retried with the same key
+    if let Some(key) = &idempotency_key {
+        repo.save_idempotent_sync_response(
+            &req.client_uuid,
+            key,
+            &fingerprint,
+            &response,
+            retention_hours,
+        )
+        .await?;
+    }
+
+    codec::respond(&http_req, HttpResponse::Ok(), &response)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/sync/preview",
+    request_body(
+        content = SyncRequest,
+        description = "Sync payload in the same formats accepted by /api/sync"
+    ),
+    responses(
+        (status = 200, description = "What the sync would change; nothing is written", body = ApiResponse<SyncPreview>),
+        (status = 400, description = "Bad request"),
+        (status = 415, description = "Unsupported Content-Type"),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Sync"
+)]
+pub async fn preview_sync(
+    http_req: HttpRequest,
+    pool: web::Data<DbPool>,
+    body: web::Bytes,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let req: SyncRequest = codec::decode_body(&http_req, &body)?;
+    let preview = repo
+        .preview_client_sync(
+            &req.client_uuid,
+            req.courses,
+            req.schedule_entries,
+            validation::ORIGIN_CLIENT,
+        )
+        .await?;
+    codec::respond(&http_req, HttpResponse::Ok(), &ApiResponse::new(preview))
+}
+
+// Sync history handlers
+#[utoipa::path(
+    get,
+    path = "/api/sync/logs",
+    params(
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20)"),
+        ("status" = Option<String>, Query, description = "Filter by status: success, failed, partial")
+    ),
+    responses(
+        (status = 200, description = "Paginated sync history of all clients", body = ApiResponse<PaginatedResponse<SyncLog>>)
+    ),
+    tag = "Sync"
+)]
+pub async fn get_sync_logs(
+    pool: web::Data<DbPool>,
+    params: web::Query<PaginationParams>,
+    filter: web::Query<SyncLogFilter>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let (logs, total) = repo
+        .get_sync_logs_paginated(
+            None,
+            filter.status.as_deref(),
+            params.offset(),
+            params.limit(),
+        )
+        .await?;
+
+    let response = PaginatedResponse {
+        data: logs,
+        pagination: PaginationInfo::new(params.page, params.page_size, total),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/sync/logs",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20)"),
+        ("status" = Option<String>, Query, description = "Filter by status: success, failed, partial")
+    ),
+    responses(
+        (status = 200, description = "Paginated sync history of a client", body = ApiResponse<PaginatedResponse<SyncLog>>),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Sync"
+)]
+pub async fn get_client_sync_logs(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    params: web::Query<PaginationParams>,
+    filter: web::Query<SyncLogFilter>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.get_client_by_id(*id).await?;
+    let (logs, total) = repo
+        .get_sync_logs_paginated(
+            Some(*id),
+            filter.status.as_deref(),
+            params.offset(),
+            params.limit(),
+        )
+        .await?;
+
+    let response = PaginatedResponse {
+        data: logs,
+        pagination: PaginationInfo::new(params.page, params.page_size, total),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/sync/health",
+    responses(
+        (status = 200, description = "Sync health summary of all clients", body = ApiResponse<Vec<SyncHealth>>)
+    ),
+    tag = "Sync"
+)]
+pub async fn get_sync_health(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let health = repo.get_sync_health(None).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(health)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/sync/health",
+    params(
+        ("id" = i32, Path, description = "Client ID")
+    ),
+    responses(
+        (status = 200, description = "Sync health summary of a client", body = ApiResponse<SyncHealth>),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Sync"
+)]
+pub async fn get_client_sync_health(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let health = repo
+        .get_sync_health(Some(*id))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound("Client not found".to_string()))?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(health)))
+}
+
+// Timetable snapshot handlers
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/snapshots",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20)")
+    ),
+    responses(
+        (status = 200, description = "Timetable versions of a client, newest first", body = ApiResponse<PaginatedResponse<TimetableSnapshotSummary>>),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Snapshots"
+)]
+pub async fn get_client_snapshots(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    params: web::Query<PaginationParams>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.get_client_by_id(*id).await?;
+    let (snapshots, total) = repo
+        .get_timetable_snapshots_paginated(*id, params.offset(), params.limit())
+        .await?;
+
+    let response = PaginatedResponse {
+        data: snapshots,
+        pagination: PaginationInfo::new(params.page, params.page_size, total),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/snapshots/{version}",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("version" = i32, Path, description = "Snapshot version")
+    ),
+    responses(
+        (status = 200, description = "Timetable snapshot", body = ApiResponse<TimetableSnapshot>),
+        (status = 404, description = "Snapshot not found")
+    ),
+    tag = "Snapshots"
+)]
+pub async fn get_client_snapshot(
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> AppResult<HttpResponse> {
+    let (id, version) = path.into_inner();
+    let repo = Repository::new(pool.get_ref().clone());
+    let snapshot = repo.get_timetable_snapshot(id, version).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(snapshot)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/snapshots/diff",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("from" = i32, Query, description = "Base version"),
+        ("to" = i32, Query, description = "Compared version")
+    ),
+    responses(
+        (status = 200, description = "Changes between two timetable versions", body = ApiResponse<TimetableDiff>),
+        (status = 404, description = "Snapshot not found")
+    ),
+    tag = "Snapshots"
+)]
+pub async fn diff_client_snapshots(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    params: web::Query<SnapshotDiffParams>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let from = repo.get_timetable_snapshot(*id, params.from).await?;
+    let to = repo.get_timetable_snapshot(*id, params.to).await?;
+
+    let diff = TimetableDiff {
+        from_version: from.version,
+        to_version: to.version,
+        courses: timetable::diff_courses(&from.courses, &to.courses),
+        schedule_entries: timetable::diff_entries(&from.schedule_entries, &to.schedule_entries),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(diff)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/clients/{id}/snapshots/{version}/rollback",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("version" = i32, Path, description = "Snapshot version to restore")
+    ),
+    responses(
+        (status = 200, description = "Timetable restored and pushed to the client if online", body = ApiResponse<RollbackResponse>),
+        (status = 404, description = "Snapshot not found")
+    ),
+    tag = "Snapshots"
+)]
+pub async fn rollback_client_snapshot(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    sync_locks: web::Data<SyncLockManager>,
+    path: web::Path<(i32, i32)>,
+) -> AppResult<HttpResponse> {
+    let (id, version) = path.into_inner();
+    let repo = Repository::new(pool.get_ref().clone());
+    let client = repo.get_client_by_id(id).await?;
+    // Wait for a sync of this client that is in progress
+    let _guard = sync_locks.lock(&client.uuid).await;
+    let (restored, snapshot) = repo.restore_timetable_snapshot(id, version).await?;
+
+    // Push the restored timetable to the client when it is connected
+    let targets: Vec<uuid::Uuid> = uuid::Uuid::parse_str(&client.uuid).into_iter().collect();
+    let pushed = ws_manager.send_command(
+        &targets,
+        "apply_timetable",
+        serde_json::json!({
+            "version": snapshot.version,
+            "courses": restored.courses,
+            "schedule_entries": restored.schedule_entries,
+        }),
+    ) > 0;
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(RollbackResponse {
+        restored_version: restored.version,
+        snapshot,
+        pushed,
+    })))
+}
+
+// Schedule exception handlers
+#[utoipa::path(
+    get,
+    path = "/api/exceptions",
+    params(
+        ("client_id" = Option<i32>, Query, description = "Exceptions of this client, including global ones"),
+        ("from" = Option<String>, Query, description = "First date (YYYY-MM-DD)"),
+        ("to" = Option<String>, Query, description = "Last date (YYYY-MM-DD)")
+    ),
+    responses(
+        (status = 200, description = "Schedule exceptions ordered by date", body = ApiResponse<Vec<ScheduleException>>)
+    ),
+    tag = "Exceptions"
+)]
+pub async fn list_schedule_exceptions(
+    pool: web::Data<DbPool>,
+    filter: web::Query<ExceptionFilter>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let exceptions = repo.get_schedule_exceptions(&filter).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(exceptions)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/exceptions",
+    request_body = CreateScheduleException,
+    responses(
+        (status = 200, description = "Exception created and pushed to affected clients", body = ApiResponse<ScheduleException>),
+        (status = 400, description = "Missing or invalid fields for the exception kind"),
+        (status = 404, description = "Client, schedule entry or bell schedule not found")
+    ),
+    tag = "Exceptions"
+)]
+pub async fn create_schedule_exception(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    exception: web::Json<CreateScheduleException>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo
+        .create_schedule_exception(exception.into_inner())
+        .await?;
+    notify_exception_change(&repo, &ws_manager, &created, "created").await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/exceptions/{id}",
+    params(
+        ("id" = i32, Path, description = "Exception ID")
+    ),
+    responses(
+        (status = 200, description = "Exception deleted and affected clients notified", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Exception not found")
+    ),
+    tag = "Exceptions"
+)]
+pub async fn delete_schedule_exception(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let deleted = repo.delete_schedule_exception(*id).await?;
+    notify_exception_change(&repo, &ws_manager, &deleted, "deleted").await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Schedule exception deleted".to_string(),
+    })))
+}
+
+/// Pushes an exception change to its client, or to every connected client for global ones.
+async fn notify_exception_change(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+    exception: &ScheduleException,
+    action: &str,
+) -> AppResult<()> {
+    let targets = match exception.client_id {
+        Some(client_id) => {
+            let client = repo.get_client_by_id(client_id).await?;
+            uuid::Uuid::parse_str(&client.uuid).into_iter().collect()
+        }
+        None => ws_manager.get_online_clients(),
+    };
+    ws_manager.send_command(
+        &targets,
+        "schedule_exception_changed",
+        serde_json::json!({
+            "action": action,
+            "exception": exception,
+        }),
+    );
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/schedule/effective",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("date" = Option<String>, Query, description = "Date as YYYY-MM-DD (default: today)")
+    ),
+    responses(
+        (status = 200, description = "Classes of the day after semester weeks and exceptions", body = ApiResponse<DaySchedule>),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Exceptions"
+)]
+pub async fn get_effective_schedule(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    query: web::Query<DateQuery>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    let schedule = repo.get_effective_schedule(*id, date).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(schedule)))
+}
+
+// Now and next handlers
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/now",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("at" = Option<String>, Query, description = "Instant as YYYY-MM-DDTHH:MM:SS (default: now)")
+    ),
+    responses(
+        (status = 200, description = "Current and next class of the client", body = ApiResponse<NowNext>),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Schedule"
+)]
+pub async fn get_client_now_next(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    query: web::Query<InstantQuery>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let at = query.at.unwrap_or_else(|| Local::now().naive_local());
+    let now_next = repo
+        .get_now_next(Some(*id), at)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound("Client not found".to_string()))?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(now_next)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/now",
+    params(
+        ("at" = Option<String>, Query, description = "Instant as YYYY-MM-DDTHH:MM:SS (default: now)")
+    ),
+    responses(
+        (status = 200, description = "Current and next class of every client", body = ApiResponse<Vec<NowNext>>)
+    ),
+    tag = "Schedule"
+)]
+pub async fn get_now_next(
+    pool: web::Data<DbPool>,
+    query: web::Query<InstantQuery>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let at = query.at.unwrap_or_else(|| Local::now().naive_local());
+    let now_next = repo.get_now_next(None, at).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(now_next)))
+}
+
+// Calendar feed handlers
+#[utoipa::path(
+    get,
+    path = "/api/calendar-feeds",
+    responses(
+        (status = 200, description = "List of calendar subscriptions", body = ApiResponse<Vec<CalendarFeed>>)
+    ),
+    tag = "Calendar"
+)]
+pub async fn list_calendar_feeds(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let feeds = repo.get_calendar_feeds().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(feeds)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/calendar-feeds",
+    request_body = CreateCalendarFeed,
+    responses(
+        (status = 200, description = "Calendar subscription created with a new token", body = ApiResponse<CalendarFeed>),
+        (status = 400, description = "Invalid scope or target"),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Calendar"
+)]
+pub async fn create_calendar_feed(
+    pool: web::Data<DbPool>,
+    feed: web::Json<CreateCalendarFeed>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let token = generate_api_key();
+    let created = repo.create_calendar_feed(feed.into_inner(), &token).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/calendar-feeds/{id}",
+    params(
+        ("id" = i32, Path, description = "Calendar feed ID")
+    ),
+    responses(
+        (status = 200, description = "Calendar subscription revoked", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Calendar feed not found")
+    ),
+    tag = "Calendar"
+)]
+pub async fn delete_calendar_feed(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.delete_calendar_feed(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Calendar feed revoked".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/calendar/{token}.ics",
+    params(
+        ("token" = String, Path, description = "Subscription token")
+    ),
+    responses(
+        (status = 200, description = "iCalendar feed with weekly recurring classes", content_type = "text/calendar", body = String),
+        (status = 404, description = "Unknown or revoked token")
+    ),
+    tag = "Calendar"
+)]
+pub async fn get_calendar_feed(
+    pool: web::Data<DbPool>,
+    token: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let ics = repo.render_calendar_feed(&token).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(ics))
+}
+
+// Printable timetable handlers
+/// `Content-Disposition` with an ASCII fallback name and the UTF-8 file name.
+fn attachment(file_name: &str, fallback: &str) -> ContentDisposition {
+    ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![
+            DispositionParam::Filename(fallback.to_string()),
+            DispositionParam::FilenameExt(ExtendedValue {
+                charset: Charset::Ext("UTF-8".to_string()),
+                language_tag: None,
+                value: file_name.as_bytes().to_vec(),
+            }),
+        ],
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/print/{scope}/{target}",
+    params(
+        ("scope" = String, Path, description = "client, teacher or location"),
+        ("target" = String, Path, description = "Client ID, teacher name or location"),
+        ("format" = Option<String>, Query, description = "pdf (default) or html"),
+        ("week" = Option<i32>, Query, description = "Only classes held in this week; omitted prints the whole semester with week ranges")
+    ),
+    responses(
+        (status = 200, description = "Weekly timetable grid as a one-page A4 PDF or a standalone HTML page", content(
+            (Vec<u8> = "application/pdf"),
+            (String = "text/html")
+        )),
+        (status = 400, description = "Unknown scope or format, or a week outside the semester"),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Printing"
+)]
+pub async fn print_timetable(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, String)>,
+    query: web::Query<PrintQuery>,
+) -> AppResult<HttpResponse> {
+    let (scope, target) = path.into_inner();
+    let format = query.format.as_deref().unwrap_or(printing::FORMAT_PDF);
+    let repo = Repository::new(pool.get_ref().clone());
+    let grid = repo
+        .get_printable_timetable(&scope, &target, query.week)
+        .await?;
+    let body = printing::render(&grid, format).map_err(AppError::BadRequest)?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(printing::content_type(format))
+        .insert_header(attachment(
+            &printing::file_name(&grid.title, format),
+            &format!("timetable.{}", format),
+        ))
+        .body(body))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/print/rooms",
+    params(
+        ("format" = Option<String>, Query, description = "pdf (default) or html"),
+        ("week" = Option<i32>, Query, description = "Only classes held in this week; omitted prints the whole semester with week ranges")
+    ),
+    responses(
+        (status = 200, description = "ZIP archive with one timetable per registered room", content_type = "application/zip", body = Vec<u8>),
+        (status = 400, description = "Unknown format or a week outside the semester")
+    ),
+    tag = "Printing"
+)]
+pub async fn print_room_timetables(
+    pool: web::Data<DbPool>,
+    query: web::Query<PrintQuery>,
+) -> AppResult<HttpResponse> {
+    let format = query.format.as_deref().unwrap_or(printing::FORMAT_PDF);
+    let repo = Repository::new(pool.get_ref().clone());
+    let files = repo
+        .get_room_timetables(query.week)
+        .await?
+        .iter()
+        .map(|grid| {
+            let body = printing::render(grid, format)?;
+            Ok((printing::file_name(&grid.title, format), body))
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(AppError::BadRequest)?;
+    let archive = printing::zip_files(files)
+        .map_err(|e| AppError::Internal(format!("Failed to build ZIP archive: {}", e)))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .insert_header(attachment("rooms.zip", "rooms.zip"))
+        .body(archive))
+}
+
+// Timetable import handlers
+#[utoipa::path(
+    post,
+    path = "/api/import",
+    request_body = ImportRequest,
+    responses(
+        (status = 200, description = "Timetable imported and pushed to affected clients", body = ApiResponse<ImportReport>),
+        (status = 400, description = "Unsupported format or mapping, or no active semester for .ics")
+    ),
+    tag = "Import"
+)]
+pub async fn import_timetable(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    request: web::Json<ImportRequest>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let mut report = repo.import_timetable(request.into_inner(), false).await?;
+
+    // Push the resulting timetable of every changed client
+    for result in &mut report.clients {
+        if let Some(version) = result.snapshot_version {
+            result.pushed = push_timetable(&repo, &ws_manager, result.client_id, version).await?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(report)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/import/preview",
+    request_body = ImportRequest,
+    responses(
+        (status = 200, description = "Dry-run report of the import; nothing is written", body = ApiResponse<ImportReport>),
+        (status = 400, description = "Unsupported format or mapping, or no active semester for .ics")
+    ),
+    tag = "Import"
+)]
+pub async fn preview_import(
+    pool: web::Data<DbPool>,
+    request: web::Json<ImportRequest>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let report = repo.import_timetable(request.into_inner(), true).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(report)))
+}
+
+// Spreadsheet handlers
+#[utoipa::path(
+    get,
+    path = "/api/export/xlsx",
+    params(
+        ("client_ids" = Option<String>, Query, description = "Comma-separated client IDs"),
+        ("template_id" = Option<i32>, Query, description = "Export the clients linked to this template; with neither filter every client is exported")
+    ),
+    responses(
+        (status = 200, description = "XLSX workbook: a flat sheet with one row per schedule entry, then a weekly grid per client", content_type = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet", body = Vec<u8>),
+        (status = 400, description = "Invalid client ID"),
+        (status = 404, description = "Client or template not found")
+    ),
+    tag = "Import"
+)]
+pub async fn export_spreadsheet(
+    pool: web::Data<DbPool>,
+    query: web::Query<SpreadsheetExportQuery>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let clients = repo.get_spreadsheet_clients(&query).await?;
+    let workbook = spreadsheet::write_workbook(&clients)
+        .map_err(|e| AppError::Internal(format!("Failed to build workbook: {}", e)))?;
+    let file_name = match &clients[..] {
+        [client] => printing::file_name(&client.client_name, "xlsx"),
+        _ => "timetables.xlsx".to_string(),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type(spreadsheet::CONTENT_TYPE)
+        .insert_header(attachment(&file_name, "timetables.xlsx"))
+        .body(workbook))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/import/xlsx",
+    request_body(
+        content = Vec<u8>,
+        content_type = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        description = "XLSX workbook whose 'Timetable' sheet (or first sheet) uses the flat export layout"
+    ),
+    responses(
+        (status = 200, description = "Rows imported through sync validation and pushed to affected clients; rejected rows are listed by row number", body = ApiResponse<ImportReport>),
+        (status = 400, description = "Not a readable XLSX workbook")
+    ),
+    tag = "Import"
+)]
+pub async fn import_spreadsheet(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    body: web::Bytes,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let mut report = repo.import_spreadsheet(&body, false).await?;
+
+    for result in &mut report.clients {
+        if let Some(version) = result.snapshot_version {
+            result.pushed = push_timetable(&repo, &ws_manager, result.client_id, version).await?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(report)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/import/xlsx/preview",
+    request_body(
+        content = Vec<u8>,
+        content_type = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        description = "XLSX workbook whose 'Timetable' sheet (or first sheet) uses the flat export layout"
+    ),
+    responses(
+        (status = 200, description = "Dry-run report of the spreadsheet import; nothing is written", body = ApiResponse<ImportReport>),
+        (status = 400, description = "Not a readable XLSX workbook")
+    ),
+    tag = "Import"
+)]
+pub async fn preview_spreadsheet_import(
+    pool: web::Data<DbPool>,
+    body: web::Bytes,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let report = repo.import_spreadsheet(&body, true).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(report)))
+}
+
+// Timetable template handlers
+#[utoipa::path(
+    get,
+    path = "/api/templates",
+    responses(
+        (status = 200, description = "List of timetable templates with their linked clients", body = ApiResponse<Vec<TimetableTemplate>>)
+    ),
+    tag = "Templates"
+)]
+pub async fn list_templates(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let templates = repo.get_templates().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(templates)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/templates",
+    request_body = CreateTemplate,
+    responses(
+        (status = 200, description = "Template created", body = ApiResponse<TimetableTemplate>),
+        (status = 400, description = "Duplicate name or courses and entries that would not pass a sync")
+    ),
+    tag = "Templates"
+)]
+pub async fn create_template(
+    pool: web::Data<DbPool>,
+    template: web::Json<CreateTemplate>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_template(template.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/templates/{id}",
+    params(
+        ("id" = i32, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 200, description = "Template details", body = ApiResponse<TimetableTemplate>),
+        (status = 404, description = "Template not found")
+    ),
+    tag = "Templates"
+)]
+pub async fn get_template(pool: web::Data<DbPool>, id: web::Path<i32>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let template = repo.get_template_by_id(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(template)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/templates/{id}",
+    params(
+        ("id" = i32, Path, description = "Template ID")
+    ),
+    request_body = UpdateTemplate,
+    responses(
+        (status = 200, description = "Template updated; linked clients change when it is published", body = ApiResponse<TimetableTemplate>),
+        (status = 400, description = "Duplicate name or courses and entries that would not pass a sync"),
+        (status = 404, description = "Template not found")
+    ),
+    tag = "Templates"
+)]
+pub async fn update_template(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    template: web::Json<UpdateTemplate>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let updated = repo.update_template(*id, template.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/templates/{id}",
+    params(
+        ("id" = i32, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 200, description = "Template deleted; published courses and entries stay on the clients", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Template not found")
+    ),
+    tag = "Templates"
+)]
+pub async fn delete_template(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.delete_template(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Template deleted".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/templates/{id}/clients/{client_id}",
+    params(
+        ("id" = i32, Path, description = "Template ID"),
+        ("client_id" = i32, Path, description = "Client ID")
+    ),
+    request_body = TemplateOverrides,
+    responses(
+        (status = 200, description = "Client linked, or its overrides replaced", body = ApiResponse<TimetableTemplate>),
+        (status = 400, description = "Overrides refer to courses or entries not in the template"),
+        (status = 404, description = "Template or client not found")
+    ),
+    tag = "Templates"
+)]
+pub async fn link_template_client(
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+    overrides: web::Json<TemplateOverrides>,
+) -> AppResult<HttpResponse> {
+    let (id, client_id) = path.into_inner();
+    let repo = Repository::new(pool.get_ref().clone());
+    let template = repo
+        .link_template_client(id, client_id, overrides.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(template)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/templates/{id}/clients/{client_id}",
+    params(
+        ("id" = i32, Path, description = "Template ID"),
+        ("client_id" = i32, Path, description = "Client ID")
+    ),
+    responses(
+        (status = 200, description = "Client unlinked; published courses and entries stay on it", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Client not linked to the template")
+    ),
+    tag = "Templates"
+)]
+pub async fn unlink_template_client(
+    pool: web::Data<DbPool>,
+    path: web::Path<(i32, i32)>,
+) -> AppResult<HttpResponse> {
+    let (id, client_id) = path.into_inner();
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.unlink_template_client(id, client_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Client unlinked from template".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/templates/{id}/preview",
+    params(
+        ("id" = i32, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 200, description = "Per-client diff of publishing the template; nothing is written", body = ApiResponse<TemplatePublishReport>),
+        (status = 404, description = "Template not found")
+    ),
+    tag = "Templates"
+)]
+pub async fn preview_template(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let report = repo.publish_template(*id, true).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(report)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/templates/{id}/publish",
+    params(
+        ("id" = i32, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 200, description = "Template applied to every linked client and pushed to the changed ones", body = ApiResponse<TemplatePublishReport>),
+        (status = 404, description = "Template not found")
+    ),
+    tag = "Templates"
+)]
+pub async fn publish_template(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let mut report = repo.publish_template(*id, false).await?;
+
+    for result in &mut report.clients {
+        if let Some(version) = result.snapshot_version {
+            result.pushed = push_timetable(&repo, &ws_manager, result.client_id, version).await?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(report)))
+}
+
+// Bell schedule handlers
+#[utoipa::path(
+    get,
+    path = "/api/bell-schedules",
+    responses(
+        (status = 200, description = "Bell schedules, the default first", body = ApiResponse<Vec<BellSchedule>>)
+    ),
+    tag = "Bell Schedules"
+)]
+pub async fn list_bell_schedules(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let schedules = repo.get_bell_schedules().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(schedules)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/bell-schedules",
+    request_body = CreateBellSchedule,
+    responses(
+        (status = 200, description = "Bell schedule created; a new default re-times period-based entries", body = ApiResponse<BellScheduleChange>),
+        (status = 400, description = "Invalid or overlapping periods, duplicate name, or periods used by entries missing from a new default")
+    ),
+    tag = "Bell Schedules"
+)]
+pub async fn create_bell_schedule(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    schedule: web::Json<CreateBellSchedule>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let schedule = repo.create_bell_schedule(schedule.into_inner()).await?;
+    let not_retimed = if schedule.is_default {
+        apply_default_bell_schedule(&repo, &ws_manager).await?
+    } else {
+        Vec::new()
+    };
+    Ok(
+        HttpResponse::Ok().json(ApiResponse::new(BellScheduleChange {
+            schedule,
+            not_retimed,
+        })),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/bell-schedules/{id}",
+    params(
+        ("id" = i32, Path, description = "Bell schedule ID")
+    ),
+    responses(
+        (status = 200, description = "Bell schedule details", body = ApiResponse<BellSchedule>),
+        (status = 404, description = "Bell schedule not found")
+    ),
+    tag = "Bell Schedules"
+)]
+pub async fn get_bell_schedule(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let schedule = repo.get_bell_schedule_by_id(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(schedule)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/bell-schedules/{id}",
+    params(
+        ("id" = i32, Path, description = "Bell schedule ID")
+    ),
+    request_body = UpdateBellSchedule,
+    responses(
+        (status = 200, description = "Bell schedule updated; changes to the default re-time period-based entries and are pushed to their clients", body = ApiResponse<BellScheduleChange>),
+        (status = 400, description = "Invalid or overlapping periods, duplicate name, or periods used by entries missing from the default"),
+        (status = 404, description = "Bell schedule not found")
+    ),
+    tag = "Bell Schedules"
+)]
+pub async fn update_bell_schedule(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+    update: web::Json<UpdateBellSchedule>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let schedule = repo.update_bell_schedule(*id, update.into_inner()).await?;
+    let not_retimed = if schedule.is_default {
+        apply_default_bell_schedule(&repo, &ws_manager).await?
+    } else {
+        Vec::new()
+    };
+    Ok(
+        HttpResponse::Ok().json(ApiResponse::new(BellScheduleChange {
+            schedule,
+            not_retimed,
+        })),
+    )
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/bell-schedules/{id}",
+    params(
+        ("id" = i32, Path, description = "Bell schedule ID")
+    ),
+    responses(
+        (status = 200, description = "Bell schedule and the date switches to it deleted", body = ApiResponse<MessageResponse>),
+        (status = 400, description = "The default bell schedule is in use by period-based entries"),
+        (status = 404, description = "Bell schedule not found")
+    ),
+    tag = "Bell Schedules"
+)]
+pub async fn delete_bell_schedule(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.delete_bell_schedule(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Bell schedule deleted".to_string(),
+    })))
+}
+
+/// Moves period-based entries to the default bell schedule's times and pushes the changed
+/// timetables; returns the entries that kept their old times.
+async fn apply_default_bell_schedule(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+) -> AppResult<Vec<SyncRejection>> {
+    let (clients, not_retimed) = repo.retime_period_entries().await?;
+    for client_id in clients {
+        publish_timetable_change(repo, ws_manager, client_id).await?;
+    }
+    Ok(not_retimed)
+}
+
+// Semester handlers
+#[utoipa::path(
+    get,
+    path = "/api/semesters",
+    responses(
+        (status = 200, description = "List of semesters, latest first", body = ApiResponse<Vec<Semester>>)
+    ),
+    tag = "Semesters"
+)]
+pub async fn list_semesters(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let semesters = repo.get_semesters().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(semesters)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/semesters",
+    request_body = CreateSemester,
+    responses(
+        (status = 200, description = "Semester created", body = ApiResponse<Semester>),
+        (status = 400, description = "Invalid dates or week count")
+    ),
+    tag = "Semesters"
+)]
+pub async fn create_semester(
+    pool: web::Data<DbPool>,
+    semester: web::Json<CreateSemester>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_semester(semester.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/semesters/{id}",
+    params(
+        ("id" = i32, Path, description = "Semester ID")
+    ),
+    responses(
+        (status = 200, description = "Semester details", body = ApiResponse<Semester>),
+        (status = 404, description = "Semester not found")
+    ),
+    tag = "Semesters"
+)]
+pub async fn get_semester(pool: web::Data<DbPool>, id: web::Path<i32>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let semester = repo.get_semester_by_id(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(semester)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/semesters/{id}",
+    params(
+        ("id" = i32, Path, description = "Semester ID")
+    ),
+    request_body = UpdateSemester,
+    responses(
+        (status = 200, description = "Semester updated", body = ApiResponse<Semester>),
+        (status = 400, description = "Invalid dates or week count"),
+        (status = 404, description = "Semester not found")
+    ),
+    tag = "Semesters"
+)]
+pub async fn update_semester(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    semester: web::Json<UpdateSemester>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let updated = repo.update_semester(*id, semester.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/semesters/{id}",
+    params(
+        ("id" = i32, Path, description = "Semester ID")
+    ),
+    responses(
+        (status = 200, description = "Semester deleted", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Semester not found")
+    ),
+    tag = "Semesters"
+)]
+pub async fn delete_semester(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.delete_semester(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Semester deleted".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/semesters/{id}/activate",
+    params(
+        ("id" = i32, Path, description = "Semester ID")
+    ),
+    responses(
+        (status = 200, description = "Semester marked as the active one", body = ApiResponse<Semester>),
+        (status = 404, description = "Semester not found")
+    ),
+    tag = "Semesters"
+)]
+pub async fn activate_semester(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.activate_semester(*id).await?;
+    let semester = repo.get_semester_by_id(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(semester)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/semesters/current",
+    responses(
+        (status = 200, description = "Active semester", body = ApiResponse<Semester>),
+        (status = 404, description = "No active semester")
+    ),
+    tag = "Semesters"
+)]
+pub async fn get_current_semester(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let semester = repo
+        .get_active_semester()
+        .await?
+        .ok_or_else(|| AppError::NotFound("No active semester".to_string()))?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(semester)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/semesters/current/week",
+    params(
+        ("date" = Option<String>, Query, description = "Date as YYYY-MM-DD (default: today)")
+    ),
+    responses(
+        (status = 200, description = "Teaching week of the date in the active semester", body = ApiResponse<WeekInfo>),
+        (status = 404, description = "No active semester")
+    ),
+    tag = "Semesters"
+)]
+pub async fn get_current_week(
+    pool: web::Data<DbPool>,
+    query: web::Query<DateQuery>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let date = query.date.unwrap_or_else(|| Local::now().date_naive());
+    let info = repo.get_week_info(date).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(info)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/semesters/current/start",
+    request_body = SetSemesterStart,
+    responses(
+        (status = 200, description = "Start of the active semester moved; week count is kept", body = ApiResponse<SemesterStartResponse>),
+        (status = 404, description = "No active semester")
+    ),
+    tag = "Semesters"
+)]
+pub async fn set_semester_start(
+    pool: web::Data<DbPool>,
+    req: web::Json<SetSemesterStart>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let semester = repo.set_active_semester_start(req.date).await?;
+    let today = Local::now().date_naive();
+
+    let response = SemesterStartResponse {
+        semester_start_date: semester.start_date.format("%Y-%m-%d").to_string(),
+        calculated_week: semester::week_number(semester.start_date, today),
+    };
+    Ok(HttpResponse::Ok().json(ApiResponse::new(response)))
+}
+
+// Room handlers
+#[utoipa::path(
+    get,
+    path = "/api/rooms",
+    responses(
+        (status = 200, description = "List of rooms by building and name", body = ApiResponse<Vec<Room>>)
+    ),
+    tag = "Rooms"
+)]
+pub async fn list_rooms(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let rooms = repo.get_rooms().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(rooms)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/rooms",
+    request_body = CreateRoom,
+    responses(
+        (status = 200, description = "Room created", body = ApiResponse<Room>),
+        (status = 400, description = "Invalid fields or duplicate name")
+    ),
+    tag = "Rooms"
+)]
+pub async fn create_room(
+    pool: web::Data<DbPool>,
+    room: web::Json<CreateRoom>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_room(room.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rooms/free",
+    params(
+        ("day_of_week" = i32, Query, description = "Weekday (1-7)"),
+        ("start_time" = String, Query, description = "Slot start (HH:MM)"),
+        ("end_time" = String, Query, description = "Slot end (HH:MM)"),
+        ("week" = Option<i32>, Query, description = "Teaching week (default: current week; all weeks without an active semester)"),
+        ("min_capacity" = Option<i32>, Query, description = "Only rooms with at least this many seats"),
+        ("building" = Option<String>, Query, description = "Only rooms in this building")
+    ),
+    responses(
+        (status = 200, description = "Rooms with no class in the slot", body = ApiResponse<Vec<Room>>),
+        (status = 400, description = "Invalid slot or week")
+    ),
+    tag = "Rooms"
+)]
+pub async fn find_free_rooms(
+    pool: web::Data<DbPool>,
+    query: web::Query<FreeRoomQuery>,
+) -> AppResult<HttpResponse> {
+    validate_schedule_filter(&ScheduleFilter {
+        week: query.week,
+        ..Default::default()
+    })?;
+
+    let repo = Repository::new(pool.get_ref().clone());
+    let rooms = repo.find_free_rooms(query.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(rooms)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{id}",
+    params(
+        ("id" = i32, Path, description = "Room ID")
+    ),
+    responses(
+        (status = 200, description = "Room details", body = ApiResponse<Room>),
+        (status = 404, description = "Room not found")
+    ),
+    tag = "Rooms"
+)]
+pub async fn get_room(pool: web::Data<DbPool>, id: web::Path<i32>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let room = repo.get_room_by_id(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(room)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/rooms/{id}",
+    params(
+        ("id" = i32, Path, description = "Room ID")
+    ),
+    request_body = UpdateRoom,
+    responses(
+        (status = 200, description = "Room updated", body = ApiResponse<Room>),
+        (status = 400, description = "Invalid fields or duplicate name"),
+        (status = 404, description = "Room not found")
+    ),
+    tag = "Rooms"
+)]
+pub async fn update_room(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    room: web::Json<UpdateRoom>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let updated = repo.update_room(*id, room.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/rooms/{id}",
+    params(
+        ("id" = i32, Path, description = "Room ID")
+    ),
+    responses(
+        (status = 200, description = "Room deleted; clients in it are left without a room", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Room not found")
+    ),
+    tag = "Rooms"
+)]
+pub async fn delete_room(pool: web::Data<DbPool>, id: web::Path<i32>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.delete_room(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Room deleted".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/rooms/{id}/occupancy",
+    params(
+        ("id" = i32, Path, description = "Room ID"),
+        ("week" = Option<i32>, Query, description = "Teaching week (default: current week; all weeks without an active semester)"),
+        ("day_of_week" = Option<i32>, Query, description = "Only this weekday (1-7); the whole week when omitted")
+    ),
+    responses(
+        (status = 200, description = "Classes held in the room", body = ApiResponse<RoomOccupancy>),
+        (status = 400, description = "Invalid week or weekday"),
+        (status = 404, description = "Room not found")
+    ),
+    tag = "Rooms"
+)]
+pub async fn get_room_occupancy(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    query: web::Query<OccupancyQuery>,
+) -> AppResult<HttpResponse> {
+    validate_schedule_filter(&ScheduleFilter {
+        client_id: None,
+        week: query.week,
+        day_of_week: query.day_of_week,
+    })?;
+
+    let repo = Repository::new(pool.get_ref().clone());
+    let occupancy = repo.get_room_occupancy(*id, &query).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(occupancy)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/clients/{id}/room",
     params(
         ("id" = i32, Path, description = "Client ID")
     ),
+    request_body = AssignRoom,
+    responses(
+        (status = 200, description = "Room of the client's display set or cleared", body = ApiResponse<Client>),
+        (status = 404, description = "Client or room not found")
+    ),
+    tag = "Rooms"
+)]
+pub async fn assign_client_room(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    assignment: web::Json<AssignRoom>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let client = repo.assign_client_room(*id, assignment.room_id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(client)))
+}
+
+// Client group handlers
+#[utoipa::path(
+    get,
+    path = "/api/client-groups",
+    responses(
+        (status = 200, description = "List of client groups with their members", body = ApiResponse<Vec<ClientGroup>>)
+    ),
+    tag = "Client Groups"
+)]
+pub async fn list_client_groups(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let groups = repo.get_client_groups().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(groups)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/client-groups",
+    request_body = CreateClientGroup,
+    responses(
+        (status = 200, description = "Client group created", body = ApiResponse<ClientGroup>),
+        (status = 400, description = "Empty or duplicate name"),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Client Groups"
+)]
+pub async fn create_client_group(
+    pool: web::Data<DbPool>,
+    group: web::Json<CreateClientGroup>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_client_group(group.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/client-groups/{id}",
+    params(
+        ("id" = i32, Path, description = "Client group ID")
+    ),
+    request_body = UpdateClientGroup,
+    responses(
+        (status = 200, description = "Client group updated", body = ApiResponse<ClientGroup>),
+        (status = 400, description = "Empty or duplicate name"),
+        (status = 404, description = "Client group or client not found")
+    ),
+    tag = "Client Groups"
+)]
+pub async fn update_client_group(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    group: web::Json<UpdateClientGroup>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let updated = repo.update_client_group(*id, group.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/client-groups/{id}",
+    params(
+        ("id" = i32, Path, description = "Client group ID")
+    ),
+    responses(
+        (status = 200, description = "Client group deleted", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Client group not found")
+    ),
+    tag = "Client Groups"
+)]
+pub async fn delete_client_group(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.delete_client_group(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Client group deleted".to_string(),
+    })))
+}
+
+// Announcement handlers
+/// Sends active announcements to connected clients that have not received them; returns the
+/// number of deliveries. Runs periodically and after announcements change.
+pub async fn deliver_announcements(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+) -> AppResult<usize> {
+    let mut delivered = 0;
+    for (announcement, clients) in repo.get_pending_announcements().await? {
+        for client in clients {
+            let Ok(uuid) = uuid::Uuid::parse_str(&client.uuid) else {
+                continue;
+            };
+            let params = serde_json::json!(announcement);
+            if ws_manager.send_command(&[uuid], announcements::COMMAND_SHOW, params) > 0 {
+                repo.mark_announcement_delivered(announcement.id, client.id)
+                    .await?;
+                delivered += 1;
+            }
+        }
+    }
+    Ok(delivered)
+}
+
+/// The displays that received an announcement. Read before changing it, since an
+/// update resets its deliveries.
+async fn announcement_recipients(repo: &Repository, id: i32) -> AppResult<Vec<uuid::Uuid>> {
+    Ok(repo
+        .get_announcement_recipients(id)
+        .await?
+        .iter()
+        .filter_map(|c| uuid::Uuid::parse_str(&c.uuid).ok())
+        .collect())
+}
+
+/// Tells the displays that received an announcement to stop showing it.
+fn withdraw_announcement(ws_manager: &WSConnectionManager, recipients: &[uuid::Uuid], id: i32) {
+    ws_manager.send_command(
+        recipients,
+        announcements::COMMAND_WITHDRAW,
+        serde_json::json!({ "id": id }),
+    );
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/announcements",
+    responses(
+        (status = 200, description = "All announcements, latest start first", body = ApiResponse<Vec<Announcement>>)
+    ),
+    tag = "Announcements"
+)]
+pub async fn list_announcements(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let announcements = repo.get_announcements().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(announcements)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/announcements",
+    request_body = CreateAnnouncement,
+    responses(
+        (status = 200, description = "Announcement created; if already active it is pushed to connected target clients", body = ApiResponse<Announcement>),
+        (status = 400, description = "Invalid title, priority or time window"),
+        (status = 404, description = "Target client or group not found")
+    ),
+    tag = "Announcements"
+)]
+pub async fn create_announcement(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    announcement: web::Json<CreateAnnouncement>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_announcement(announcement.into_inner()).await?;
+    deliver_announcements(&repo, &ws_manager).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/announcements/active",
+    params(
+        ("client_uuid" = String, Query, description = "UUID of the requesting client")
+    ),
+    responses(
+        (status = 200, description = "Announcements currently addressed to the client, e.g. fetched after reconnecting; they are recorded as delivered", body = ApiResponse<Vec<Announcement>>),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Announcements"
+)]
+pub async fn get_active_announcements(
+    pool: web::Data<DbPool>,
+    query: web::Query<ClientUuidQuery>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let announcements = repo.get_client_announcements(&query.client_uuid).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(announcements)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/announcements/{id}",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    responses(
+        (status = 200, description = "Announcement details", body = ApiResponse<Announcement>),
+        (status = 404, description = "Announcement not found")
+    ),
+    tag = "Announcements"
+)]
+pub async fn get_announcement(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let announcement = repo.get_announcement_by_id(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(announcement)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/announcements/{id}",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    request_body = UpdateAnnouncement,
     responses(
-        (status = 200, description = "List of client schedule entries", body = ApiResponse<Vec<ScheduleEntry>>)
+        (status = 200, description = "Announcement updated; displays that showed it withdraw it and the new version is delivered again", body = ApiResponse<Announcement>),
+        (status = 400, description = "Invalid title, priority or time window"),
+        (status = 404, description = "Announcement, target client or group not found")
     ),
-    tag = "Clients"
+    tag = "Announcements"
 )]
-pub async fn get_client_schedule(
+pub async fn update_announcement(
     pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
     id: web::Path<i32>,
+    announcement: web::Json<UpdateAnnouncement>,
 ) -> AppResult<HttpResponse> {
     let repo = Repository::new(pool.get_ref().clone());
-    let schedule = repo.get_client_schedule(*id).await?;
-    Ok(HttpResponse::Ok().json(ApiResponse::new(schedule)))
+    repo.get_announcement_by_id(*id).await?;
+    let recipients = announcement_recipients(&repo, *id).await?;
+    // An invalid update leaves the announcement on the displays
+    let updated = repo
+        .update_announcement(*id, announcement.into_inner())
+        .await?;
+    withdraw_announcement(&ws_manager, &recipients, *id);
+    deliver_announcements(&repo, &ws_manager).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/announcements/{id}",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    responses(
+        (status = 200, description = "Announcement deleted and withdrawn from the displays that showed it", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Announcement not found")
+    ),
+    tag = "Announcements"
+)]
+pub async fn delete_announcement(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.get_announcement_by_id(*id).await?;
+    let recipients = announcement_recipients(&repo, *id).await?;
+    repo.delete_announcement(*id).await?;
+    withdraw_announcement(&ws_manager, &recipients, *id);
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Announcement deleted".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/announcements/{id}/deliveries",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    responses(
+        (status = 200, description = "Delivery and display acknowledgement of every target client", body = ApiResponse<Vec<AnnouncementDelivery>>),
+        (status = 404, description = "Announcement not found")
+    ),
+    tag = "Announcements"
+)]
+pub async fn get_announcement_deliveries(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let deliveries = repo.get_announcement_deliveries(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(deliveries)))
 }
 
-// Sync handler
 #[utoipa::path(
     post,
-    path = "/api/sync",
-    request_body = SyncRequest,
+    path = "/api/announcements/{id}/ack",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    request_body = AcknowledgeAnnouncement,
     responses(
-        (status = 200, description = "Data synced successfully", body = SyncResponse),
-        (status = 400, description = "Bad request"),
-        (status = 404, description = "Client not found")
+        (status = 200, description = "Display acknowledgement recorded", body = ApiResponse<AnnouncementDelivery>),
+        (status = 400, description = "The announcement is not addressed to the client"),
+        (status = 404, description = "Announcement or client not found")
     ),
-    tag = "Sync"
+    tag = "Announcements"
 )]
-pub async fn sync_data(
+pub async fn acknowledge_announcement(
     pool: web::Data<DbPool>,
-    request: web::Json<SyncRequest>,
+    id: web::Path<i32>,
+    ack: web::Json<AcknowledgeAnnouncement>,
 ) -> AppResult<HttpResponse> {
     let repo = Repository::new(pool.get_ref().clone());
-    let req = request.into_inner();
+    let delivery = repo.acknowledge_announcement(*id, &ack.client_uuid).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(delivery)))
+}
 
-    let response = repo
-        .sync_client_data(&req.client_uuid, req.courses, req.schedule_entries)
-        .await?;
+// Emergency alert handlers
+fn emergency_message(alert: &EmergencyAlert) -> WSMessage {
+    WSMessage::Emergency {
+        alert_id: alert.id,
+        kind: alert.kind.clone(),
+        message: alert.message.clone(),
+        drill: alert.drill,
+        started_at: alert.started_at,
+    }
+}
 
-    Ok(HttpResponse::Ok().json(response))
+/// Stores the acknowledgements displays sent over WebSocket.
+async fn flush_emergency_acknowledgements(repo: &Repository, ws_manager: &WSConnectionManager) {
+    for (uuid, alert_id) in ws_manager.take_acknowledgements() {
+        if let Err(e) = repo
+            .acknowledge_emergency(alert_id, &uuid.to_string())
+            .await
+        {
+            tracing::warn!(
+                "Ignoring acknowledgement of emergency alert {} from {}: {}",
+                alert_id,
+                uuid,
+                e
+            );
+        }
+    }
+}
+
+/// Sends every active alert to the connected target clients that have not acknowledged it;
+/// called every few seconds until each display confirms.
+pub async fn repeat_emergency_alerts(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+) -> AppResult<usize> {
+    flush_emergency_acknowledgements(repo, ws_manager).await;
+
+    let mut sent = 0;
+    for alert in repo.get_active_emergency_alerts().await? {
+        let message = emergency_message(&alert);
+        for client in repo.get_unacknowledged_emergency_targets(&alert).await? {
+            let Ok(uuid) = uuid::Uuid::parse_str(&client.uuid) else {
+                continue;
+            };
+            if ws_manager.send_to_client(uuid, message.clone()).is_ok() {
+                repo.record_emergency_sent(alert.id, client.id).await?;
+                sent += 1;
+            }
+        }
+    }
+    Ok(sent)
+}
+
+async fn emergency_status(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+    alert: EmergencyAlert,
+) -> AppResult<EmergencyStatus> {
+    repo.get_emergency_status(alert, &ws_manager.get_online_clients())
+        .await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/emergency",
+    responses(
+        (status = 200, description = "All emergency alerts, latest first", body = ApiResponse<Vec<EmergencyAlert>>)
+    ),
+    tag = "Emergency"
+)]
+pub async fn list_emergency_alerts(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let alerts = repo.get_emergency_alerts().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(alerts)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/emergency",
+    request_body = CreateEmergencyAlert,
+    responses(
+        (status = 200, description = "Alert raised and sent to connected target clients; it is repeated every few seconds until each acknowledges", body = ApiResponse<EmergencyStatus>),
+        (status = 400, description = "Invalid kind or message"),
+        (status = 404, description = "Target client or group not found")
+    ),
+    tag = "Emergency"
+)]
+pub async fn create_emergency_alert(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    alert: web::Json<CreateEmergencyAlert>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_emergency_alert(alert.into_inner()).await?;
+    repeat_emergency_alerts(&repo, &ws_manager).await?;
+    let status = emergency_status(&repo, &ws_manager, created).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(status)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/emergency/active",
+    responses(
+        (status = 200, description = "Live acknowledgement dashboard of every active alert; unconfirmed clients come first", body = ApiResponse<Vec<EmergencyStatus>>)
+    ),
+    tag = "Emergency"
+)]
+pub async fn get_active_emergency_alerts(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    flush_emergency_acknowledgements(&repo, &ws_manager).await;
+    let mut statuses = Vec::new();
+    for alert in repo.get_active_emergency_alerts().await? {
+        statuses.push(emergency_status(&repo, &ws_manager, alert).await?);
+    }
+    Ok(HttpResponse::Ok().json(ApiResponse::new(statuses)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/emergency/{id}",
+    params(
+        ("id" = i32, Path, description = "Emergency alert ID")
+    ),
+    responses(
+        (status = 200, description = "Acknowledgement status of the alert and the rooms that have not confirmed", body = ApiResponse<EmergencyStatus>),
+        (status = 404, description = "Emergency alert not found")
+    ),
+    tag = "Emergency"
+)]
+pub async fn get_emergency_alert(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    flush_emergency_acknowledgements(&repo, &ws_manager).await;
+    let alert = repo.get_emergency_alert_by_id(*id).await?;
+    let status = emergency_status(&repo, &ws_manager, alert).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(status)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/emergency/{id}/ack",
+    params(
+        ("id" = i32, Path, description = "Emergency alert ID")
+    ),
+    request_body = AcknowledgeEmergency,
+    responses(
+        (status = 200, description = "Acknowledgement recorded; the client no longer receives the alert", body = ApiResponse<EmergencyStatus>),
+        (status = 400, description = "The alert is not addressed to the client"),
+        (status = 404, description = "Emergency alert or client not found")
+    ),
+    tag = "Emergency"
+)]
+pub async fn acknowledge_emergency_alert(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+    ack: web::Json<AcknowledgeEmergency>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    repo.acknowledge_emergency(*id, &ack.client_uuid).await?;
+    let alert = repo.get_emergency_alert_by_id(*id).await?;
+    let status = emergency_status(&repo, &ws_manager, alert).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(status)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/emergency/{id}/clear",
+    params(
+        ("id" = i32, Path, description = "Emergency alert ID")
+    ),
+    responses(
+        (status = 200, description = "All clear sent to connected target clients and repeats stopped", body = ApiResponse<EmergencyStatus>),
+        (status = 400, description = "Emergency alert already cleared"),
+        (status = 404, description = "Emergency alert not found")
+    ),
+    tag = "Emergency"
+)]
+pub async fn clear_emergency_alert(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    flush_emergency_acknowledgements(&repo, &ws_manager).await;
+    let cleared = repo.clear_emergency_alert(*id).await?;
+    for client in repo.get_emergency_targets(&cleared).await? {
+        if let Ok(uuid) = uuid::Uuid::parse_str(&client.uuid) {
+            // 离线的客户端重连后不会再收到已解除的警报
+            let _ = ws_manager.send_to_client(uuid, WSMessage::AllClear { alert_id: *id });
+        }
+    }
+    let status = emergency_status(&repo, &ws_manager, cleared).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(status)))
+}
+
+// Display event handlers
+/// Sends the connected clients among `clients` their current countdowns.
+async fn push_countdowns(
+    repo: &Repository,
+    ws_manager: &WSConnectionManager,
+    clients: &[Client],
+) -> AppResult<()> {
+    let online = ws_manager.get_online_clients();
+    let today = Local::now().date_naive();
+    for client in clients {
+        let Ok(uuid) = uuid::Uuid::parse_str(&client.uuid) else {
+            continue;
+        };
+        if !online.contains(&uuid) {
+            continue;
+        }
+        let countdowns = repo.get_client_countdowns(client.id, today).await?;
+        ws_manager.send_command(
+            &[uuid],
+            events::COMMAND_UPDATE,
+            serde_json::json!(countdowns),
+        );
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    responses(
+        (status = 200, description = "All display events, nearest target date first", body = ApiResponse<Vec<DisplayEvent>>)
+    ),
+    tag = "Events"
+)]
+pub async fn list_display_events(pool: web::Data<DbPool>) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let display_events = repo.get_display_events().await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(display_events)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/events",
+    request_body = CreateDisplayEvent,
+    responses(
+        (status = 200, description = "Display event created; connected target clients receive their updated countdowns", body = ApiResponse<DisplayEvent>),
+        (status = 400, description = "Invalid label, style or dates"),
+        (status = 404, description = "Target client or group not found")
+    ),
+    tag = "Events"
+)]
+pub async fn create_display_event(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    event: web::Json<CreateDisplayEvent>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let created = repo.create_display_event(event.into_inner()).await?;
+    let targets = repo.get_display_event_targets(&created).await?;
+    push_countdowns(&repo, &ws_manager, &targets).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(created)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/events/{id}",
+    params(
+        ("id" = i32, Path, description = "Display event ID")
+    ),
+    responses(
+        (status = 200, description = "Display event details", body = ApiResponse<DisplayEvent>),
+        (status = 404, description = "Display event not found")
+    ),
+    tag = "Events"
+)]
+pub async fn get_display_event(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let event = repo.get_display_event_by_id(*id).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(event)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/events/{id}",
+    params(
+        ("id" = i32, Path, description = "Display event ID")
+    ),
+    request_body = UpdateDisplayEvent,
+    responses(
+        (status = 200, description = "Display event updated; connected clients it was or is now shown on receive their updated countdowns", body = ApiResponse<DisplayEvent>),
+        (status = 400, description = "Invalid label, style or dates"),
+        (status = 404, description = "Display event, target client or group not found")
+    ),
+    tag = "Events"
+)]
+pub async fn update_display_event(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+    event: web::Json<UpdateDisplayEvent>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let current = repo.get_display_event_by_id(*id).await?;
+    let mut targets = repo.get_display_event_targets(&current).await?;
+    let updated = repo.update_display_event(*id, event.into_inner()).await?;
+    for client in repo.get_display_event_targets(&updated).await? {
+        if !targets.iter().any(|c| c.id == client.id) {
+            targets.push(client);
+        }
+    }
+    push_countdowns(&repo, &ws_manager, &targets).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/events/{id}",
+    params(
+        ("id" = i32, Path, description = "Display event ID")
+    ),
+    responses(
+        (status = 200, description = "Display event deleted; connected clients it was shown on receive their updated countdowns", body = ApiResponse<MessageResponse>),
+        (status = 404, description = "Display event not found")
+    ),
+    tag = "Events"
+)]
+pub async fn delete_display_event(
+    pool: web::Data<DbPool>,
+    ws_manager: web::Data<WSConnectionManager>,
+    id: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let event = repo.get_display_event_by_id(*id).await?;
+    let targets = repo.get_display_event_targets(&event).await?;
+    repo.delete_display_event(*id).await?;
+    push_countdowns(&repo, &ws_manager, &targets).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(MessageResponse {
+        message: "Display event deleted".to_string(),
+    })))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/clients/{id}/display",
+    params(
+        ("id" = i32, Path, description = "Client ID"),
+        ("at" = Option<String>, Query, description = "Instant as YYYY-MM-DDTHH:MM:SS (default: now)")
+    ),
+    responses(
+        (status = 200, description = "Current and next class, countdowns, active announcements and emergency alerts of the client's display", body = ApiResponse<DisplayContent>),
+        (status = 404, description = "Client not found")
+    ),
+    tag = "Events"
+)]
+pub async fn get_client_display(
+    pool: web::Data<DbPool>,
+    id: web::Path<i32>,
+    query: web::Query<InstantQuery>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let at = query.at.unwrap_or_else(|| Local::now().naive_local());
+    let content = repo.get_display_content(*id, at).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(content)))
 }
 
 // Statistics handlers
@@ -259,7 +2780,8 @@ pub async fn get_setting(
     ),
     request_body = UpdateSetting,
     responses(
-        (status = 200, description = "Setting updated", body = ApiResponse<MessageResponse>)
+        (status = 200, description = "Setting updated", body = ApiResponse<MessageResponse>),
+        (status = 400, description = "Unknown setting, or value of the wrong type or out of range")
     ),
     tag = "Settings"
 )]
@@ -275,6 +2797,38 @@ pub async fn update_setting(
     })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/settings/schema",
+    responses(
+        (status = 200, description = "Type, default, allowed range and description of every setting", body = ApiResponse<Vec<SettingSchema>>)
+    ),
+    tag = "Settings"
+)]
+pub async fn get_settings_schema() -> AppResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(ApiResponse::new(settings::schema())))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/settings/reset",
+    request_body = ResetSettingsRequest,
+    responses(
+        (status = 200, description = "Settings restored to their defaults, except the excluded keys; returns all settings", body = ApiResponse<std::collections::HashMap<String, String>>),
+        (status = 400, description = "Unknown setting in exclude")
+    ),
+    tag = "Settings"
+)]
+pub async fn reset_settings(
+    pool: web::Data<DbPool>,
+    req: web::Json<ResetSettingsRequest>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let exclude = req.into_inner().exclude.unwrap_or_default();
+    let settings = repo.reset_settings(&exclude).await?;
+    Ok(HttpResponse::Ok().json(ApiResponse::new(settings)))
+}
+
 // LMS Management handlers
 #[utoipa::path(
     post,
@@ -610,3 +3164,38 @@ pub async fn get_courses_paginated(
 
     Ok(HttpResponse::Ok().json(ApiResponse::new(response)))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/courses/search",
+    params(
+        ("client_id" = Option<i32>, Query, description = "Filter by client ID"),
+        ("location" = Option<String>, Query, description = "Filter by exact location"),
+        ("attribute" = Option<String>, Query, description = "Attribute name, e.g. credits"),
+        ("value" = Option<String>, Query, description = "Attribute value; JSON literals such as 3 or true are matched by type"),
+        ("page" = Option<i64>, Query, description = "Page number (default: 1)"),
+        ("page_size" = Option<i64>, Query, description = "Page size (default: 20)")
+    ),
+    responses(
+        (status = 200, description = "Paginated list of matching courses", body = ApiResponse<PaginatedResponse<Course>>),
+        (status = 400, description = "Bad request")
+    ),
+    tag = "Courses"
+)]
+pub async fn search_courses(
+    pool: web::Data<DbPool>,
+    search: web::Query<CourseSearchParams>,
+    params: web::Query<PaginationParams>,
+) -> AppResult<HttpResponse> {
+    let repo = Repository::new(pool.get_ref().clone());
+    let (courses, total) = repo
+        .search_courses(&search, params.offset(), params.limit())
+        .await?;
+
+    let response = PaginatedResponse {
+        data: courses,
+        pagination: PaginationInfo::new(params.page, params.page_size, total),
+    };
+
+    Ok(HttpResponse::Ok().json(ApiResponse::new(response)))
+}
@@ -0,0 +1,367 @@
+use crate::calendar::{self, STATUS_CANCELLED, STATUS_SCHEDULED};
+use crate::models::{EffectiveEntry, ScheduleEntry, ScheduleException, Semester};
+use crate::semester::{week_number, week_start};
+use crate::validation::parse_hhmm;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use std::collections::BTreeSet;
+
+/// 订阅范围
+pub const SCOPE_CLIENT: &str = "client"; // target 为客户端 ID
+pub const SCOPE_TEACHER: &str = "teacher"; // target 为教师姓名，跨客户端汇总
+pub const SCOPE_LOCATION: &str = "location"; // target 为教室（条目教室优先于课程地点）
+
+pub const FEED_SCOPES: &[&str] = &[SCOPE_CLIENT, SCOPE_TEACHER, SCOPE_LOCATION];
+
+const PRODUCT_ID: &str = "-//Zixiao-System//ClassTop Management Server//CN";
+
+/// iCalendar 内容行的最大字节数（不含换行）
+const MAX_LINE_OCTETS: usize = 75;
+
+/// 订阅地址，令牌即凭据，无需登录
+pub fn feed_path(token: &str) -> String {
+    format!("/api/calendar/{}.ics", token)
+}
+
+/// 课表条目的稳定 UID，按客户端与客户端上的条目 ID 生成，重新同步后保持不变
+pub fn entry_uid(entry: &ScheduleEntry) -> String {
+    format!(
+        "classtop-{}-{}@classtop",
+        entry.client_id, entry.entry_id_on_client
+    )
+}
+
+/// 生成 VCALENDAR 文本
+///
+/// 每个条目生成一个每周重复的事件：有当前学期时从第一次上课重复到最后一次，
+/// 不在 `weeks` 中的周用 EXDATE 排除；没有学期时从 `today` 所在周起无限重复。
+/// 例外日当天停上、调走的课同样用 EXDATE 排除，调课日加上的课与调到的课生成单次事件。
+/// 时间为不带时区的本地时间，`stamp` 为 UTC。
+pub fn render_calendar(
+    name: &str,
+    semester: Option<&Semester>,
+    entries: &[ScheduleEntry],
+    exceptions: &[ScheduleException],
+    stamp: NaiveDateTime,
+    today: NaiveDate,
+) -> String {
+    let range_start = semester.map_or_else(|| week_start(today), |s| s.start_date);
+    let range_end = semester.map(|s| s.end_date);
+    let week_of = |date: NaiveDate| semester.map(|s| week_number(s.start_date, date));
+    let dtstamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(name)),
+        "X-PUBLISHED-TTL:PT1H".to_string(),
+    ];
+
+    for entry in entries {
+        let (Some(start), Some(end)) = (parse_hhmm(&entry.start_time), parse_hhmm(&entry.end_time))
+        else {
+            continue;
+        };
+        let client_exceptions: Vec<ScheduleException> = exceptions
+            .iter()
+            .filter(|e| e.client_id.is_none_or(|id| id == entry.client_id))
+            .cloned()
+            .collect();
+        let occurs = |date: NaiveDate| {
+            date >= range_start
+                && range_end.is_none_or(|end| date <= end)
+                && calendar::day_of_week(date) == entry.day_of_week
+                && week_of(date).is_none_or(|week| entry.held_in_week(week))
+        };
+
+        // Weekly dates from the first to the last class
+        let first_date = range_start
+            + Duration::days(
+                i64::from(entry.day_of_week - calendar::day_of_week(range_start)).rem_euclid(7),
+            );
+        let weekly: Vec<NaiveDate> = match range_end {
+            Some(end) => std::iter::successors(Some(first_date), |d| Some(*d + Duration::days(7)))
+                .take_while(|d| *d <= end)
+                .collect(),
+            None => vec![first_date],
+        };
+        let held: Vec<NaiveDate> = weekly.iter().copied().filter(|d| occurs(*d)).collect();
+        let (Some(first), Some(last)) = (held.first().copied(), held.last().copied()) else {
+            continue;
+        };
+        let mut excluded: BTreeSet<NaiveDate> = weekly
+            .iter()
+            .copied()
+            .filter(|d| *d > first && *d < last && !occurs(*d))
+            .collect();
+
+        // Dates touched by exceptions
+        let mut one_off = Vec::new();
+        let exception_dates: BTreeSet<NaiveDate> = client_exceptions
+            .iter()
+            .flat_map(|e| [Some(e.date), e.new_date])
+            .flatten()
+            .filter(|d| *d >= range_start && range_end.is_none_or(|end| *d <= end))
+            .collect();
+        for date in exception_dates {
+            let effective = calendar::effective_schedule(
+                date,
+                week_of(date),
+                std::slice::from_ref(entry),
+                &client_exceptions,
+            );
+            let at_usual_time = |e: &EffectiveEntry| {
+                e.status == STATUS_SCHEDULED
+                    && e.entry.start_time == entry.start_time
+                    && e.entry.end_time == entry.end_time
+            };
+            let regular = occurs(date) && effective.iter().any(at_usual_time);
+            if occurs(date) && !regular {
+                excluded.insert(date);
+            }
+            // Remapped days, bell schedule switches and relocated classes become single events
+            for e in effective {
+                let is_regular = regular && at_usual_time(&e);
+                if e.status != STATUS_CANCELLED && !is_regular {
+                    one_off.push((date, e.entry));
+                }
+            }
+        }
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", entry_uid(entry)));
+        lines.push(format!("DTSTAMP:{}", dtstamp));
+        lines.push(format!("DTSTART:{}", date_time(first, start)));
+        lines.push(format!("DTEND:{}", date_time(first, end)));
+        lines.push(match range_end {
+            Some(_) => format!("RRULE:FREQ=WEEKLY;UNTIL={}", date_time(last, end)),
+            None => "RRULE:FREQ=WEEKLY".to_string(),
+        });
+        if !excluded.is_empty() {
+            let dates: Vec<String> = excluded.iter().map(|d| date_time(*d, start)).collect();
+            lines.push(format!("EXDATE:{}", dates.join(",")));
+        }
+        lines.extend(event_details(entry));
+        lines.push("END:VEVENT".to_string());
+
+        for (date, moved) in one_off {
+            let (Some(start), Some(end)) =
+                (parse_hhmm(&moved.start_time), parse_hhmm(&moved.end_time))
+            else {
+                continue;
+            };
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!(
+                "UID:classtop-{}-{}-{}@classtop",
+                entry.client_id,
+                entry.entry_id_on_client,
+                date.format("%Y%m%d")
+            ));
+            lines.push(format!("DTSTAMP:{}", dtstamp));
+            lines.push(format!("DTSTART:{}", date_time(date, start)));
+            lines.push(format!("DTEND:{}", date_time(date, end)));
+            lines.extend(event_details(&moved));
+            lines.push("END:VEVENT".to_string());
+        }
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line) + "\r\n").collect()
+}
+
+fn event_details(entry: &ScheduleEntry) -> Vec<String> {
+    let mut lines = vec![format!(
+        "SUMMARY:{}",
+        escape_text(entry.course_name.as_deref().unwrap_or("Class"))
+    )];
+    if let Some(location) = entry.room.as_deref().or(entry.location.as_deref()) {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+
+    let mut description = Vec::new();
+    if let Some(teacher) = &entry.teacher {
+        description.push(format!("Teacher: {}", teacher));
+    }
+    if let Some(weeks) = &entry.weeks_text {
+        description.push(format!("Weeks: {}", weeks));
+    }
+    if let Some(note) = &entry.note {
+        description.push(note.clone());
+    }
+    if !description.is_empty() {
+        lines.push(format!(
+            "DESCRIPTION:{}",
+            escape_text(&description.join("\n"))
+        ));
+    }
+    lines
+}
+
+/// 不带时区的本地时间，`minutes` 为自零点起的分钟数
+fn date_time(date: NaiveDate, minutes: u32) -> String {
+    format!(
+        "{}T{:02}{:02}00",
+        date.format("%Y%m%d"),
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// 转义 TEXT 值中的反斜杠、分号、逗号与换行
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 按 75 字节折行，续行以空格开头，不拆分多字节字符
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the continuation line
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Attributes;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn semester() -> Semester {
+        // Monday 2024-09-02, four weeks
+        Semester {
+            id: 1,
+            name: "Autumn".to_string(),
+            start_date: date("2024-09-02"),
+            end_date: date("2024-09-29"),
+            week_count: 4,
+            is_active: true,
+            created_at: date("2024-08-01").and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    fn entry(weeks: Option<Vec<i32>>) -> ScheduleEntry {
+        ScheduleEntry {
+            id: 10,
+            client_id: 1,
+            entry_id_on_client: 3,
+            course_id: 7,
+            course_name: Some("Math, advanced".to_string()),
+            teacher: Some("Li".to_string()),
+            location: Some("A101".to_string()),
+            color: None,
+            day_of_week: 2,
+            start_time: "08:00".to_string(),
+            end_time: "08:45".to_string(),
+            weeks_text: weeks.as_deref().map(crate::weeks::format),
+            weeks_legacy: None,
+            weeks,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+            origin: "client".to_string(),
+            room_id: None,
+            period_start: None,
+            period_end: None,
+        }
+    }
+
+    fn exception(kind: &str, on: &str) -> ScheduleException {
+        ScheduleException {
+            id: 1,
+            client_id: None,
+            date: date(on),
+            kind: kind.to_string(),
+            follows_day_of_week: None,
+            bell_schedule_id: None,
+            bell_periods: None,
+            entry_id: None,
+            new_date: None,
+            new_start_time: None,
+            new_end_time: None,
+            new_room: None,
+            reason: None,
+            created_at: date("2024-08-01").and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    fn render(entries: &[ScheduleEntry], exceptions: &[ScheduleException]) -> String {
+        render_calendar(
+            "Class 1",
+            Some(&semester()),
+            entries,
+            exceptions,
+            date("2024-08-01").and_hms_opt(12, 0, 0).unwrap(),
+            date("2024-09-10"),
+        )
+    }
+
+    #[test]
+    fn test_weekly_rule_with_skipped_weeks() {
+        let ics = render(&[entry(Some(vec![1, 3, 4]))], &[]);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("UID:classtop-1-3@classtop\r\n"));
+        assert!(ics.contains("DTSTART:20240903T080000\r\n"));
+        assert!(ics.contains("DTEND:20240903T084500\r\n"));
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;UNTIL=20240924T084500\r\n"));
+        assert!(ics.contains("EXDATE:20240910T080000\r\n"));
+        assert!(ics.contains("SUMMARY:Math\\, advanced\r\n"));
+        assert!(ics.contains("LOCATION:A101\r\n"));
+    }
+
+    #[test]
+    fn test_exceptions_exclude_and_add_events() {
+        let mut relocated = exception(calendar::RELOCATE_ENTRY, "2024-09-17");
+        relocated.entry_id = Some(10);
+        relocated.new_date = Some(date("2024-09-19"));
+        relocated.new_start_time = Some("14:00".to_string());
+        relocated.new_end_time = Some("14:45".to_string());
+        let exceptions = [exception(calendar::CANCEL_DAY, "2024-09-10"), relocated];
+
+        let ics = render(&[entry(None)], &exceptions);
+        assert!(ics.contains("RRULE:FREQ=WEEKLY;UNTIL=20240924T084500\r\n"));
+        assert!(ics.contains("EXDATE:20240910T080000,20240917T080000\r\n"));
+        assert!(ics.contains("UID:classtop-1-3-20240919@classtop\r\n"));
+        assert!(ics.contains("DTSTART:20240919T140000\r\n"));
+    }
+
+    #[test]
+    fn test_entry_outside_semester_weeks_is_omitted() {
+        let ics = render(&[entry(Some(vec![9]))], &[]);
+        assert!(!ics.contains("BEGIN:VEVENT"));
+    }
+
+    #[test]
+    fn test_escape_and_fold() {
+        assert_eq!(escape_text("a;b,c\\d\ne"), "a\\;b\\,c\\\\d\\ne");
+
+        let line = format!("DESCRIPTION:{}", "课".repeat(40));
+        let folded = fold_line(&line);
+        assert!(folded.split("\r\n").all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
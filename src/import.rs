@@ -0,0 +1,935 @@
+use crate::calendar;
+use crate::models::{ClientCourse, ClientScheduleEntry, ImportIssue, Semester, SyncRejection};
+use crate::semester::{week_number, week_start};
+use crate::timetable;
+use crate::validation::MAX_WEEK_NUMBER;
+use crate::weeks;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// 导入文件格式
+pub const FORMAT_ICS: &str = "ics";
+pub const FORMAT_CSV: &str = "csv";
+
+pub const IMPORT_FORMATS: &[&str] = &[FORMAT_ICS, FORMAT_CSV];
+
+/// 课程映射到客户端的方式
+pub const MAP_BY_ROOM: &str = "room"; // 按教室匹配客户端名称或描述
+pub const MAP_BY_CLIENT_NAME: &str = "client_name"; // 按日历名称（ics）或班级列（csv）匹配客户端名称
+
+pub const MAP_BY_OPTIONS: &[&str] = &[MAP_BY_ROOM, MAP_BY_CLIENT_NAME];
+
+/// 单次 RRULE 展开的最多次数，防止无终止的规则
+const MAX_OCCURRENCES: usize = 366;
+
+/// 从导入文件中解析出的一节课
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedClass {
+    pub source: String, // 行号或事件 UID，用于报告
+    pub client_key: Option<String>,
+    pub course_name: String,
+    pub teacher: Option<String>,
+    pub location: Option<String>,
+    pub day_of_week: i32,
+    pub start_time: String,
+    pub end_time: String,
+    pub weeks: Option<Vec<i32>>, // None 表示每周
+    pub note: Option<String>,
+}
+
+fn issue(source: impl Into<String>, reason: impl Into<String>) -> ImportIssue {
+    ImportIssue {
+        source: source.into(),
+        reason: reason.into(),
+    }
+}
+
+/// 将表格中常见的 `8:00`、`08:00:00` 规范化为 `HH:MM`
+pub fn normalize_time(value: &str) -> Option<String> {
+    let value = value.trim();
+    let time = NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .ok()?;
+    Some(time.format("%H:%M").to_string())
+}
+
+/// 合并仅周次不同的同一节课；任一来源为每周时结果为每周
+pub fn merge_classes(classes: Vec<ImportedClass>) -> Vec<ImportedClass> {
+    let mut merged: Vec<ImportedClass> = Vec::new();
+    for class in classes {
+        let same = merged.iter_mut().find(|c| {
+            c.client_key == class.client_key
+                && c.course_name == class.course_name
+                && c.teacher == class.teacher
+                && c.location == class.location
+                && c.day_of_week == class.day_of_week
+                && c.start_time == class.start_time
+                && c.end_time == class.end_time
+        });
+        match same {
+            Some(existing) => {
+                existing.weeks = match (existing.weeks.take(), class.weeks) {
+                    (Some(mut weeks), Some(more)) => {
+                        weeks.extend(more);
+                        weeks.sort_unstable();
+                        weeks.dedup();
+                        Some(weeks)
+                    }
+                    _ => None,
+                };
+            }
+            None => merged.push(class),
+        }
+    }
+    merged
+}
+
+// iCalendar
+
+/// 解析 .ics 文件，按 `semester` 将日期换算为教学周
+///
+/// 每周重复的事件（RRULE FREQ=WEEKLY，支持 INTERVAL、COUNT、UNTIL、BYDAY）
+/// 按星期拆分为课程，EXDATE 与被 RECURRENCE-ID 覆盖的日期不计入周次；
+/// 覆盖事件与单次事件各自成为只在一周上课的课程。
+/// 全天事件、已取消事件与学期外的事件记为问题。
+pub fn parse_ics(
+    content: &str,
+    semester: &Semester,
+    map_by: &str,
+) -> (Vec<ImportedClass>, Vec<ImportIssue>) {
+    let mut classes = Vec::new();
+    let mut issues = Vec::new();
+    let mut calendar_name = None;
+    let mut events: Vec<Vec<Property>> = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+
+    for line in unfold(content) {
+        let Some(property) = Property::parse(&line) else {
+            continue;
+        };
+        match (property.name.as_str(), property.value.as_str()) {
+            ("BEGIN", "VEVENT") => current = Some(Vec::new()),
+            ("END", "VEVENT") => events.extend(current.take()),
+            ("X-WR-CALNAME", _) if current.is_none() => {
+                calendar_name = Some(unescape_text(&property.value))
+            }
+            _ => {
+                if let Some(event) = current.as_mut() {
+                    event.push(property);
+                }
+            }
+        }
+    }
+
+    // Occurrences replaced by RECURRENCE-ID overrides, per UID
+    let mut overridden: HashMap<String, BTreeSet<NaiveDate>> = HashMap::new();
+    for event in &events {
+        if let (Some(uid), Some(recurrence_id)) = (
+            find(event, "UID"),
+            find(event, "RECURRENCE-ID").and_then(parse_date_time),
+        ) {
+            overridden
+                .entry(uid.value.clone())
+                .or_default()
+                .insert(recurrence_id.date());
+        }
+    }
+
+    for (index, event) in events.iter().enumerate() {
+        let source = find(event, "UID")
+            .map(|uid| uid.value.clone())
+            .unwrap_or_else(|| format!("event {}", index + 1));
+        let is_override = find(event, "RECURRENCE-ID").is_some();
+        let excluded = if is_override {
+            BTreeSet::new()
+        } else {
+            overridden.get(&source).cloned().unwrap_or_default()
+        };
+        match parse_event(event, semester, excluded) {
+            Ok(parsed) => {
+                for mut class in parsed {
+                    class.source = source.clone();
+                    class.client_key = if map_by == MAP_BY_CLIENT_NAME {
+                        calendar_name.clone()
+                    } else {
+                        class.location.clone()
+                    };
+                    classes.push(class);
+                }
+            }
+            Err(reason) => issues.push(issue(source, reason)),
+        }
+    }
+
+    (merge_classes(classes), issues)
+}
+
+fn parse_event(
+    event: &[Property],
+    semester: &Semester,
+    mut excluded: BTreeSet<NaiveDate>,
+) -> Result<Vec<ImportedClass>, String> {
+    if find(event, "STATUS").is_some_and(|s| s.value.eq_ignore_ascii_case("CANCELLED")) {
+        return Err("event is cancelled".to_string());
+    }
+    let course_name = find(event, "SUMMARY")
+        .map(|s| unescape_text(&s.value).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or("event has no SUMMARY")?;
+    let dtstart = find(event, "DTSTART").ok_or("event has no DTSTART")?;
+    if !dtstart.value.contains('T') {
+        return Err("all-day events are not classes".to_string());
+    }
+    let start = parse_date_time(dtstart).ok_or("DTSTART is not a date-time")?;
+    let end = match (find(event, "DTEND"), find(event, "DURATION")) {
+        (Some(dtend), _) => parse_date_time(dtend).ok_or("DTEND is not a date-time")?,
+        (None, Some(duration)) => {
+            start + parse_duration(&duration.value).ok_or("DURATION is not supported")?
+        }
+        (None, None) => return Err("event has no DTEND or DURATION".to_string()),
+    };
+    if end <= start || end.date() != start.date() {
+        return Err("event must end after it starts on the same day".to_string());
+    }
+
+    let mut dates = match find(event, "RRULE") {
+        Some(rule) => expand_weekly(&rule.value, start.date(), semester.end_date)?,
+        None => vec![start.date()],
+    };
+    for property in event.iter().filter(|p| p.name == "RDATE") {
+        dates.extend(parse_date_list(property));
+    }
+    for property in event.iter().filter(|p| p.name == "EXDATE") {
+        excluded.extend(parse_date_list(property));
+    }
+    dates.retain(|d| !excluded.contains(d));
+
+    let mut weeks_by_day: BTreeMap<i32, BTreeSet<i32>> = BTreeMap::new();
+    for date in dates {
+        if date < semester.start_date || date > semester.end_date {
+            continue;
+        }
+        weeks_by_day
+            .entry(calendar::day_of_week(date))
+            .or_default()
+            .insert(week_number(semester.start_date, date));
+    }
+    if weeks_by_day.is_empty() {
+        return Err(format!(
+            "no occurrence falls within semester '{}'",
+            semester.name
+        ));
+    }
+
+    let teacher = find(event, "ORGANIZER")
+        .and_then(|o| param(&o.params, "CN"))
+        .or_else(|| {
+            let description = unescape_text(&find(event, "DESCRIPTION")?.value);
+            description.lines().find_map(|line| {
+                ["Teacher:", "教师：", "教师:"]
+                    .iter()
+                    .find_map(|prefix| line.strip_prefix(prefix))
+                    .map(|t| t.trim().to_string())
+            })
+        });
+    let location = find(event, "LOCATION")
+        .map(|l| unescape_text(&l.value).trim().to_string())
+        .filter(|l| !l.is_empty());
+    let every_week: BTreeSet<i32> = (1..=semester.week_count).collect();
+
+    Ok(weeks_by_day
+        .into_iter()
+        .map(|(day_of_week, weeks)| ImportedClass {
+            source: String::new(),
+            client_key: None,
+            course_name: course_name.clone(),
+            teacher: teacher.clone(),
+            location: location.clone(),
+            day_of_week,
+            start_time: start.format("%H:%M").to_string(),
+            end_time: end.format("%H:%M").to_string(),
+            weeks: (weeks != every_week).then(|| weeks.into_iter().collect()),
+            note: None,
+        })
+        .collect())
+}
+
+/// 展开每周重复规则，最晚到 `until`（学期结束日）
+fn expand_weekly(rule: &str, start: NaiveDate, until: NaiveDate) -> Result<Vec<NaiveDate>, String> {
+    let parts: HashMap<String, String> = rule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.to_ascii_uppercase(), value.to_string()))
+        .collect();
+
+    if parts.get("FREQ").map(String::as_str) != Some("WEEKLY") {
+        return Err(format!(
+            "only weekly recurrence is supported, got '{}'",
+            rule
+        ));
+    }
+    // 间隔超过一学期的规则只会在第一周出现，更大的值会使日期计算溢出
+    let interval: i64 = match parts.get("INTERVAL") {
+        Some(value) => value
+            .parse()
+            .ok()
+            .filter(|i| (1..=i64::from(MAX_WEEK_NUMBER)).contains(i))
+            .ok_or_else(|| format!("INTERVAL must be between 1 and {}", MAX_WEEK_NUMBER))?,
+        None => 1,
+    };
+    let count: Option<usize> = match parts.get("COUNT") {
+        Some(value) => Some(value.parse().map_err(|_| "COUNT must be a number")?),
+        None => None,
+    };
+    let rule_until = match parts.get("UNTIL") {
+        Some(value) => Some(parse_date_value(value).ok_or("UNTIL is not a date")?),
+        None => None,
+    };
+    let until = rule_until.map_or(until, |u| u.min(until));
+
+    let mut days: Vec<i64> = match parts.get("BYDAY") {
+        Some(value) => value
+            .split(',')
+            .map(|day| {
+                // Weekly rules ignore ordinal prefixes such as "1MO"
+                let day =
+                    day.trim_start_matches(|c: char| c == '+' || c == '-' || c.is_ascii_digit());
+                ["MO", "TU", "WE", "TH", "FR", "SA", "SU"]
+                    .iter()
+                    .position(|d| d.eq_ignore_ascii_case(day))
+                    .map(|i| i as i64)
+                    .ok_or_else(|| format!("unknown BYDAY value '{}'", day))
+            })
+            .collect::<Result<_, _>>()?,
+        None => vec![i64::from(start.weekday().num_days_from_monday())],
+    };
+    days.sort_unstable();
+    days.dedup();
+
+    let mut dates = Vec::new();
+    let mut week = week_start(start);
+    while week <= until && dates.len() < MAX_OCCURRENCES {
+        for day in &days {
+            let date = week + Duration::days(*day);
+            if date < start || date > until {
+                continue;
+            }
+            if count.is_some_and(|count| dates.len() >= count) {
+                return Ok(dates);
+            }
+            dates.push(date);
+        }
+        week += Duration::days(7 * interval);
+    }
+    Ok(dates)
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: String,
+    value: String,
+}
+
+impl Property {
+    /// `NAME;PARAM=VALUE:VALUE`，参数中的引号内可以有冒号
+    fn parse(line: &str) -> Option<Self> {
+        let mut in_quotes = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                None
+            }
+            ':' if !in_quotes => Some(i),
+            _ => None,
+        })?;
+        let (head, value) = (&line[..colon], &line[colon + 1..]);
+        let (name, params) = head.split_once(';').unwrap_or((head, ""));
+        Some(Property {
+            name: name.trim().to_ascii_uppercase(),
+            params: params.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+fn find<'a>(event: &'a [Property], name: &str) -> Option<&'a Property> {
+    event.iter().find(|p| p.name == name)
+}
+
+fn param(params: &str, name: &str) -> Option<String> {
+    params.split(';').find_map(|p| {
+        let (key, value) = p.split_once('=')?;
+        key.eq_ignore_ascii_case(name)
+            .then(|| value.trim_matches('"').to_string())
+    })
+}
+
+/// 展开折行：以空格或制表符开头的行接在上一行之后
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => text.push('\n'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+/// 日期时间按本地时间解析；带 `Z` 的 UTC 时间转换为服务器本地时间，TZID 按本地时间处理
+fn parse_date_time(property: &Property) -> Option<NaiveDateTime> {
+    let value = property.value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(
+            Utc.from_utc_datetime(&naive)
+                .with_timezone(&Local)
+                .naive_local(),
+        );
+    }
+    NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()
+}
+
+/// UNTIL、EXDATE 等取日期部分
+fn parse_date_value(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+fn parse_date_list(property: &Property) -> Vec<NaiveDate> {
+    property
+        .value
+        .split(',')
+        .filter_map(|v| parse_date_value(v.trim()))
+        .collect()
+}
+
+/// 仅支持时、分、秒组成的 DURATION，如 `PT45M`、`PT1H30M`
+fn parse_duration(value: &str) -> Option<Duration> {
+    let rest = value.trim().strip_prefix("PT")?;
+    let mut total = 0i64;
+    let mut number = String::new();
+    for c in rest.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let n: i64 = number.parse().ok()?;
+        number.clear();
+        total += match c {
+            'H' => n * 3600,
+            'M' => n * 60,
+            'S' => n,
+            _ => return None,
+        };
+    }
+    (number.is_empty() && total > 0).then(|| Duration::seconds(total))
+}
+
+// CSV
+
+/// CSV 列名（小写）与字段的对应关系
+const CSV_COLUMNS: &[(&str, &[&str])] = &[
+    (
+        "client",
+        &["client", "client_name", "class", "班级", "客户端"],
+    ),
+    (
+        "course",
+        &["course", "course_name", "name", "课程", "课程名称"],
+    ),
+    ("teacher", &["teacher", "教师", "老师"]),
+    ("room", &["room", "location", "教室", "地点"]),
+    ("day", &["day", "day_of_week", "weekday", "星期"]),
+    ("start", &["start", "start_time", "开始", "开始时间"]),
+    ("end", &["end", "end_time", "结束", "结束时间"]),
+    ("weeks", &["weeks", "周次"]),
+    ("note", &["note", "备注"]),
+];
+
+/// 解析带表头的 CSV，每行一节课
+///
+/// 必需列为课程、星期、开始与结束时间；星期可写作 `1`-`7`、`Mon`、`周一` 等，
+/// 周次为周次表达式，留空表示每周。
+pub fn parse_csv(content: &str, map_by: &str) -> (Vec<ImportedClass>, Vec<ImportIssue>) {
+    let mut rows = csv_rows(content).into_iter();
+    let mut issues = Vec::new();
+    let Some((_, header)) = rows.next() else {
+        return (Vec::new(), vec![issue("line 1", "file is empty")]);
+    };
+
+    let columns: HashMap<&str, usize> = CSV_COLUMNS
+        .iter()
+        .filter_map(|(field, aliases)| {
+            let index = header
+                .iter()
+                .position(|h| aliases.contains(&h.trim().to_lowercase().as_str()))?;
+            Some((*field, index))
+        })
+        .collect();
+    let missing: Vec<&str> = ["course", "day", "start", "end"]
+        .into_iter()
+        .filter(|field| !columns.contains_key(field))
+        .collect();
+    if !missing.is_empty() {
+        return (
+            Vec::new(),
+            vec![issue(
+                "line 1",
+                format!("missing columns: {}", missing.join(", ")),
+            )],
+        );
+    }
+
+    let mut classes = Vec::new();
+    for (line, row) in rows {
+        let source = format!("line {}", line);
+        let cell = |field: &str| {
+            columns
+                .get(field)
+                .and_then(|i| row.get(*i))
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        if row.iter().all(|v| v.trim().is_empty()) {
+            continue;
+        }
+        match csv_class(&cell) {
+            Ok(mut class) => {
+                class.source = source;
+                class.client_key = if map_by == MAP_BY_CLIENT_NAME {
+                    cell("client")
+                } else {
+                    class.location.clone()
+                };
+                classes.push(class);
+            }
+            Err(reason) => issues.push(issue(source, reason)),
+        }
+    }
+
+    (merge_classes(classes), issues)
+}
+
+fn csv_class(cell: &dyn Fn(&str) -> Option<String>) -> Result<ImportedClass, String> {
+    let course_name = cell("course").ok_or("course is empty")?;
+    let day = cell("day").ok_or("day is empty")?;
+    let day_of_week = parse_day(&day).ok_or_else(|| format!("'{}' is not a weekday", day))?;
+    let start_time = cell("start")
+        .as_deref()
+        .and_then(normalize_time)
+        .ok_or("start must be HH:MM")?;
+    let end_time = cell("end")
+        .as_deref()
+        .and_then(normalize_time)
+        .ok_or("end must be HH:MM")?;
+    let weeks = match cell("weeks") {
+        Some(expr) => Some(weeks::parse(&expr)?),
+        None => None,
+    };
+
+    Ok(ImportedClass {
+        source: String::new(),
+        client_key: None,
+        course_name,
+        teacher: cell("teacher"),
+        location: cell("room"),
+        day_of_week,
+        start_time,
+        end_time,
+        weeks,
+        note: cell("note"),
+    })
+}
+
+/// 星期：`1`-`7`、英文全称或缩写、`周一`/`星期一`/`礼拜一`（`日`/`天` 为周日）
+fn parse_day(value: &str) -> Option<i32> {
+    let value = value.trim().to_lowercase();
+    if let Ok(day) = value.parse::<i32>() {
+        return (1..=7).contains(&day).then_some(day);
+    }
+    const ENGLISH: [&str; 7] = [
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ];
+    if value.len() >= 3 {
+        if let Some(i) = ENGLISH.iter().position(|d| d.starts_with(&value)) {
+            return Some(i as i32 + 1);
+        }
+    }
+    let chinese = ["周", "星期", "礼拜"]
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))?;
+    match chinese {
+        "一" => Some(1),
+        "二" => Some(2),
+        "三" => Some(3),
+        "四" => Some(4),
+        "五" => Some(5),
+        "六" => Some(6),
+        "日" | "天" | "七" => Some(7),
+        _ => None,
+    }
+}
+
+/// 按 RFC 4180 拆分 CSV，返回（起始行号, 字段）；支持引号、转义引号与引号内换行
+fn csv_rows(content: &str) -> Vec<(usize, Vec<String>)> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    rows
+}
+
+/// 一个客户端的导入数据（同步格式）
+#[derive(Debug, Default)]
+pub struct ImportPayload {
+    pub courses: Vec<ClientCourse>,
+    pub entries: Vec<ClientScheduleEntry>,
+    /// 客户端 ID 对应的首个来源（行号或事件 UID），用于报告校验错误
+    pub course_sources: HashMap<i32, String>,
+    pub entry_sources: HashMap<i32, String>,
+}
+
+impl ImportPayload {
+    /// 同步校验拒绝的课程或条目，按其来源报告
+    pub fn issue(&self, rejection: &SyncRejection) -> ImportIssue {
+        let sources = if rejection.item_type == "course" {
+            &self.course_sources
+        } else {
+            &self.entry_sources
+        };
+        issue(
+            sources
+                .get(&rejection.id)
+                .cloned()
+                .unwrap_or_else(|| format!("{} {}", rejection.item_type, rejection.id)),
+            format!("{}: {}", rejection.field, rejection.reason),
+        )
+    }
+}
+
+/// 将一个客户端的导入课程转换为同步格式
+///
+/// 按名称与教师复用已有课程，按课程、星期与开始时间复用已有条目（更新其周次与教室），
+/// 其余分配新的服务端（负数）ID，避免与客户端自有数据冲突。
+pub fn to_sync_payload(
+    classes: &[ImportedClass],
+    existing_courses: &[ClientCourse],
+    existing_entries: &[ClientScheduleEntry],
+) -> ImportPayload {
+    let mut next_course_id = timetable::next_server_id(existing_courses.iter().map(|c| c.id));
+    let mut next_entry_id = timetable::next_server_id(existing_entries.iter().map(|e| e.id));
+    let mut courses: Vec<ClientCourse> = Vec::new();
+    let mut entries: Vec<ClientScheduleEntry> = Vec::new();
+    let mut course_sources = HashMap::new();
+    let mut entry_sources = HashMap::new();
+
+    for class in classes {
+        let course_id = match courses
+            .iter()
+            .chain(existing_courses)
+            .find(|c| c.name == class.course_name && c.teacher == class.teacher)
+            .cloned()
+        {
+            Some(course) => {
+                let id = course.id;
+                if !courses.iter().any(|c| c.id == id) {
+                    courses.push(course);
+                }
+                id
+            }
+            None => {
+                let id = next_course_id;
+                next_course_id -= 1;
+                courses.push(ClientCourse {
+                    id,
+                    name: class.course_name.clone(),
+                    teacher: class.teacher.clone(),
+                    location: class.location.clone(),
+                    color: None,
+                    note: None,
+                    attributes: Default::default(),
+                });
+                id
+            }
+        };
+
+        let course_location = courses
+            .iter()
+            .find(|c| c.id == course_id)
+            .and_then(|c| c.location.clone());
+        let room = class
+            .location
+            .clone()
+            .filter(|room| Some(room) != course_location.as_ref());
+        let existing = existing_entries.iter().find(|e| {
+            e.course_id == course_id
+                && e.day_of_week == class.day_of_week
+                && e.start_time == class.start_time
+        });
+        let entry = match existing {
+            Some(existing) => ClientScheduleEntry {
+                end_time: class.end_time.clone(),
+                weeks: class.weeks.clone(),
+                room,
+                note: class.note.clone().or_else(|| existing.note.clone()),
+                ..existing.clone()
+            },
+            None => {
+                next_entry_id -= 1;
+                ClientScheduleEntry {
+                    id: next_entry_id + 1,
+                    course_id,
+                    day_of_week: class.day_of_week,
+                    start_time: class.start_time.clone(),
+                    end_time: class.end_time.clone(),
+                    weeks: class.weeks.clone(),
+                    invalid_weeks: None,
+                    note: class.note.clone(),
+                    room,
+                    attributes: Default::default(),
+                }
+            }
+        };
+        course_sources
+            .entry(course_id)
+            .or_insert_with(|| class.source.clone());
+        entry_sources
+            .entry(entry.id)
+            .or_insert_with(|| class.source.clone());
+        if !entries.iter().any(|e| e.id == entry.id) {
+            entries.push(entry);
+        }
+    }
+
+    ImportPayload {
+        courses,
+        entries,
+        course_sources,
+        entry_sources,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn semester() -> Semester {
+        // Monday 2024-09-02, four weeks
+        Semester {
+            id: 1,
+            name: "Autumn".to_string(),
+            start_date: date("2024-09-02"),
+            end_date: date("2024-09-29"),
+            week_count: 4,
+            is_active: true,
+            created_at: date("2024-08-01").and_hms_opt(0, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_parse_ics_weekly_rule() {
+        let ics = "BEGIN:VCALENDAR\r\nX-WR-CALNAME:Class 1\r\nBEGIN:VEVENT\r\nUID:math\r\n\
+            SUMMARY:Math\\, advanced\r\nLOCATION:A101\r\nORGANIZER;CN=\"Li\":mailto:li@example.com\r\n\
+            DTSTART;TZID=Asia/Shanghai:20240903T080000\r\nDURATION:PT45M\r\n\
+            RRULE:FREQ=WEEKLY;BYDAY=TU,TH;UNTIL=20240930T000000Z\r\n\
+            EXDATE:20240910T080000\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:math\r\nRECURRENCE-ID:20240917T080000\r\nSUMMARY:Math\\, advanced\r\n\
+            DTSTART:20240917T140000\r\nDTEND:20240917T144500\r\nLOCATION:A101\r\nEND:VEVENT\r\n\
+            BEGIN:VEVENT\r\nUID:holiday\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20240916\r\nEND:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let (classes, issues) = parse_ics(ics, &semester(), MAP_BY_ROOM);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].source, "holiday");
+        assert_eq!(classes.len(), 3);
+
+        let tuesday = &classes[0];
+        assert_eq!(tuesday.course_name, "Math, advanced");
+        assert_eq!(tuesday.teacher.as_deref(), Some("Li"));
+        assert_eq!(tuesday.client_key.as_deref(), Some("A101"));
+        assert_eq!(
+            (tuesday.day_of_week, tuesday.start_time.as_str()),
+            (2, "08:00")
+        );
+        assert_eq!(tuesday.end_time, "08:45");
+        assert_eq!(tuesday.weeks, Some(vec![1, 4]));
+
+        let thursday = &classes[1];
+        assert_eq!(thursday.day_of_week, 4);
+        assert_eq!(thursday.weeks, None);
+
+        let moved = &classes[2];
+        assert_eq!((moved.day_of_week, moved.start_time.as_str()), (2, "14:00"));
+        assert_eq!(moved.weeks, Some(vec![3]));
+    }
+
+    #[test]
+    fn test_expand_weekly_interval_and_count() {
+        let dates = expand_weekly(
+            "FREQ=WEEKLY;INTERVAL=2;COUNT=3",
+            date("2024-09-02"),
+            date("2025-01-12"),
+        )
+        .unwrap();
+        assert_eq!(
+            dates,
+            vec![date("2024-09-02"), date("2024-09-16"), date("2024-09-30")]
+        );
+        assert!(expand_weekly("FREQ=DAILY", date("2024-09-02"), date("2025-01-12")).is_err());
+    }
+
+    #[test]
+    fn test_expand_weekly_rejects_huge_interval() {
+        for interval in ["53", "100000000", "9223372036854775807"] {
+            let rule = format!("FREQ=WEEKLY;INTERVAL={}", interval);
+            assert_eq!(
+                expand_weekly(&rule, date("2024-09-02"), date("2025-01-12")),
+                Err("INTERVAL must be between 1 and 52".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let csv = "班级,课程,教师,教室,星期,开始,结束,周次\n\
+            Class 1,\"Math, advanced\",Li,A101,周二,8:00,08:45,1-8\n\
+            Class 1,\"Math, advanced\",Li,A101,Tue,8:00,08:45,10\n\
+            Class 2,Art,,B2,Funday,10:00,10:45,\n";
+
+        let (classes, issues) = parse_csv(csv, MAP_BY_CLIENT_NAME);
+        assert_eq!(classes.len(), 1);
+        assert_eq!(classes[0].client_key.as_deref(), Some("Class 1"));
+        assert_eq!(classes[0].start_time, "08:00");
+        assert_eq!(classes[0].weeks, Some(vec![1, 2, 3, 4, 5, 6, 7, 8, 10]));
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].source, "line 4");
+
+        let (_, issues) = parse_csv("course,teacher\nMath,Li\n", MAP_BY_ROOM);
+        assert!(issues[0].reason.contains("day, start, end"));
+    }
+
+    #[test]
+    fn test_to_sync_payload_reuses_existing_ids() {
+        let existing_course = ClientCourse {
+            id: 4,
+            name: "Math".to_string(),
+            teacher: Some("Li".to_string()),
+            location: Some("A101".to_string()),
+            color: Some("#ff0000".to_string()),
+            note: None,
+            attributes: Default::default(),
+        };
+        let existing_entry = ClientScheduleEntry {
+            id: 9,
+            course_id: 4,
+            day_of_week: 2,
+            start_time: "08:00".to_string(),
+            end_time: "08:45".to_string(),
+            weeks: None,
+            invalid_weeks: None,
+            note: None,
+            room: None,
+            attributes: Default::default(),
+        };
+        let class = |name: &str, day: i32| ImportedClass {
+            source: "line 2".to_string(),
+            client_key: None,
+            course_name: name.to_string(),
+            teacher: Some("Li".to_string()),
+            location: Some("A101".to_string()),
+            day_of_week: day,
+            start_time: "08:00".to_string(),
+            end_time: "08:50".to_string(),
+            weeks: Some(vec![1, 2]),
+            note: None,
+        };
+
+        let payload = to_sync_payload(
+            &[class("Math", 2), class("Art", 3)],
+            &[existing_course],
+            &[existing_entry],
+        );
+        let (courses, entries) = (&payload.courses, &payload.entries);
+        assert_eq!(
+            courses.iter().map(|c| c.id).collect::<Vec<_>>(),
+            vec![4, -1]
+        );
+        assert_eq!(courses[0].color.as_deref(), Some("#ff0000"));
+        assert_eq!(entries[0].id, 9);
+        assert_eq!(entries[0].end_time, "08:50");
+        assert_eq!(entries[0].room, None);
+        assert_eq!((entries[1].id, entries[1].course_id), (-1, -1));
+
+        let rejected = payload.issue(&SyncRejection {
+            item_type: "schedule_entry".to_string(),
+            id: -1,
+            field: "start_time".to_string(),
+            reason: "overlaps with schedule entry 9".to_string(),
+        });
+        assert_eq!(rejected.source, "line 2");
+        assert_eq!(
+            rejected.reason,
+            "start_time: overlaps with schedule entry 9"
+        );
+    }
+}
@@ -1,9 +1,28 @@
 // Library exports for testing and future use
+pub mod announcements;
 pub mod auth;
+pub mod bells;
+pub mod calendar;
+pub mod codec;
 pub mod config;
+pub mod conflicts;
 pub mod db;
+pub mod emergency;
 pub mod error;
+pub mod events;
 pub mod handlers;
+pub mod ical;
+pub mod import;
 pub mod models;
+pub mod printing;
+pub mod rooms;
 pub mod routes;
+pub mod semester;
+pub mod settings;
+pub mod spreadsheet;
+pub mod sync;
+pub mod templates;
+pub mod timetable;
+pub mod validation;
 pub mod websocket;
+pub mod weeks;
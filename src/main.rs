@@ -1,10 +1,13 @@
-use classtop_management_server::{config, db, routes, websocket};
+use classtop_management_server::{
+    announcements, config, db, emergency, handlers, routes, sync, websocket,
+};
 
 use actix_cors::Cors;
 use actix_files::Files;
 use actix_governor::{Governor, GovernorConfigBuilder};
 use actix_web::{http::header, middleware, web, App, HttpServer};
-use tracing::info;
+use std::time::Duration;
+use tracing::{info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use utoipa::OpenApi;
 use utoipa_redoc::{Redoc, Servable};
@@ -50,6 +53,41 @@ async fn main() -> anyhow::Result<()> {
     // Initialize WebSocket connection manager
     let ws_manager = actix_web::web::Data::new(websocket::WSConnectionManager::new());
 
+    // Deliver announcements as they become active, and to clients that reconnect
+    {
+        let repo = db::repository::Repository::new(db_pool.clone());
+        let ws_manager = ws_manager.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(announcements::DELIVERY_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = handlers::deliver_announcements(&repo, &ws_manager).await {
+                    warn!("Announcement delivery failed: {}", e);
+                }
+            }
+        });
+    }
+
+    // Repeat emergency alerts until every target display acknowledges
+    {
+        let repo = db::repository::Repository::new(db_pool.clone());
+        let ws_manager = ws_manager.clone();
+        actix_web::rt::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(emergency::REPEAT_INTERVAL_SECS));
+            loop {
+                interval.tick().await;
+                if let Err(e) = handlers::repeat_emergency_alerts(&repo, &ws_manager).await {
+                    warn!("Emergency alert repeat failed: {}", e);
+                }
+            }
+        });
+    }
+
+    // Serialize concurrent syncs of the same client
+    let sync_locks = web::Data::new(sync::SyncLockManager::new());
+
     // Start HTTP server
     HttpServer::new(move || {
         // Configure CORS
@@ -61,6 +99,7 @@ async fn main() -> anyhow::Result<()> {
                     header::AUTHORIZATION,
                     header::ACCEPT,
                     header::CONTENT_TYPE,
+                    header::HeaderName::from_static("idempotency-key"),
                 ])
                 .max_age(3600);
 
@@ -87,6 +126,7 @@ async fn main() -> anyhow::Result<()> {
             .app_data(web::Data::new(db_pool.clone()))
             .app_data(config_data.clone())
             .app_data(ws_manager.clone())
+            .app_data(sync_locks.clone())
             .wrap(cors)
             .wrap(Governor::new(&governor_conf))
             .wrap(tracing_actix_web::TracingLogger::default())
@@ -1,7 +1,18 @@
-use chrono::NaiveDateTime;
-use serde::{Deserialize, Serialize};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::ToSchema;
 
+/// 修改请求中可清空的字段：省略为 `None`（不变），`null` 为 `Some(None)`（清空）
+///
+/// 用于 `#[serde(default, deserialize_with = "crate::models::nullable")]`。
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
 // Response wrapper
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ApiResponse<T> {
@@ -34,6 +45,8 @@ pub struct Client {
     pub status: String,  // online, offline, error
     #[schema(value_type = String, example = "2024-01-01T00:00:00")]
     pub created_at: NaiveDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_id: Option<i32>, // 客户端所在的教室
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -64,6 +77,9 @@ pub struct MessageResponse {
     pub message: String,
 }
 
+// 扩展属性（学分、课程代码、班级等），以 JSONB 存储
+pub type Attributes = serde_json::Map<String, serde_json::Value>;
+
 // Course model (从客户端同步的课程数据)
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Course {
@@ -79,9 +95,13 @@ pub struct Course {
     pub color: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    #[schema(value_type = Object)]
+    pub attributes: Attributes,
+    pub origin: String, // client（客户端同步）或 server（管理端维护）
 }
 
-#[allow(dead_code)]
+// 管理端创建课程
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateCourse {
     pub name: String,
@@ -91,19 +111,33 @@ pub struct CreateCourse {
     pub location: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub attributes: Attributes,
 }
 
-#[allow(dead_code)]
+// 管理端修改课程，省略的字段保持不变，teacher 等可选字段传 null 时清空
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateCourse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub teacher: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub location: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub note: Option<Option<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub teacher: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub location: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub color: Option<String>,
+    #[schema(value_type = Option<Object>)]
+    pub attributes: Option<Attributes>,
 }
 
 // Schedule Entry model (从客户端同步的课程表数据)
@@ -126,21 +160,238 @@ pub struct ScheduleEntry {
     pub end_time: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub weeks: Option<Vec<i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weeks_text: Option<String>, // 周次的可读形式，如 "1-8,10,12-16"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weeks_legacy: Option<String>, // 迁移时无法解析的原始周次文本，修正前按周查看时不显示
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>, // 条目教室，覆盖课程地点
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    #[schema(value_type = Object)]
+    pub attributes: Attributes,
+    pub origin: String, // client（客户端同步）或 server（管理端维护）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_id: Option<i32>, // 按名称匹配的教室，未填写地点时为客户端所在教室
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period_start: Option<i32>, // 按节次安排时的起始节次，时间由作息时间表解析
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period_end: Option<i32>,
 }
 
-#[allow(dead_code)]
+impl ScheduleEntry {
+    /// 是否在某教学周上课；周次无法解析的条目不算
+    pub fn held_in_week(&self, week: i32) -> bool {
+        match &self.weeks {
+            Some(weeks) => weeks.contains(&week),
+            None => self.weeks_legacy.is_none(),
+        }
+    }
+}
+
+// 管理端创建课程表条目
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateScheduleEntry {
-    pub course_id: i32,
+    pub course_id: i32, // 管理服务器上的课程 ID，须属于同一客户端
     pub day_of_week: i32,
-    pub start_time: String,
-    pub end_time: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>, // 与节次二选一
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_start: Option<i32>, // 按默认作息时间表解析起止时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_end: Option<i32>, // 省略时与起始节次相同
+    /// Week numbers, or a week expression such as "1-8,10,12-16" or "1-15单"
+    #[serde(
+        default,
+        deserialize_with = "crate::weeks::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub weeks: Option<Vec<i32>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Object)]
+    pub attributes: Attributes,
+}
+
+// 管理端修改课程表条目，省略的字段保持不变；weeks、note、room 传 null 时清空
+// 给出节次时按节次重新解析时间；只给出时间时不再按节次安排
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateScheduleEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub course_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_week: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_start: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_end: Option<i32>,
+    /// Week numbers, or a week expression such as "1-8,10,12-16" or "1-15单"; null means every week
+    #[serde(default, deserialize_with = "crate::weeks::deserialize_update")]
+    #[schema(value_type = Option<Vec<i32>>, nullable)]
+    pub weeks: Option<Option<Vec<i32>>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub note: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    #[schema(value_type = Option<String>, nullable)]
+    pub room: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub attributes: Option<Attributes>,
+}
+
+// Schedule filter (按客户端、周次、星期筛选课程表)
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ScheduleFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>, // 只返回该周上课的条目
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_week: Option<i32>,
+}
+
+// Room (教室登记)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Room {
+    pub id: i32,
+    pub name: String, // 与课程地点、条目教室中填写的名称一致
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub building: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    #[serde(default)]
+    pub equipment: Vec<String>, // 投影仪、电子白板等
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateRoom {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub building: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    #[serde(default)]
+    pub equipment: Vec<String>,
+}
+
+// 省略的字段保持不变
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRoom {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub building: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub equipment: Option<Vec<String>>,
+}
+
+// 设置客户端所在教室，room_id 为 null 时解除关联
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignRoom {
+    pub room_id: Option<i32>,
+}
+
+// 教室占用查询，省略周次时使用当前学期的当前周
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct OccupancyQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_week: Option<i32>, // 只返回该天，省略时返回整周
+}
+
+// 教室在某周（或某天）的占用情况
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoomOccupancy {
+    pub room: Room,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>, // None 表示没有当前学期，按所有周次统计
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_week: Option<i32>,
+    pub entries: Vec<ScheduleEntry>, // 按星期与开始时间排序
+}
+
+// 空闲教室查询
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FreeRoomQuery {
+    pub day_of_week: i32,
+    pub start_time: String, // HH:MM
+    pub end_time: String,   // HH:MM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>, // 省略时使用当前周，没有当前学期时要求所有周都空闲
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_capacity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub building: Option<String>,
+}
+
+// Bell schedule (作息时间表，如常规日、短课日)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct BellSchedule {
+    pub id: i32,
+    pub name: String,
+    pub is_default: bool, // 按节次安排的条目按默认作息时间表解析时间
+    pub periods: Vec<BellPeriod>,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub updated_at: NaiveDateTime,
+}
+
+// 新建或修改作息时间表的结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BellScheduleChange {
+    #[serde(flatten)]
+    pub schedule: BellSchedule,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub not_retimed: Vec<SyncRejection>, // 未能按默认作息时间表调整、保持原时间的条目（服务器条目 ID）
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct BellPeriod {
+    pub period: i32,        // 节次，从 1 开始
+    pub start_time: String, // HH:MM
+    pub end_time: String,   // HH:MM
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateBellSchedule {
+    pub name: String,
+    #[serde(default)]
+    pub is_default: bool,
+    pub periods: Vec<BellPeriod>,
+}
+
+// 省略的字段保持不变；默认作息时间表改动后按节次安排的条目随之调整
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateBellSchedule {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_default: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub periods: Option<Vec<BellPeriod>>,
 }
 
 // Settings model
@@ -155,6 +406,21 @@ pub struct UpdateSetting {
     pub value: String,
 }
 
+// 设置项的类型、默认值与取值范围，供管理后台渲染表单
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SettingSchema {
+    pub key: String,
+    pub value_type: String, // integer 或 string
+    pub default: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>, // 仅 string
+    pub description: String,
+}
+
 // Health check
 #[derive(Debug, Serialize, ToSchema)]
 pub struct HealthResponse {
@@ -163,28 +429,256 @@ pub struct HealthResponse {
     pub version: String,
 }
 
-// Week info
-#[allow(dead_code)]
-#[derive(Debug, Serialize, ToSchema)]
+// Semester (学期)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Semester {
+    pub id: i32,
+    pub name: String,
+    #[schema(value_type = String, example = "2024-09-02")]
+    pub start_date: NaiveDate,
+    #[schema(value_type = String, example = "2025-01-12")]
+    pub end_date: NaiveDate,
+    pub week_count: i32,
+    pub is_active: bool, // 是否为当前学期
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateSemester {
+    pub name: String,
+    #[schema(value_type = String, example = "2024-09-02")]
+    pub start_date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2025-01-12")]
+    pub end_date: Option<NaiveDate>, // 与 week_count 至少提供一项
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_count: Option<i32>,
+    #[serde(default)]
+    pub is_active: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSemester {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-09-02")]
+    pub start_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2025-01-12")]
+    pub end_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_count: Option<i32>,
+}
+
+// Week info (某一天在学期中的周次)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct WeekInfo {
     pub week: i32,
+    pub date: String,
+    pub in_semester: bool, // 日期是否在学期范围内
+    pub semester_id: i32,
+    pub semester_name: String,
     pub semester_start_date: String,
-    pub is_calculated: bool,
+    pub week_count: i32,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct SetSemesterStart {
-    pub date: String,
+    #[schema(value_type = String, example = "2024-09-02")]
+    pub date: NaiveDate,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Serialize, ToSchema)]
 pub struct SemesterStartResponse {
     pub semester_start_date: String,
     pub calculated_week: i32,
 }
 
+// Schedule exception (节假日、调课、停课等课表例外)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ScheduleException {
+    pub id: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<i32>, // 为空表示适用于所有客户端
+    #[schema(value_type = String, example = "2024-10-07")]
+    pub date: NaiveDate,
+    pub kind: String, // cancel_day, remap_day, cancel_entry, relocate_entry, bell_schedule
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follows_day_of_week: Option<i32>, // remap_day：当天按该星期的课表上课
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bell_schedule_id: Option<i32>, // bell_schedule：当天采用的作息时间表
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bell_periods: Option<Vec<BellPeriod>>, // 该作息时间表的节次
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_id: Option<i32>, // 课表条目的服务器 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-10-08")]
+    pub new_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_start_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_end_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_room: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateScheduleException {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<i32>, // 条目类例外取条目所属客户端
+    #[schema(value_type = String, example = "2024-10-07")]
+    pub date: NaiveDate,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follows_day_of_week: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bell_schedule_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-10-08")]
+    pub new_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_start_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_end_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_room: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ExceptionFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<i32>, // 包含适用于所有客户端的例外
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-10-01")]
+    pub from: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-10-31")]
+    pub to: Option<NaiveDate>,
+}
+
+// Effective schedule (应用例外后的某日课表)
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct EffectiveEntry {
+    pub entry: ScheduleEntry, // 已应用调课后的时间与教室
+    pub status: String,       // scheduled, cancelled, relocated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exception_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relocated_to: Option<String>, // 调到其他日期时的新日期
+}
+
+impl EffectiveEntry {
+    pub fn new(entry: ScheduleEntry, status: &str, exception: Option<&ScheduleException>) -> Self {
+        Self {
+            entry,
+            status: status.to_string(),
+            exception_id: exception.map(|e| e.id),
+            reason: exception.and_then(|e| e.reason.clone()),
+            relocated_to: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DaySchedule {
+    pub client_id: i32,
+    pub date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>, // 当前学期的教学周，无当前学期时为空
+    pub in_semester: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_week: Option<i32>, // 实际采用的星期，全天停课时为空
+    pub exceptions: Vec<ScheduleException>,
+    pub entries: Vec<EffectiveEntry>,
+}
+
+// Now and next (某一时刻正在上的课与下一节课)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct NowNext {
+    pub client_id: i32,
+    pub client_name: String,
+    pub at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<EffectiveEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<EffectiveEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minutes_until_next: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InstantQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-10-28T08:30:00")]
+    pub at: Option<NaiveDateTime>, // 缺省为当前时间
+}
+
+// Calendar feeds
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CalendarFeed {
+    pub id: i32,
+    pub token: String,
+    pub scope: String,  // client, teacher, location
+    pub target: String, // 客户端 ID、教师姓名或教室
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub url: String, // 订阅地址，无需登录
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T00:00:00")]
+    pub last_accessed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCalendarFeed {
+    pub scope: String,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>, // 日历名称，缺省按范围生成
+}
+
+// Printable timetable export (可打印的周课表)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PrintQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>, // pdf（默认）或 html
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week: Option<i32>, // 只打印该周上课的课程，省略时打印整个学期并注明周次
+}
+
+// Spreadsheet export (XLSX 导出)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SpreadsheetExportQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ids: Option<String>, // 逗号分隔的客户端 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_id: Option<i32>, // 关联到该模板的客户端；两者均省略时导出全部客户端
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DateQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-10-28")]
+    pub date: Option<NaiveDate>, // 缺省为今天
+}
+
 // Statistics
 #[derive(Debug, Serialize, ToSchema)]
 pub struct Statistics {
@@ -205,46 +699,717 @@ pub struct ClientStatistics {
 }
 
 // Sync Request (客户端主动同步数据到服务器)
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 pub struct SyncRequest {
     pub client_uuid: String,
     pub courses: Vec<ClientCourse>,
     pub schedule_entries: Vec<ClientScheduleEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>, // 幂等键，Idempotency-Key 请求头优先
 }
 
-#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
 pub struct ClientCourse {
     pub id: i32, // 客户端上的 ID
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub teacher: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub note: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    #[schema(value_type = Object)]
+    pub attributes: Attributes,
 }
 
-#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, ToSchema)]
+#[serde(from = "ClientScheduleEntryInput")]
 pub struct ClientScheduleEntry {
     pub id: i32,        // 客户端上的 ID
     pub course_id: i32, // 客户端上的课程 ID
     pub day_of_week: i32,
     pub start_time: String,
     pub end_time: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Week numbers, or a week expression such as "1-8,10,12-16" or "1-15单"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub weeks: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(ignore)]
+    pub invalid_weeks: Option<String>, // 无法解析的周次表达式原文，校验时拒绝该条目；仍计入幂等请求内容
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    #[schema(value_type = Object)]
+    pub attributes: Attributes,
 }
 
-// Sync Response
-#[derive(Debug, Serialize, ToSchema)]
-pub struct SyncResponse {
-    pub success: bool,
-    pub message: String,
-    pub synced_courses: i32,
-    pub synced_entries: i32,
+// 反序列化时的课表条目：周次表达式有误时保留原文，只拒绝该条目而不是整个请求
+#[derive(Deserialize)]
+struct ClientScheduleEntryInput {
+    id: i32,
+    course_id: i32,
+    day_of_week: i32,
+    start_time: String,
+    end_time: String,
+    #[serde(default)]
+    weeks: Option<crate::weeks::RawWeeks>,
+    note: Option<String>,
+    room: Option<String>,
+    #[serde(default)]
+    attributes: Attributes,
 }
 
-// Logs
+impl From<ClientScheduleEntryInput> for ClientScheduleEntry {
+    fn from(input: ClientScheduleEntryInput) -> Self {
+        let (weeks, invalid_weeks) = match input.weeks {
+            Some(raw) => match raw.parse() {
+                Ok(weeks) => (Some(weeks), None),
+                Err(expr) => (None, Some(expr)),
+            },
+            None => (None, None),
+        };
+        Self {
+            id: input.id,
+            course_id: input.course_id,
+            day_of_week: input.day_of_week,
+            start_time: input.start_time,
+            end_time: input.end_time,
+            weeks,
+            invalid_weeks,
+            note: input.note,
+            room: input.room,
+            attributes: input.attributes,
+        }
+    }
+}
+
+// Sync Response
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SyncResponse {
+    pub success: bool,
+    pub message: String,
+    pub synced_courses: i32,
+    pub synced_entries: i32,
+    #[serde(default)]
+    pub rejected: Vec<SyncRejection>, // 未通过校验而被拒绝的条目
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kept_server_version: Vec<KeptServerItem>, // 由管理端维护、保留服务器版本的条目，不影响同步状态
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub semester: Option<WeekInfo>, // 当前学期与周次，保证各客户端显示一致
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<ScheduleConflict>, // 同步后涉及该客户端的教师与教室冲突，仅作提醒
+}
+
+// 教师或教室的时间冲突，可跨客户端
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ScheduleConflict {
+    pub kind: String,     // teacher, location
+    pub resource: String, // 教师姓名或教室
+    pub day_of_week: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weeks: Option<Vec<i32>>, // 两个条目共同上课的周次，None 表示每周
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weeks_text: Option<String>,
+    pub entries: Vec<ScheduleEntry>, // 冲突的两个条目
+}
+
+// Conflict filter
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ConflictFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<i32>, // 只返回涉及该客户端的冲突
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>, // teacher 或 location
+}
+
+// 同步时被拒绝的条目及原因
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SyncRejection {
+    pub item_type: String, // course, schedule_entry
+    pub id: i32,           // 客户端上的 ID
+    pub field: String,
+    pub reason: String,
+}
+
+// 由管理端维护或已删除的条目，客户端同步不会覆盖，客户端应改用服务器版本
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct KeptServerItem {
+    pub item_type: String, // course, schedule_entry
+    pub id: i32,           // 客户端上的 ID
+}
+
+// Timetable snapshots (每次同步后的课程表版本)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimetableSnapshot {
+    pub id: i32,
+    pub client_id: i32,
+    pub version: i32,
+    pub source: String, // sync, rollback, import, admin, template
+    pub courses: Vec<ClientCourse>,
+    pub schedule_entries: Vec<ClientScheduleEntry>,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimetableSnapshotSummary {
+    pub id: i32,
+    pub client_id: i32,
+    pub version: i32,
+    pub source: String,
+    pub courses_count: i32,
+    pub entries_count: i32,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SnapshotDiffParams {
+    pub from: i32, // 起始版本
+    pub to: i32,   // 目标版本
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CourseChange {
+    pub before: ClientCourse,
+    pub after: ClientCourse,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduleEntryChange {
+    pub before: ClientScheduleEntry,
+    pub after: ClientScheduleEntry,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct CourseDiff {
+    pub added: Vec<ClientCourse>,
+    pub removed: Vec<ClientCourse>,
+    pub changed: Vec<CourseChange>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ScheduleEntryDiff {
+    pub added: Vec<ClientScheduleEntry>,
+    pub removed: Vec<ClientScheduleEntry>,
+    pub changed: Vec<ScheduleEntryChange>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimetableDiff {
+    pub from_version: i32,
+    pub to_version: i32,
+    pub courses: CourseDiff,
+    pub schedule_entries: ScheduleEntryDiff,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RollbackResponse {
+    pub restored_version: i32,
+    pub snapshot: TimetableSnapshotSummary, // 回滚后新建的快照
+    pub pushed: bool,                       // 是否已通过 WebSocket 推送给客户端
+}
+
+// Sync preview (同步预演，不写入数据库)
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct CourseSyncPreview {
+    pub inserted: Vec<ClientCourse>,
+    pub updated: Vec<CourseChange>,
+    pub unchanged: Vec<i32>,        // 内容未变化的课程客户端 ID
+    pub deleted: Vec<ClientCourse>, // 同步只做 upsert，目前始终为空
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct ScheduleEntrySyncPreview {
+    pub inserted: Vec<ClientScheduleEntry>,
+    pub updated: Vec<ScheduleEntryChange>,
+    pub unchanged: Vec<i32>,
+    pub deleted: Vec<ClientScheduleEntry>,
+    pub skipped: Vec<ClientScheduleEntry>, // 找不到所属课程
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncPreview {
+    pub client_id: i32,
+    pub courses: CourseSyncPreview,
+    pub schedule_entries: ScheduleEntrySyncPreview,
+    pub invalid: Vec<SyncRejection>,              // 校验未通过的条目
+    pub kept_server_version: Vec<KeptServerItem>, // 保留服务器版本、不会被覆盖的条目
+}
+
+// Timetable import (从教务系统导出的 .ics / CSV 导入课程表)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ImportRequest {
+    pub format: String, // ics, csv
+    pub content: String,
+    #[serde(default = "default_import_map_by")]
+    pub map_by: String, // room, client_name
+    #[serde(default)]
+    pub mapping: std::collections::HashMap<String, i32>, // 显式指定教室或名称对应的客户端 ID
+}
+
+fn default_import_map_by() -> String {
+    crate::import::MAP_BY_ROOM.to_string()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportIssue {
+    pub source: String, // 行号或事件 UID
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportClientResult {
+    pub client_id: i32,
+    pub client_name: String,
+    pub keys: Vec<String>, // 映射到该客户端的教室或名称
+    pub classes: usize,
+    pub preview: SyncPreview,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_version: Option<i32>, // 导入后生成的快照版本，未变化时为空
+    pub pushed: bool, // 是否已推送到在线的客户端
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub parsed: usize, // 解析出的课程条目数（合并周次后）
+    pub clients: Vec<ImportClientResult>,
+    pub unmapped: Vec<ImportIssue>, // 未能对应到客户端的条目
+    pub errors: Vec<ImportIssue>,   // 无法解析或未通过同步校验的行、事件
+}
+
+// Timetable templates (可应用到多个客户端的课表模板)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TimetableTemplate {
+    pub id: i32,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub courses: Vec<ClientCourse>, // ID 仅在模板内有效
+    pub schedule_entries: Vec<ClientScheduleEntry>,
+    pub clients: Vec<TemplateClient>,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateTemplate {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub courses: Vec<ClientCourse>,
+    #[serde(default)]
+    pub schedule_entries: Vec<ClientScheduleEntry>,
+}
+
+// 省略的字段保持不变，courses 与 schedule_entries 整体替换
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateTemplate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub courses: Option<Vec<ClientCourse>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_entries: Option<Vec<ClientScheduleEntry>>,
+}
+
+// 使用模板的客户端
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TemplateClient {
+    pub client_id: i32,
+    pub overrides: TemplateOverrides,
+    #[schema(value_type = Option<String>, example = "2024-01-01T00:00:00")]
+    pub published_at: Option<NaiveDateTime>, // 最近一次发布到该客户端的时间
+}
+
+// 客户端对模板的覆盖，按模板内 ID 指定
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct TemplateOverrides {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub courses: Vec<CourseOverride>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule_entries: Vec<EntryOverride>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_entries: Vec<i32>, // 该客户端不上的模板条目
+}
+
+// 省略的字段沿用模板
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct CourseOverride {
+    pub id: i32, // 模板内的课程 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub teacher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct EntryOverride {
+    pub id: i32, // 模板内的条目 ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day_of_week: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<String>,
+    /// Week numbers, or a week expression such as "1-8,10,12-16" or "1-15单"
+    #[serde(
+        default,
+        deserialize_with = "crate::weeks::deserialize",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub weeks: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+// 模板发布到单个客户端的结果
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TemplateClientResult {
+    pub client_id: i32,
+    pub client_name: String,
+    pub courses: CourseDiff, // 发布前后该客户端课表的差异
+    pub schedule_entries: ScheduleEntryDiff,
+    pub invalid: Vec<SyncRejection>, // 未通过校验、不会写入的模板条目（ID 为客户端上的 ID）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_version: Option<i32>, // 发布后生成的快照版本，未变化或预演时为空
+    pub pushed: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TemplatePublishReport {
+    pub dry_run: bool,
+    pub template_id: i32,
+    pub clients: Vec<TemplateClientResult>,
+}
+
+// Course search (按地点或扩展属性查询课程)
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CourseSearchParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>, // 属性名，如 credits
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>, // 属性值，可为 JSON 字面量（如 3、true）或字符串
+}
+
+// Sync history (同步日志)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncLog {
+    pub id: i32,
+    pub client_id: i32,
+    pub client_name: String,
+    pub sync_type: String, // full, incremental
+    pub status: String,    // success, failed, partial
+    pub courses_count: i32,
+    pub entries_count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload_bytes: Option<i32>,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SyncLogFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+// Sync health summary (同步健康度)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SyncHealth {
+    pub client_id: i32,
+    pub client_name: String,
+    #[schema(value_type = Option<String>, example = "2024-01-01T00:00:00")]
+    pub last_success: Option<NaiveDateTime>,
+    #[schema(value_type = Option<String>, example = "2024-01-01T00:00:00")]
+    pub last_failure: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub consecutive_failures: i64, // 自上次成功以来的连续失败次数
+    pub total_syncs: i64,
+    pub average_payload_bytes: Option<f64>,
+}
+
+// Client groups (客户端分组，如年级或楼栋)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ClientGroup {
+    pub id: i32,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub client_ids: Vec<i32>,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateClientGroup {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub client_ids: Vec<i32>,
+}
+
+// 省略的字段保持不变，client_ids 整体替换
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateClientGroup {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ids: Option<Vec<i32>>,
+}
+
+// Announcements (推送到教室显示屏的公告)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Announcement {
+    pub id: i32,
+    pub title: String,
+    pub body: String,
+    pub priority: String, // low, normal 或 high
+    #[schema(value_type = String, example = "2024-01-01T08:00:00")]
+    pub starts_at: NaiveDateTime, // 学校当地时间
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T18:00:00")]
+    pub ends_at: Option<NaiveDateTime>, // 省略时一直显示到删除
+    pub client_ids: Vec<i32>,
+    pub group_ids: Vec<i32>, // 与 client_ids 均为空时投放到所有客户端
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateAnnouncement {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>, // 默认 normal
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T08:00:00")]
+    pub starts_at: Option<NaiveDateTime>, // 默认立即开始
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T18:00:00")]
+    pub ends_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub client_ids: Vec<i32>,
+    #[serde(default)]
+    pub group_ids: Vec<i32>,
+}
+
+// 省略的字段保持不变；修改后公告重新投放
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateAnnouncement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T08:00:00")]
+    pub starts_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T18:00:00")]
+    pub ends_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ids: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_ids: Option<Vec<i32>>,
+}
+
+// 公告在一个目标客户端上的送达与确认情况
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AnnouncementDelivery {
+    pub client_id: i32,
+    pub client_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T08:00:05")]
+    pub delivered_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T08:00:07")]
+    pub acknowledged_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ClientUuidQuery {
+    pub client_uuid: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AcknowledgeAnnouncement {
+    pub client_uuid: String,
+}
+
+// Emergency alerts (接管所有显示屏的紧急广播)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct EmergencyAlert {
+    pub id: i32,
+    pub kind: String, // fire, lockdown, evacuation, shelter 或 other
+    pub message: String,
+    pub drill: bool, // 演练
+    pub client_ids: Vec<i32>,
+    pub group_ids: Vec<i32>, // 与 client_ids 均为空时发往所有客户端
+    #[schema(value_type = String, example = "2024-01-01T10:00:00")]
+    pub started_at: NaiveDateTime,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T10:20:00")]
+    pub cleared_at: Option<NaiveDateTime>, // 解除警报的时间
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateEmergencyAlert {
+    pub kind: String,
+    pub message: String,
+    #[serde(default)]
+    pub drill: bool,
+    #[serde(default)]
+    pub client_ids: Vec<i32>,
+    #[serde(default)]
+    pub group_ids: Vec<i32>,
+}
+
+// 未使用 WebSocket 的客户端通过 HTTP 确认
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AcknowledgeEmergency {
+    pub client_uuid: String,
+}
+
+// 警报在一个目标客户端上的状态
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct EmergencyClientStatus {
+    pub client_id: i32,
+    pub client_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>, // 显示屏所在教室
+    pub connected: bool,
+    pub attempts: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T10:00:05")]
+    pub last_sent_at: Option<NaiveDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-01-01T10:00:06")]
+    pub acknowledged_at: Option<NaiveDateTime>,
+}
+
+// 确认情况看板：未确认的客户端排在前面
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EmergencyStatus {
+    pub alert: EmergencyAlert,
+    pub total: usize,
+    pub acknowledged: usize,
+    pub unconfirmed_rooms: Vec<String>, // 尚未确认的教室，未登记教室时为客户端名称
+    pub clients: Vec<EmergencyClientStatus>,
+}
+
+// Display events (显示屏上的倒计时)
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DisplayEvent {
+    pub id: i32,
+    pub label: String,
+    #[schema(value_type = String, example = "2024-06-07")]
+    pub target_date: NaiveDate,
+    pub style: String, // default, exam, sport, holiday 或 celebration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-05-01")]
+    pub show_from: Option<NaiveDate>, // 省略时立即显示
+    pub client_ids: Vec<i32>,
+    pub group_ids: Vec<i32>, // 与 client_ids 均为空时显示在所有客户端
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub created_at: NaiveDateTime,
+    #[schema(value_type = String, example = "2024-01-01T00:00:00")]
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateDisplayEvent {
+    pub label: String,
+    #[schema(value_type = String, example = "2024-06-07")]
+    pub target_date: NaiveDate,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>, // 默认 default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-05-01")]
+    pub show_from: Option<NaiveDate>,
+    #[serde(default)]
+    pub client_ids: Vec<i32>,
+    #[serde(default)]
+    pub group_ids: Vec<i32>,
+}
+
+// 省略的字段保持不变
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateDisplayEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-06-07")]
+    pub target_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, example = "2024-05-01")]
+    pub show_from: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_ids: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_ids: Option<Vec<i32>>,
+}
+
+// 显示屏当天要渲染的倒计时
+#[derive(Debug, Serialize, Clone, ToSchema)]
+pub struct Countdown {
+    pub event_id: i32,
+    pub label: String,
+    pub style: String,
+    #[schema(value_type = String, example = "2024-06-07")]
+    pub target_date: NaiveDate,
+    pub days_remaining: i64, // 当天为 0
+}
+
+// 显示屏在课表旁渲染的全部内容
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DisplayContent {
+    pub client_id: i32,
+    pub client_name: String,
+    #[schema(value_type = String, example = "2024-06-01")]
+    pub date: NaiveDate,
+    pub now_next: NowNext,
+    pub countdowns: Vec<Countdown>,
+    pub announcements: Vec<Announcement>,
+    pub emergency_alerts: Vec<EmergencyAlert>, // 未解除的警报，应覆盖其他内容
+}
+
+// Logs
 #[allow(dead_code)]
 #[derive(Debug, Serialize, ToSchema)]
 pub struct LogsResponse {
@@ -259,7 +1424,6 @@ pub struct RootResponse {
     pub docs: String,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct ResetSettingsRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -0,0 +1,608 @@
+use crate::ical::{SCOPE_LOCATION, SCOPE_TEACHER};
+use crate::models::ScheduleEntry;
+use crate::validation::is_hex_color;
+use std::collections::BTreeSet;
+use std::io::Write;
+
+/// 导出格式
+pub const FORMAT_PDF: &str = "pdf";
+pub const FORMAT_HTML: &str = "html";
+
+pub const PRINT_FORMATS: &[&str] = &[FORMAT_PDF, FORMAT_HTML];
+
+pub const DAY_NAMES: [&str; 7] = [
+    "星期一",
+    "星期二",
+    "星期三",
+    "星期四",
+    "星期五",
+    "星期六",
+    "星期日",
+];
+
+/// 可打印的周课表：行为时间段，列为星期
+#[derive(Debug, Clone)]
+pub struct TimetableGrid {
+    pub title: String,
+    pub subtitle: String,
+    pub days: Vec<i32>, // 周一至周五，有周末的课时加上周六、周日
+    pub rows: Vec<GridRow>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GridRow {
+    pub label: Vec<String>,        // 节次与时间
+    pub cells: Vec<Vec<GridCell>>, // 与 `days` 一一对应，同一格可有多门课（如单双周）
+}
+
+#[derive(Debug, Clone)]
+pub struct GridCell {
+    pub lines: Vec<String>,
+    pub color: Option<(u8, u8, u8)>,
+}
+
+/// `#RGB` 或 `#RRGGBB` 颜色转为 RGB 分量
+pub fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    if !is_hex_color(value) {
+        return None;
+    }
+    let hex = &value[1..];
+    let channel = |i: usize, len: usize| {
+        let digits = &hex[i * len..(i + 1) * len];
+        let value = u8::from_str_radix(digits, 16).ok()?;
+        Some(if len == 1 { value * 17 } else { value })
+    };
+    let len = hex.len() / 3;
+    Some((channel(0, len)?, channel(1, len)?, channel(2, len)?))
+}
+
+/// 由课表条目生成周课表
+///
+/// `week` 为 `Some` 时只保留该周上课的条目，否则格内注明周次。
+/// 格内依次为课程名、教师与教室，按教师或教室导出时省略对应的一项；
+/// 底色取自课程颜色。
+pub fn build_grid(
+    title: &str,
+    subtitle: &str,
+    scope: &str,
+    week: Option<i32>,
+    entries: &[ScheduleEntry],
+) -> TimetableGrid {
+    let entries: Vec<&ScheduleEntry> = entries
+        .iter()
+        .filter(|e| (1..=7).contains(&e.day_of_week))
+        .filter(|e| week.is_none_or(|week| e.held_in_week(week)))
+        .collect();
+
+    let has_weekend = entries.iter().any(|e| e.day_of_week > 5);
+    let days: Vec<i32> = (1..=if has_weekend { 7 } else { 5 }).collect();
+    let slots: BTreeSet<(&str, &str)> = entries
+        .iter()
+        .map(|e| (e.start_time.as_str(), e.end_time.as_str()))
+        .collect();
+
+    let rows = slots
+        .into_iter()
+        .map(|(start, end)| {
+            let in_slot: Vec<&&ScheduleEntry> = entries
+                .iter()
+                .filter(|e| e.start_time == start && e.end_time == end)
+                .collect();
+            let periods: BTreeSet<(Option<i32>, Option<i32>)> = in_slot
+                .iter()
+                .map(|e| (e.period_start, e.period_end.or(e.period_start)))
+                .collect();
+            let mut label = Vec::new();
+            if let [(Some(first), Some(last))] = periods.into_iter().collect::<Vec<_>>()[..] {
+                label.push(if first == last {
+                    format!("第{}节", first)
+                } else {
+                    format!("第{}-{}节", first, last)
+                });
+            }
+            label.push(format!("{}-{}", start, end));
+
+            let cells = days
+                .iter()
+                .map(|day| {
+                    in_slot
+                        .iter()
+                        .filter(|e| e.day_of_week == *day)
+                        .map(|e| cell(e, scope, week.is_none()))
+                        .collect()
+                })
+                .collect();
+            GridRow { label, cells }
+        })
+        .collect();
+
+    TimetableGrid {
+        title: title.to_string(),
+        subtitle: subtitle.to_string(),
+        days,
+        rows,
+    }
+}
+
+fn cell(entry: &ScheduleEntry, scope: &str, show_weeks: bool) -> GridCell {
+    let room = entry.room.as_deref().or(entry.location.as_deref());
+    let lines = [
+        Some(entry.course_name.as_deref().unwrap_or("—")),
+        entry.teacher.as_deref().filter(|_| scope != SCOPE_TEACHER),
+        room.filter(|_| scope != SCOPE_LOCATION),
+        entry.weeks_text.as_deref().filter(|_| show_weeks),
+    ];
+    GridCell {
+        lines: lines
+            .into_iter()
+            .flatten()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect(),
+        color: entry.color.as_deref().and_then(parse_color),
+    }
+}
+
+/// 深色底色上使用白色文字
+pub fn light_text(color: (u8, u8, u8)) -> bool {
+    let (r, g, b) = color;
+    0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b) < 140.0
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 独立的 HTML 页面，样式内联，按 A4 横向打印
+pub fn render_html(grid: &TimetableGrid) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(&grid.title)));
+    html.push_str(
+        "<style>\n\
+         @page { size: A4 landscape; margin: 12mm; }\n\
+         body { font-family: \"Noto Sans CJK SC\", \"Microsoft YaHei\", sans-serif; margin: 0; color: #222; }\n\
+         h1 { font-size: 20pt; margin: 0 0 2mm; }\n\
+         .subtitle { font-size: 10pt; color: #666; margin: 0 0 4mm; }\n\
+         table { width: 100%; border-collapse: collapse; table-layout: fixed; }\n\
+         th, td { border: 1px solid #999; padding: 1.5mm; vertical-align: top; font-size: 9pt; }\n\
+         th { background: #f0f0f0; }\n\
+         th.time { width: 22mm; font-weight: normal; }\n\
+         .class { border-radius: 1mm; padding: 1mm; margin-bottom: 1mm; -webkit-print-color-adjust: exact; print-color-adjust: exact; }\n\
+         .class div:first-child { font-weight: bold; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&grid.title)));
+    if !grid.subtitle.is_empty() {
+        html.push_str(&format!(
+            "<p class=\"subtitle\">{}</p>\n",
+            escape_html(&grid.subtitle)
+        ));
+    }
+
+    html.push_str("<table>\n<thead><tr><th class=\"time\"></th>");
+    for day in &grid.days {
+        html.push_str(&format!("<th>{}</th>", DAY_NAMES[(*day - 1) as usize]));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for row in &grid.rows {
+        let label: Vec<String> = row.label.iter().map(|l| escape_html(l)).collect();
+        html.push_str(&format!(
+            "<tr><th class=\"time\">{}</th>",
+            label.join("<br>")
+        ));
+        for cell in &row.cells {
+            html.push_str("<td>");
+            for class in cell {
+                let style = match class.color {
+                    Some(color @ (r, g, b)) => format!(
+                        " style=\"background: #{:02x}{:02x}{:02x}; color: {}\"",
+                        r,
+                        g,
+                        b,
+                        if light_text(color) { "#fff" } else { "#222" }
+                    ),
+                    None => " style=\"background: #eef2f7\"".to_string(),
+                };
+                html.push_str(&format!("<div class=\"class\"{}>", style));
+                for line in &class.lines {
+                    html.push_str(&format!("<div>{}</div>", escape_html(line)));
+                }
+                html.push_str("</div>");
+            }
+            html.push_str("</td>");
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n</body>\n</html>\n");
+    html
+}
+
+// A4 横向，单位为 pt
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 36.0;
+const TIME_COLUMN_WIDTH: f32 = 72.0;
+const HEADER_HEIGHT: f32 = 22.0;
+const MAX_ROW_HEIGHT: f32 = 72.0;
+
+/// 文字宽度的估算：ASCII 为半角，其余为全角
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars()
+        .map(|c| if c.is_ascii() { 0.5 } else { 1.0 })
+        .sum::<f32>()
+        * size
+}
+
+/// 超出宽度时截断并加省略号
+fn fit_text(text: &str, max_width: f32, size: f32) -> String {
+    if text_width(text, size) <= max_width {
+        return text.to_string();
+    }
+    let mut fitted = String::new();
+    for c in text.chars() {
+        let mut candidate = fitted.clone();
+        candidate.push(c);
+        if text_width(&candidate, size) + size > max_width {
+            break;
+        }
+        fitted = candidate;
+    }
+    fitted.push('…');
+    fitted
+}
+
+/// PDF 文本串：UniGB-UCS2-H 编码，每个字符为两字节大端 UCS-2
+fn pdf_text(text: &str) -> String {
+    let hex: String = text
+        .chars()
+        .map(|c| {
+            let code = u16::try_from(u32::from(c)).unwrap_or(u16::from(b'?'));
+            format!("{:04X}", code)
+        })
+        .collect();
+    format!("<{}>", hex)
+}
+
+/// 内容流中的绘制指令
+struct Canvas {
+    ops: String,
+}
+
+impl Canvas {
+    fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, (r, g, b): (u8, u8, u8)) {
+        self.ops.push_str(&format!(
+            "{:.3} {:.3} {:.3} rg {:.2} {:.2} {:.2} {:.2} re f\n",
+            f32::from(r) / 255.0,
+            f32::from(g) / 255.0,
+            f32::from(b) / 255.0,
+            x,
+            y,
+            w,
+            h
+        ));
+    }
+
+    fn stroke_rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.ops.push_str(&format!(
+            "0.6 G 0.5 w {:.2} {:.2} {:.2} {:.2} re S\n",
+            x, y, w, h
+        ));
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, light: bool, text: &str) {
+        self.ops.push_str(&format!(
+            "{} g BT /F1 {:.1} Tf {:.2} {:.2} Td {} Tj ET\n",
+            if light { "1" } else { "0.13" },
+            size,
+            x,
+            y,
+            pdf_text(text)
+        ));
+    }
+
+    fn centered_text(&mut self, center: f32, y: f32, size: f32, text: &str) {
+        self.text(center - text_width(text, size) / 2.0, y, size, false, text);
+    }
+}
+
+/// 单页 PDF（A4 横向）
+///
+/// 使用 PDF 阅读器自带的 STSong-Light 字体（Adobe-GB1），不嵌入字体文件即可显示中文。
+pub fn render_pdf(grid: &TimetableGrid) -> Vec<u8> {
+    let mut canvas = Canvas { ops: String::new() };
+
+    let title_y = PAGE_HEIGHT - MARGIN - 16.0;
+    canvas.text(MARGIN, title_y, 16.0, false, &grid.title);
+    if !grid.subtitle.is_empty() {
+        canvas.text(MARGIN, title_y - 16.0, 9.0, false, &grid.subtitle);
+    }
+
+    let top = title_y - 28.0;
+    let day_width = (PAGE_WIDTH - 2.0 * MARGIN - TIME_COLUMN_WIDTH) / grid.days.len() as f32;
+    let row_height =
+        ((top - HEADER_HEIGHT - MARGIN) / grid.rows.len().max(1) as f32).min(MAX_ROW_HEIGHT);
+    let column_x = |i: usize| MARGIN + TIME_COLUMN_WIDTH + i as f32 * day_width;
+
+    canvas.fill_rect(
+        MARGIN,
+        top - HEADER_HEIGHT,
+        PAGE_WIDTH - 2.0 * MARGIN,
+        HEADER_HEIGHT,
+        (240, 240, 240),
+    );
+    canvas.stroke_rect(
+        MARGIN,
+        top - HEADER_HEIGHT,
+        TIME_COLUMN_WIDTH,
+        HEADER_HEIGHT,
+    );
+    for (i, day) in grid.days.iter().enumerate() {
+        canvas.stroke_rect(column_x(i), top - HEADER_HEIGHT, day_width, HEADER_HEIGHT);
+        canvas.centered_text(
+            column_x(i) + day_width / 2.0,
+            top - HEADER_HEIGHT + 7.0,
+            10.0,
+            DAY_NAMES[(*day - 1) as usize],
+        );
+    }
+
+    for (r, row) in grid.rows.iter().enumerate() {
+        let row_top = top - HEADER_HEIGHT - r as f32 * row_height;
+        let row_bottom = row_top - row_height;
+
+        canvas.stroke_rect(MARGIN, row_bottom, TIME_COLUMN_WIDTH, row_height);
+        let label_size = 8.0_f32.min(row_height / 3.0);
+        for (l, line) in row.label.iter().enumerate() {
+            canvas.centered_text(
+                MARGIN + TIME_COLUMN_WIDTH / 2.0,
+                row_top - 4.0 - (l as f32 + 1.0) * label_size * 1.25,
+                label_size,
+                line,
+            );
+        }
+
+        for (i, classes) in row.cells.iter().enumerate() {
+            let x = column_x(i);
+            canvas.stroke_rect(x, row_bottom, day_width, row_height);
+            if classes.is_empty() {
+                continue;
+            }
+            let part_height = row_height / classes.len() as f32;
+            for (c, class) in classes.iter().enumerate() {
+                let part_top = row_top - c as f32 * part_height;
+                let color = class.color.unwrap_or((238, 242, 247));
+                canvas.fill_rect(
+                    x + 1.0,
+                    part_top - part_height + 1.0,
+                    day_width - 2.0,
+                    part_height - 2.0,
+                    color,
+                );
+
+                let size = 9.0_f32.min((part_height - 4.0) / 1.25).max(4.0);
+                let mut y = part_top - 3.0 - size;
+                for (l, line) in class.lines.iter().enumerate() {
+                    if y < part_top - part_height + 2.0 {
+                        break;
+                    }
+                    let line_size = if l == 0 { size } else { size * 0.9 };
+                    let text = fit_text(line, day_width - 8.0, line_size);
+                    canvas.text(x + 4.0, y, line_size, light_text(color), &text);
+                    y -= line_size * 1.25;
+                }
+            }
+        }
+    }
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+             /Resources << /Font << /F1 5 0 R >> >> /Contents 4 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT
+        ),
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            canvas.ops.len(),
+            canvas.ops
+        ),
+        "<< /Type /Font /Subtype /Type0 /BaseFont /STSong-Light-UniGB-UCS2-H \
+         /Encoding /UniGB-UCS2-H /DescendantFonts [6 0 R] >>"
+            .to_string(),
+        "<< /Type /Font /Subtype /CIDFontType0 /BaseFont /STSong-Light \
+         /CIDSystemInfo << /Registry (Adobe) /Ordering (GB1) /Supplement 2 >> \
+         /FontDescriptor 7 0 R /DW 1000 /W [1 95 500] >>"
+            .to_string(),
+        "<< /Type /FontDescriptor /FontName /STSong-Light /Flags 6 \
+         /FontBBox [-25 -254 1000 880] /ItalicAngle 0 /Ascent 880 /Descent -120 \
+         /CapHeight 880 /StemV 93 >>"
+            .to_string(),
+    ];
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", i + 1, object).as_bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+/// 按格式生成文件内容
+pub fn render(grid: &TimetableGrid, format: &str) -> Result<Vec<u8>, String> {
+    match format {
+        FORMAT_PDF => Ok(render_pdf(grid)),
+        FORMAT_HTML => Ok(render_html(grid).into_bytes()),
+        _ => Err(format!(
+            "format must be one of {}",
+            PRINT_FORMATS.join(", ")
+        )),
+    }
+}
+
+pub fn content_type(format: &str) -> &'static str {
+    match format {
+        FORMAT_HTML => "text/html; charset=utf-8",
+        _ => "application/pdf",
+    }
+}
+
+/// 导出文件名，去掉路径分隔符等不能用于文件名的字符
+pub fn file_name(name: &str, format: &str) -> String {
+    let stem: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let stem = if stem.is_empty() { "timetable" } else { &stem };
+    format!("{}.{}", stem, format)
+}
+
+/// 将多个文件打包为 ZIP，重名的文件加上序号
+pub fn zip_files(files: Vec<(String, Vec<u8>)>) -> std::io::Result<Vec<u8>> {
+    let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut used = BTreeSet::new();
+    for (name, content) in files {
+        let mut unique = name.clone();
+        let mut n = 2;
+        while !used.insert(unique.clone()) {
+            unique = match name.rsplit_once('.') {
+                Some((stem, ext)) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", name, n),
+            };
+            n += 1;
+        }
+        writer.start_file(unique, options)?;
+        writer.write_all(&content)?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ical::SCOPE_CLIENT;
+    use crate::models::Attributes;
+
+    fn entry(
+        id: i32,
+        day: i32,
+        start: &str,
+        color: Option<&str>,
+        weeks: Option<Vec<i32>>,
+    ) -> ScheduleEntry {
+        ScheduleEntry {
+            id,
+            client_id: 1,
+            entry_id_on_client: id,
+            course_id: id,
+            course_name: Some(format!("课程{}", id)),
+            teacher: Some("Li".to_string()),
+            location: Some("A101".to_string()),
+            color: color.map(str::to_string),
+            day_of_week: day,
+            start_time: start.to_string(),
+            end_time: "23:00".to_string(),
+            weeks_text: weeks.as_deref().map(crate::weeks::format),
+            weeks_legacy: None,
+            weeks,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+            origin: "client".to_string(),
+            room_id: None,
+            period_start: None,
+            period_end: None,
+        }
+    }
+
+    #[test]
+    fn test_build_grid() {
+        let entries = vec![
+            entry(1, 1, "08:00", Some("#f00"), None),
+            entry(2, 3, "08:00", None, Some(vec![1, 3])),
+            entry(3, 3, "08:00", None, Some(vec![2, 4])),
+            entry(4, 2, "10:00", None, None),
+        ];
+
+        let grid = build_grid("高一(1)班", "", SCOPE_CLIENT, None, &entries);
+        assert_eq!(grid.days, vec![1, 2, 3, 4, 5]);
+        assert_eq!(grid.rows.len(), 2);
+        assert_eq!(grid.rows[0].label, vec!["08:00-23:00"]);
+        assert_eq!(grid.rows[0].cells[0][0].color, Some((255, 0, 0)));
+        assert_eq!(grid.rows[0].cells[2].len(), 2);
+        assert_eq!(
+            grid.rows[0].cells[2][0].lines,
+            vec!["课程2", "Li", "A101", "1,3"]
+        );
+
+        let week_two = build_grid("Li", "", SCOPE_TEACHER, Some(2), &entries);
+        assert_eq!(week_two.rows[0].cells[2].len(), 1);
+        assert_eq!(week_two.rows[0].cells[2][0].lines, vec!["课程3", "A101"]);
+    }
+
+    #[test]
+    fn test_render_html_escapes() {
+        let mut course = entry(1, 6, "08:00", Some("#000000"), None);
+        course.course_name = Some("<b>Math</b>".to_string());
+        let grid = build_grid("A&B", "第 3 周", SCOPE_CLIENT, None, &[course]);
+        let html = render_html(&grid);
+
+        assert!(html.contains("<title>A&amp;B</title>"));
+        assert!(html.contains("&lt;b&gt;Math&lt;/b&gt;"));
+        assert!(html.contains("background: #000000; color: #fff"));
+        assert!(html.contains("<th>星期六</th>"));
+    }
+
+    #[test]
+    fn test_render_pdf_structure() {
+        let grid = build_grid(
+            "课表",
+            "",
+            SCOPE_CLIENT,
+            None,
+            &[entry(1, 1, "08:00", None, None)],
+        );
+        let pdf = render_pdf(&grid);
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(text.contains(&pdf_text("课表")));
+        let startxref: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .and_then(|tail| tail.lines().next())
+            .and_then(|offset| offset.parse().ok())
+            .unwrap();
+        assert!(pdf[startxref..].starts_with(b"xref"));
+        assert_eq!(pdf_text("A课"), "<00418BFE>");
+    }
+}
@@ -0,0 +1,201 @@
+use crate::models::{FreeRoomQuery, Room, ScheduleEntry};
+use crate::validation::{parse_hhmm, weeks_intersect, MAX_LOCATION_LEN};
+
+/// 设备项的最大数量
+pub const MAX_EQUIPMENT: usize = 32;
+
+/// 校验教室字段，返回 `字段: 原因` 形式的错误
+pub fn validate_room(
+    name: &str,
+    capacity: Option<i32>,
+    equipment: &[String],
+) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("name: must not be empty".to_string());
+    }
+    if name.chars().count() > MAX_LOCATION_LEN {
+        return Err(format!(
+            "name: must be at most {} characters",
+            MAX_LOCATION_LEN
+        ));
+    }
+    if capacity.is_some_and(|c| c < 0) {
+        return Err("capacity: must not be negative".to_string());
+    }
+    if equipment.len() > MAX_EQUIPMENT {
+        return Err(format!(
+            "equipment: at most {} items are allowed",
+            MAX_EQUIPMENT
+        ));
+    }
+    if equipment.iter().any(|item| item.trim().is_empty()) {
+        return Err("equipment: items must not be empty".to_string());
+    }
+    Ok(())
+}
+
+/// 校验空闲教室查询的时间段，返回开始与结束的分钟数
+pub fn parse_slot(query: &FreeRoomQuery) -> Result<(u32, u32), String> {
+    if !(1..=7).contains(&query.day_of_week) {
+        return Err("day_of_week must be between 1 and 7".to_string());
+    }
+    let start = parse_hhmm(&query.start_time)
+        .ok_or_else(|| format!("'{}' is not a valid HH:MM time", query.start_time))?;
+    let end = parse_hhmm(&query.end_time)
+        .ok_or_else(|| format!("'{}' is not a valid HH:MM time", query.end_time))?;
+    if start >= end {
+        return Err(format!(
+            "end time {} must be after start time {}",
+            query.end_time, query.start_time
+        ));
+    }
+    Ok((start, end))
+}
+
+/// 在给定星期、时间段与周次内没有课的教室
+///
+/// `entries` 为各客户端的课表条目，按 `room_id` 对应教室；`week` 为 `None`
+/// 时任何周次上课都算占用。容量与楼栋筛选取自查询。
+pub fn free_rooms(rooms: Vec<Room>, entries: &[ScheduleEntry], query: &FreeRoomQuery) -> Vec<Room> {
+    let Ok((start, end)) = parse_slot(query) else {
+        return Vec::new();
+    };
+    let week = query.week.map(|w| vec![w]);
+    let occupied = |room: &Room| {
+        entries.iter().any(|entry| {
+            let (Some(entry_start), Some(entry_end)) =
+                (parse_hhmm(&entry.start_time), parse_hhmm(&entry.end_time))
+            else {
+                return false;
+            };
+            entry.room_id == Some(room.id)
+                && entry.day_of_week == query.day_of_week
+                && entry_start < end
+                && start < entry_end
+                && weeks_intersect(entry.weeks.as_deref(), week.as_deref())
+        })
+    };
+
+    rooms
+        .into_iter()
+        .filter(|room| {
+            query
+                .min_capacity
+                .is_none_or(|min| room.capacity.is_some_and(|c| c >= min))
+        })
+        .filter(|room| {
+            query
+                .building
+                .as_deref()
+                .is_none_or(|b| room.building.as_deref() == Some(b))
+        })
+        .filter(|room| !occupied(room))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Attributes;
+    use chrono::NaiveDate;
+
+    fn room(id: i32, building: &str, capacity: i32) -> Room {
+        Room {
+            id,
+            name: format!("R{}", id),
+            building: Some(building.to_string()),
+            floor: None,
+            capacity: Some(capacity),
+            equipment: Vec::new(),
+            created_at: NaiveDate::from_ymd_opt(2024, 9, 1)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap(),
+        }
+    }
+
+    fn entry(room_id: i32, start: &str, end: &str, weeks: Option<Vec<i32>>) -> ScheduleEntry {
+        ScheduleEntry {
+            id: room_id,
+            client_id: 1,
+            entry_id_on_client: room_id,
+            course_id: 1,
+            course_name: None,
+            teacher: None,
+            location: None,
+            color: None,
+            day_of_week: 1,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            weeks,
+            weeks_text: None,
+            weeks_legacy: None,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+            origin: "client".to_string(),
+            room_id: Some(room_id),
+            period_start: None,
+            period_end: None,
+        }
+    }
+
+    fn query(week: Option<i32>) -> FreeRoomQuery {
+        FreeRoomQuery {
+            day_of_week: 1,
+            start_time: "08:00".to_string(),
+            end_time: "09:00".to_string(),
+            week,
+            min_capacity: None,
+            building: None,
+        }
+    }
+
+    #[test]
+    fn test_free_rooms_in_week() {
+        let rooms = vec![room(1, "A", 40), room(2, "A", 40), room(3, "B", 60)];
+        let entries = vec![
+            entry(1, "08:30", "09:15", None),
+            entry(2, "08:00", "08:45", Some(vec![2, 4])),
+            entry(3, "09:00", "09:45", None),
+        ];
+
+        let ids = |rooms: Vec<Room>| rooms.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(free_rooms(rooms.clone(), &entries, &query(Some(1)))),
+            vec![2, 3]
+        );
+        assert_eq!(
+            ids(free_rooms(rooms.clone(), &entries, &query(None))),
+            vec![3]
+        );
+
+        let filtered = FreeRoomQuery {
+            min_capacity: Some(50),
+            ..query(Some(1))
+        };
+        assert_eq!(ids(free_rooms(rooms, &entries, &filtered)), vec![3]);
+    }
+
+    #[test]
+    fn test_invalid_slot() {
+        let mut invalid = query(None);
+        invalid.end_time = "07:00".to_string();
+        assert!(parse_slot(&invalid).is_err());
+        invalid.day_of_week = 0;
+        assert_eq!(
+            parse_slot(&invalid),
+            Err("day_of_week must be between 1 and 7".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_room() {
+        assert!(validate_room("A101", Some(40), &["projector".to_string()]).is_ok());
+        assert!(validate_room(" ", None, &[]).is_err());
+        assert_eq!(
+            validate_room("A101", Some(-1), &[]),
+            Err("capacity: must not be negative".to_string())
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use crate::handlers;
 use crate::models::*;
+use crate::spreadsheet;
+use crate::sync;
 use actix_web::{web, HttpResponse};
 use utoipa::OpenApi;
 
@@ -14,11 +16,100 @@ use utoipa::OpenApi;
         handlers::delete_client,
         handlers::get_client_courses,
         handlers::get_client_schedule,
+        handlers::create_course,
+        handlers::update_course,
+        handlers::delete_course,
+        handlers::create_schedule_entry,
+        handlers::update_schedule_entry,
+        handlers::delete_schedule_entry,
         handlers::sync_data,
+        handlers::preview_sync,
+        handlers::get_sync_logs,
+        handlers::get_client_sync_logs,
+        handlers::get_sync_health,
+        handlers::get_client_sync_health,
+        handlers::get_client_snapshots,
+        handlers::diff_client_snapshots,
+        handlers::get_client_snapshot,
+        handlers::rollback_client_snapshot,
+        handlers::list_schedule_exceptions,
+        handlers::create_schedule_exception,
+        handlers::delete_schedule_exception,
+        handlers::get_effective_schedule,
+        handlers::get_client_now_next,
+        handlers::get_now_next,
+        handlers::list_calendar_feeds,
+        handlers::create_calendar_feed,
+        handlers::delete_calendar_feed,
+        handlers::get_calendar_feed,
+        handlers::print_timetable,
+        handlers::print_room_timetables,
+        handlers::import_timetable,
+        handlers::preview_import,
+        handlers::export_spreadsheet,
+        handlers::import_spreadsheet,
+        handlers::preview_spreadsheet_import,
+        handlers::list_templates,
+        handlers::create_template,
+        handlers::get_template,
+        handlers::update_template,
+        handlers::delete_template,
+        handlers::link_template_client,
+        handlers::unlink_template_client,
+        handlers::preview_template,
+        handlers::publish_template,
+        handlers::list_bell_schedules,
+        handlers::create_bell_schedule,
+        handlers::get_bell_schedule,
+        handlers::update_bell_schedule,
+        handlers::delete_bell_schedule,
+        handlers::list_semesters,
+        handlers::create_semester,
+        handlers::get_current_semester,
+        handlers::get_current_week,
+        handlers::set_semester_start,
+        handlers::get_semester,
+        handlers::update_semester,
+        handlers::delete_semester,
+        handlers::activate_semester,
+        handlers::list_rooms,
+        handlers::create_room,
+        handlers::find_free_rooms,
+        handlers::get_room,
+        handlers::update_room,
+        handlers::delete_room,
+        handlers::get_room_occupancy,
+        handlers::assign_client_room,
+        handlers::list_client_groups,
+        handlers::create_client_group,
+        handlers::update_client_group,
+        handlers::delete_client_group,
+        handlers::list_announcements,
+        handlers::create_announcement,
+        handlers::get_active_announcements,
+        handlers::get_announcement,
+        handlers::update_announcement,
+        handlers::delete_announcement,
+        handlers::get_announcement_deliveries,
+        handlers::acknowledge_announcement,
+        handlers::list_emergency_alerts,
+        handlers::create_emergency_alert,
+        handlers::get_active_emergency_alerts,
+        handlers::get_emergency_alert,
+        handlers::acknowledge_emergency_alert,
+        handlers::clear_emergency_alert,
+        handlers::list_display_events,
+        handlers::create_display_event,
+        handlers::get_display_event,
+        handlers::update_display_event,
+        handlers::delete_display_event,
+        handlers::get_client_display,
         handlers::get_statistics,
         handlers::get_client_statistics,
         handlers::get_settings,
         handlers::get_setting,
+        handlers::get_settings_schema,
+        handlers::reset_settings,
         handlers::update_setting,
         handlers::register_lms,
         handlers::lms_heartbeat,
@@ -31,6 +122,9 @@ use utoipa::OpenApi;
         handlers::login,
         handlers::get_clients_paginated,
         handlers::get_courses_paginated,
+        handlers::get_schedule_paginated,
+        handlers::get_schedule_conflicts,
+        handlers::search_courses,
     ),
     components(
         schemas(
@@ -38,6 +132,8 @@ use utoipa::OpenApi;
             ApiResponse<Vec<Client>>,
             ApiResponse<Client>,
             ApiResponse<Vec<Course>>,
+            ApiResponse<Course>,
+            ApiResponse<ScheduleEntry>,
             ApiResponse<Vec<ScheduleEntry>>,
             ApiResponse<MessageResponse>,
             ApiResponse<Statistics>,
@@ -51,20 +147,140 @@ use utoipa::OpenApi;
             ApiResponse<UserInfo>,
             ApiResponse<PaginatedResponse<Client>>,
             ApiResponse<PaginatedResponse<Course>>,
+            ApiResponse<PaginatedResponse<ScheduleEntry>>,
+            ApiResponse<PaginatedResponse<SyncLog>>,
+            ApiResponse<Vec<SyncHealth>>,
+            ApiResponse<SyncHealth>,
+            ApiResponse<SyncPreview>,
+            ApiResponse<Vec<ScheduleConflict>>,
+            ApiResponse<PaginatedResponse<TimetableSnapshotSummary>>,
+            ApiResponse<TimetableSnapshot>,
+            ApiResponse<TimetableDiff>,
+            ApiResponse<RollbackResponse>,
+            ApiResponse<Vec<ScheduleException>>,
+            ApiResponse<ScheduleException>,
+            ApiResponse<DaySchedule>,
+            ApiResponse<NowNext>,
+            ApiResponse<Vec<NowNext>>,
+            ApiResponse<Vec<CalendarFeed>>,
+            ApiResponse<CalendarFeed>,
+            ApiResponse<ImportReport>,
+            ApiResponse<Vec<TimetableTemplate>>,
+            ApiResponse<TimetableTemplate>,
+            ApiResponse<TemplatePublishReport>,
+            ApiResponse<Vec<BellSchedule>>,
+            ApiResponse<BellSchedule>,
+            ApiResponse<BellScheduleChange>,
+            ApiResponse<Vec<Semester>>,
+            ApiResponse<Semester>,
+            ApiResponse<WeekInfo>,
+            ApiResponse<SemesterStartResponse>,
+            ApiResponse<Vec<Room>>,
+            ApiResponse<Room>,
+            ApiResponse<RoomOccupancy>,
             HealthResponse,
             Client,
             RegisterClient,
             UpdateClient,
             Course,
+            CreateCourse,
+            UpdateCourse,
             ScheduleEntry,
+            CreateScheduleEntry,
+            UpdateScheduleEntry,
+            ScheduleFilter,
             SyncRequest,
             SyncResponse,
+            SyncRejection,
+            KeptServerItem,
+            ScheduleConflict,
+            ConflictFilter,
+            SyncLog,
+            SyncLogFilter,
+            CourseSearchParams,
+            SyncHealth,
             ClientCourse,
             ClientScheduleEntry,
+            TimetableSnapshot,
+            TimetableSnapshotSummary,
+            SnapshotDiffParams,
+            CourseChange,
+            ScheduleEntryChange,
+            CourseDiff,
+            ScheduleEntryDiff,
+            TimetableDiff,
+            RollbackResponse,
+            SyncPreview,
+            ScheduleException,
+            CreateScheduleException,
+            ExceptionFilter,
+            EffectiveEntry,
+            DaySchedule,
+            NowNext,
+            InstantQuery,
+            CalendarFeed,
+            CreateCalendarFeed,
+            PrintQuery,
+            SpreadsheetExportQuery,
+            ImportRequest,
+            ImportIssue,
+            ImportClientResult,
+            ImportReport,
+            TimetableTemplate,
+            CreateTemplate,
+            UpdateTemplate,
+            TemplateClient,
+            TemplateOverrides,
+            CourseOverride,
+            EntryOverride,
+            TemplateClientResult,
+            TemplatePublishReport,
+            BellSchedule,
+            BellScheduleChange,
+            BellPeriod,
+            CreateBellSchedule,
+            UpdateBellSchedule,
+            Semester,
+            CreateSemester,
+            UpdateSemester,
+            WeekInfo,
+            DateQuery,
+            SetSemesterStart,
+            SemesterStartResponse,
+            Room,
+            CreateRoom,
+            UpdateRoom,
+            AssignRoom,
+            OccupancyQuery,
+            RoomOccupancy,
+            FreeRoomQuery,
+            ClientGroup,
+            CreateClientGroup,
+            UpdateClientGroup,
+            Announcement,
+            CreateAnnouncement,
+            UpdateAnnouncement,
+            AnnouncementDelivery,
+            ClientUuidQuery,
+            AcknowledgeAnnouncement,
+            EmergencyAlert,
+            CreateEmergencyAlert,
+            AcknowledgeEmergency,
+            EmergencyClientStatus,
+            EmergencyStatus,
+            DisplayEvent,
+            CreateDisplayEvent,
+            UpdateDisplayEvent,
+            Countdown,
+            DisplayContent,
+            CourseSyncPreview,
+            ScheduleEntrySyncPreview,
             Statistics,
             ClientStatistics,
             Setting,
             UpdateSetting,
+            SettingSchema,
+            ResetSettingsRequest,
             MessageResponse,
             RootResponse,
             LMSInstance,
@@ -81,13 +297,28 @@ use utoipa::OpenApi;
             PaginationParams,
             PaginatedResponse<Client>,
             PaginatedResponse<Course>,
+            PaginatedResponse<SyncLog>,
             PaginationInfo,
         )
     ),
     tags(
         (name = "System", description = "System endpoints"),
         (name = "Clients", description = "Client management"),
+        (name = "Schedule", description = "Schedule queries and central editing across clients"),
         (name = "Sync", description = "Data synchronization"),
+        (name = "Snapshots", description = "Timetable versions and rollback"),
+        (name = "Semesters", description = "Semesters and teaching weeks"),
+        (name = "Rooms", description = "Room registry, occupancy and free-room search"),
+        (name = "Bell Schedules", description = "Period tables such as regular and short days"),
+        (name = "Exceptions", description = "Holidays, make-up days, bell schedule switches and class changes"),
+        (name = "Calendar", description = "iCalendar subscriptions of client, teacher and room timetables"),
+        (name = "Printing", description = "Printable PDF and HTML timetables"),
+        (name = "Import", description = "Timetable import from .ics, CSV and XLSX, and XLSX export"),
+        (name = "Templates", description = "Timetable templates shared by groups of clients"),
+        (name = "Client Groups", description = "Named groups of clients, e.g. a grade or building"),
+        (name = "Announcements", description = "Notices broadcast to classroom displays with delivery tracking"),
+        (name = "Emergency", description = "Emergency broadcasts repeated until every display acknowledges"),
+        (name = "Events", description = "Countdowns to exams and other special days, and the content each display renders"),
         (name = "Statistics", description = "Statistics"),
         (name = "Settings", description = "Settings management"),
         (name = "LMS Management", description = "Light Management Service instances management"),
@@ -129,18 +360,240 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 .route("/{id}", web::put().to(handlers::update_client))
                 .route("/{id}", web::delete().to(handlers::delete_client))
                 .route("/{id}/courses", web::get().to(handlers::get_client_courses))
+                .route("/{id}/courses", web::post().to(handlers::create_course))
                 .route(
                     "/{id}/schedule",
                     web::get().to(handlers::get_client_schedule),
+                )
+                .route(
+                    "/{id}/schedule",
+                    web::post().to(handlers::create_schedule_entry),
+                )
+                .route(
+                    "/{id}/schedule/effective",
+                    web::get().to(handlers::get_effective_schedule),
+                )
+                .route("/{id}/now", web::get().to(handlers::get_client_now_next))
+                .route("/{id}/display", web::get().to(handlers::get_client_display))
+                .route("/{id}/room", web::put().to(handlers::assign_client_room))
+                .route(
+                    "/{id}/sync/logs",
+                    web::get().to(handlers::get_client_sync_logs),
+                )
+                .route(
+                    "/{id}/sync/health",
+                    web::get().to(handlers::get_client_sync_health),
+                )
+                .route(
+                    "/{id}/snapshots",
+                    web::get().to(handlers::get_client_snapshots),
+                )
+                .route(
+                    "/{id}/snapshots/diff",
+                    web::get().to(handlers::diff_client_snapshots),
+                )
+                .route(
+                    "/{id}/snapshots/{version}",
+                    web::get().to(handlers::get_client_snapshot),
+                )
+                .route(
+                    "/{id}/snapshots/{version}/rollback",
+                    web::post().to(handlers::rollback_client_snapshot),
                 ),
         )
         // Courses
         .service(
             web::scope("/courses")
-                .route("/paginated", web::get().to(handlers::get_courses_paginated)),
+                .route("/paginated", web::get().to(handlers::get_courses_paginated))
+                .route("/search", web::get().to(handlers::search_courses))
+                .route("/{id}", web::put().to(handlers::update_course))
+                .route("/{id}", web::delete().to(handlers::delete_course)),
+        )
+        // Schedule
+        .service(
+            web::scope("/schedule")
+                .route("", web::get().to(handlers::get_schedule_paginated))
+                .route("/{id}", web::put().to(handlers::update_schedule_entry))
+                .route("/{id}", web::delete().to(handlers::delete_schedule_entry)),
         )
         // Sync
-        .route("/sync", web::post().to(handlers::sync_data))
+        .service(
+            web::scope("/sync")
+                .app_data(web::PayloadConfig::new(sync::MAX_SYNC_BYTES))
+                .route("", web::post().to(handlers::sync_data))
+                .route("/preview", web::post().to(handlers::preview_sync))
+                .route("/logs", web::get().to(handlers::get_sync_logs))
+                .route("/health", web::get().to(handlers::get_sync_health)),
+        )
+        // Teacher and location conflicts across clients
+        .route(
+            "/conflicts",
+            web::get().to(handlers::get_schedule_conflicts),
+        )
+        // Now and next classes of all clients
+        .route("/now", web::get().to(handlers::get_now_next))
+        // Schedule exceptions
+        .service(
+            web::scope("/exceptions")
+                .route("", web::get().to(handlers::list_schedule_exceptions))
+                .route("", web::post().to(handlers::create_schedule_exception))
+                .route(
+                    "/{id}",
+                    web::delete().to(handlers::delete_schedule_exception),
+                ),
+        )
+        // Calendar subscriptions, managed by token
+        .service(
+            web::scope("/calendar-feeds")
+                .route("", web::get().to(handlers::list_calendar_feeds))
+                .route("", web::post().to(handlers::create_calendar_feed))
+                .route("/{id}", web::delete().to(handlers::delete_calendar_feed)),
+        )
+        // Public iCalendar feeds, the token is the credential
+        .route(
+            "/calendar/{token}.ics",
+            web::get().to(handlers::get_calendar_feed),
+        )
+        // Printable timetables
+        .service(
+            web::scope("/print")
+                .route("/rooms", web::get().to(handlers::print_room_timetables))
+                .route(
+                    "/{scope}/{target}",
+                    web::get().to(handlers::print_timetable),
+                ),
+        )
+        // Timetable import and spreadsheets
+        .service(
+            web::scope("/import")
+                .route("", web::post().to(handlers::import_timetable))
+                .route("/preview", web::post().to(handlers::preview_import))
+                .service(
+                    web::scope("/xlsx")
+                        .app_data(web::PayloadConfig::new(spreadsheet::MAX_XLSX_BYTES))
+                        .route("", web::post().to(handlers::import_spreadsheet))
+                        .route(
+                            "/preview",
+                            web::post().to(handlers::preview_spreadsheet_import),
+                        ),
+                ),
+        )
+        .route("/export/xlsx", web::get().to(handlers::export_spreadsheet))
+        // Timetable templates
+        .service(
+            web::scope("/templates")
+                .route("", web::get().to(handlers::list_templates))
+                .route("", web::post().to(handlers::create_template))
+                .route("/{id}", web::get().to(handlers::get_template))
+                .route("/{id}", web::put().to(handlers::update_template))
+                .route("/{id}", web::delete().to(handlers::delete_template))
+                .route(
+                    "/{id}/clients/{client_id}",
+                    web::put().to(handlers::link_template_client),
+                )
+                .route(
+                    "/{id}/clients/{client_id}",
+                    web::delete().to(handlers::unlink_template_client),
+                )
+                .route("/{id}/preview", web::get().to(handlers::preview_template))
+                .route("/{id}/publish", web::post().to(handlers::publish_template)),
+        )
+        // Bell schedules
+        .service(
+            web::scope("/bell-schedules")
+                .route("", web::get().to(handlers::list_bell_schedules))
+                .route("", web::post().to(handlers::create_bell_schedule))
+                .route("/{id}", web::get().to(handlers::get_bell_schedule))
+                .route("/{id}", web::put().to(handlers::update_bell_schedule))
+                .route("/{id}", web::delete().to(handlers::delete_bell_schedule)),
+        )
+        // Semesters
+        .service(
+            web::scope("/semesters")
+                .route("", web::get().to(handlers::list_semesters))
+                .route("", web::post().to(handlers::create_semester))
+                .route("/current", web::get().to(handlers::get_current_semester))
+                .route("/current/week", web::get().to(handlers::get_current_week))
+                .route(
+                    "/current/start",
+                    web::put().to(handlers::set_semester_start),
+                )
+                .route("/{id}", web::get().to(handlers::get_semester))
+                .route("/{id}", web::put().to(handlers::update_semester))
+                .route("/{id}", web::delete().to(handlers::delete_semester))
+                .route(
+                    "/{id}/activate",
+                    web::post().to(handlers::activate_semester),
+                ),
+        )
+        // Rooms
+        .service(
+            web::scope("/rooms")
+                .route("", web::get().to(handlers::list_rooms))
+                .route("", web::post().to(handlers::create_room))
+                .route("/free", web::get().to(handlers::find_free_rooms))
+                .route("/{id}", web::get().to(handlers::get_room))
+                .route("/{id}", web::put().to(handlers::update_room))
+                .route("/{id}", web::delete().to(handlers::delete_room))
+                .route(
+                    "/{id}/occupancy",
+                    web::get().to(handlers::get_room_occupancy),
+                ),
+        )
+        // Client groups
+        .service(
+            web::scope("/client-groups")
+                .route("", web::get().to(handlers::list_client_groups))
+                .route("", web::post().to(handlers::create_client_group))
+                .route("/{id}", web::put().to(handlers::update_client_group))
+                .route("/{id}", web::delete().to(handlers::delete_client_group)),
+        )
+        // Announcements
+        .service(
+            web::scope("/announcements")
+                .route("", web::get().to(handlers::list_announcements))
+                .route("", web::post().to(handlers::create_announcement))
+                .route("/active", web::get().to(handlers::get_active_announcements))
+                .route("/{id}", web::get().to(handlers::get_announcement))
+                .route("/{id}", web::put().to(handlers::update_announcement))
+                .route("/{id}", web::delete().to(handlers::delete_announcement))
+                .route(
+                    "/{id}/deliveries",
+                    web::get().to(handlers::get_announcement_deliveries),
+                )
+                .route(
+                    "/{id}/ack",
+                    web::post().to(handlers::acknowledge_announcement),
+                ),
+        )
+        // Emergency alerts
+        .service(
+            web::scope("/emergency")
+                .route("", web::get().to(handlers::list_emergency_alerts))
+                .route("", web::post().to(handlers::create_emergency_alert))
+                .route(
+                    "/active",
+                    web::get().to(handlers::get_active_emergency_alerts),
+                )
+                .route("/{id}", web::get().to(handlers::get_emergency_alert))
+                .route(
+                    "/{id}/ack",
+                    web::post().to(handlers::acknowledge_emergency_alert),
+                )
+                .route(
+                    "/{id}/clear",
+                    web::post().to(handlers::clear_emergency_alert),
+                ),
+        )
+        // Display events
+        .service(
+            web::scope("/events")
+                .route("", web::get().to(handlers::list_display_events))
+                .route("", web::post().to(handlers::create_display_event))
+                .route("/{id}", web::get().to(handlers::get_display_event))
+                .route("/{id}", web::put().to(handlers::update_display_event))
+                .route("/{id}", web::delete().to(handlers::delete_display_event)),
+        )
         // Statistics
         .service(
             web::scope("/statistics")
@@ -151,6 +604,8 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         .service(
             web::scope("/settings")
                 .route("", web::get().to(handlers::get_settings))
+                .route("/schema", web::get().to(handlers::get_settings_schema))
+                .route("/reset", web::post().to(handlers::reset_settings))
                 .route("/{key}", web::get().to(handlers::get_setting))
                 .route("/{key}", web::put().to(handlers::update_setting)),
         )
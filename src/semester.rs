@@ -0,0 +1,106 @@
+use crate::models::{Semester, WeekInfo};
+use crate::validation::MAX_WEEK_NUMBER;
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// 日期所在周的周一
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+/// 日期对应的教学周，开始日期所在的周（周一至周日）为第 1 周
+///
+/// 开学前的日期得到 0 或负数。
+pub fn week_number(start_date: NaiveDate, date: NaiveDate) -> i32 {
+    let days = (date - week_start(start_date)).num_days();
+    (days.div_euclid(7) + 1) as i32
+}
+
+/// 由结束日期或周数补全另一项，返回 (结束日期, 周数)
+///
+/// 只给周数时，学期在最后一周的周日结束；两者都给时必须一致。
+pub fn resolve_dates(
+    start_date: NaiveDate,
+    end_date: Option<NaiveDate>,
+    week_count: Option<i32>,
+) -> Result<(NaiveDate, i32), String> {
+    let (end_date, week_count) = match (end_date, week_count) {
+        (Some(end_date), Some(week_count)) => {
+            if week_number(start_date, end_date) != week_count {
+                return Err(format!(
+                    "{} to {} spans {} weeks, not {}",
+                    start_date,
+                    end_date,
+                    week_number(start_date, end_date),
+                    week_count
+                ));
+            }
+            (end_date, week_count)
+        }
+        (Some(end_date), None) => (end_date, week_number(start_date, end_date)),
+        (None, Some(week_count)) => (
+            week_start(start_date) + Duration::days(i64::from(week_count) * 7 - 1),
+            week_count,
+        ),
+        (None, None) => return Err("either end_date or week_count is required".to_string()),
+    };
+
+    if end_date < start_date {
+        return Err("end_date must not be before start_date".to_string());
+    }
+    if !(1..=MAX_WEEK_NUMBER).contains(&week_count) {
+        return Err(format!(
+            "week_count must be between 1 and {}",
+            MAX_WEEK_NUMBER
+        ));
+    }
+    Ok((end_date, week_count))
+}
+
+/// 学期中某一天的周次信息
+pub fn week_info(semester: &Semester, date: NaiveDate) -> WeekInfo {
+    WeekInfo {
+        week: week_number(semester.start_date, date),
+        date: date.format("%Y-%m-%d").to_string(),
+        in_semester: semester.start_date <= date && date <= semester.end_date,
+        semester_id: semester.id,
+        semester_name: semester.name.clone(),
+        semester_start_date: semester.start_date.format("%Y-%m-%d").to_string(),
+        week_count: semester.week_count,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_week_number_aligns_to_monday() {
+        // 2024-09-04 is a Wednesday; its week starts on Monday 2024-09-02
+        let start = date("2024-09-04");
+        assert_eq!(week_number(start, date("2024-09-02")), 1);
+        assert_eq!(week_number(start, date("2024-09-08")), 1);
+        assert_eq!(week_number(start, date("2024-09-09")), 2);
+        assert_eq!(week_number(start, date("2024-11-01")), 9);
+        assert_eq!(week_number(start, date("2024-08-30")), 0);
+    }
+
+    #[test]
+    fn test_resolve_dates() {
+        let start = date("2024-09-02");
+        assert_eq!(
+            resolve_dates(start, None, Some(18)).unwrap(),
+            (date("2025-01-05"), 18)
+        );
+        assert_eq!(
+            resolve_dates(start, Some(date("2025-01-05")), None).unwrap(),
+            (date("2025-01-05"), 18)
+        );
+        assert!(resolve_dates(start, Some(date("2025-01-05")), Some(20)).is_err());
+        assert!(resolve_dates(start, Some(date("2024-08-01")), None).is_err());
+        assert!(resolve_dates(start, None, None).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use crate::models::SettingSchema;
+use crate::sync;
+use crate::validation;
+use crate::weeks;
+
+/// 设置项的类型与取值约束
+#[derive(Debug, Clone, Copy)]
+pub enum SettingKind {
+    Integer {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    /// 不能为空
+    Text {
+        default: &'static str,
+        max_len: usize,
+    },
+}
+
+/// 设置项定义
+#[derive(Debug, Clone, Copy)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub kind: SettingKind,
+    pub description: &'static str,
+}
+
+impl SettingDefinition {
+    pub fn default_value(&self) -> String {
+        match self.kind {
+            SettingKind::Integer { default, .. } => default.to_string(),
+            SettingKind::Text { default, .. } => default.to_string(),
+        }
+    }
+}
+
+/// 所有可修改的设置项；不在表中的键会被拒绝
+pub const SETTINGS: &[SettingDefinition] = &[
+    SettingDefinition {
+        key: "server_name",
+        kind: SettingKind::Text {
+            default: "ClassTop Management Server",
+            max_len: 100,
+        },
+        description: "Name of this server shown in the dashboard",
+    },
+    SettingDefinition {
+        key: "auto_sync_interval",
+        kind: SettingKind::Integer {
+            default: 300,
+            min: 10,
+            max: 86_400,
+        },
+        description: "Interval between automatic client syncs, in seconds",
+    },
+    SettingDefinition {
+        key: "max_clients",
+        kind: SettingKind::Integer {
+            default: 100,
+            min: 1,
+            max: 10_000,
+        },
+        description: "Maximum number of registered clients",
+    },
+    SettingDefinition {
+        key: "semester_weeks",
+        kind: SettingKind::Integer {
+            default: weeks::DEFAULT_SEMESTER_WEEKS as i64,
+            min: 1,
+            max: validation::MAX_WEEK_NUMBER as i64,
+        },
+        description: "Number of weeks in a semester when no semester is active",
+    },
+    SettingDefinition {
+        key: "sync_idempotency_retention_hours",
+        kind: SettingKind::Integer {
+            default: sync::DEFAULT_IDEMPOTENCY_RETENTION_HOURS,
+            min: 1,
+            max: 720,
+        },
+        description:
+            "How long sync responses are kept for replaying Idempotency-Key requests, in hours",
+    },
+];
+
+pub fn find(key: &str) -> Option<&'static SettingDefinition> {
+    SETTINGS.iter().find(|s| s.key == key)
+}
+
+/// 校验设置值，返回规范化后的值；错误为 `字段: 原因` 形式
+pub fn validate(key: &str, value: &str) -> Result<String, String> {
+    let definition = find(key).ok_or_else(|| format!("{}: unknown setting", key))?;
+    let value = value.trim();
+    match definition.kind {
+        SettingKind::Integer { min, max, .. } => {
+            let parsed: i64 = value
+                .parse()
+                .map_err(|_| format!("{}: must be an integer", key))?;
+            if !(min..=max).contains(&parsed) {
+                return Err(format!("{}: must be between {} and {}", key, min, max));
+            }
+            Ok(parsed.to_string())
+        }
+        SettingKind::Text { max_len, .. } => {
+            if value.is_empty() {
+                return Err(format!("{}: must not be empty", key));
+            }
+            if value.chars().count() > max_len {
+                return Err(format!("{}: must be at most {} characters", key, max_len));
+            }
+            Ok(value.to_string())
+        }
+    }
+}
+
+/// 恢复默认值的设置项，跳过 `exclude` 中的键
+pub fn reset_targets(exclude: &[String]) -> Result<Vec<&'static SettingDefinition>, String> {
+    if let Some(unknown) = exclude.iter().find(|key| find(key).is_none()) {
+        return Err(format!("exclude: unknown setting {}", unknown));
+    }
+    Ok(SETTINGS
+        .iter()
+        .filter(|s| !exclude.iter().any(|key| key == s.key))
+        .collect())
+}
+
+/// 供管理后台渲染设置表单
+pub fn schema() -> Vec<SettingSchema> {
+    SETTINGS
+        .iter()
+        .map(|s| {
+            let (value_type, min, max, max_length) = match s.kind {
+                SettingKind::Integer { min, max, .. } => ("integer", Some(min), Some(max), None),
+                SettingKind::Text { max_len, .. } => ("string", None, None, Some(max_len)),
+            };
+            SettingSchema {
+                key: s.key.to_string(),
+                value_type: value_type.to_string(),
+                default: s.default_value(),
+                min,
+                max,
+                max_length,
+                description: s.description.to_string(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate("max_clients", " 250 "), Ok("250".to_string()));
+        assert_eq!(
+            validate("max_clients", "lots"),
+            Err("max_clients: must be an integer".to_string())
+        );
+        assert_eq!(
+            validate("auto_sync_interval", "5"),
+            Err("auto_sync_interval: must be between 10 and 86400".to_string())
+        );
+        assert!(validate("server_name", "  ").is_err());
+        assert_eq!(
+            validate("theme", "dark"),
+            Err("theme: unknown setting".to_string())
+        );
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        for definition in SETTINGS {
+            assert_eq!(
+                validate(definition.key, &definition.default_value()),
+                Ok(definition.default_value())
+            );
+        }
+        assert_eq!(schema().len(), SETTINGS.len());
+    }
+
+    #[test]
+    fn test_reset_targets() {
+        let keys: Vec<&str> = reset_targets(&["server_name".to_string()])
+            .unwrap()
+            .iter()
+            .map(|s| s.key)
+            .collect();
+        assert!(!keys.contains(&"server_name"));
+        assert_eq!(keys.len(), SETTINGS.len() - 1);
+        assert!(reset_targets(&["theme".to_string()]).is_err());
+    }
+}
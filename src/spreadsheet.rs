@@ -0,0 +1,936 @@
+use crate::import::normalize_time;
+use crate::models::{Attributes, ClientCourse, ClientScheduleEntry, ImportIssue};
+use crate::printing::{light_text, TimetableGrid, DAY_NAMES};
+use crate::weeks;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{Cursor, Read, Write};
+
+pub const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// 平铺表的工作表名称，导入时优先读取
+pub const FLAT_SHEET: &str = "Timetable";
+
+/// 平铺表的列，每行一个课表条目；没有条目的课程单独占一行，条目列留空
+pub const FLAT_COLUMNS: &[&str] = &[
+    "client_id",
+    "client_name",
+    "course_id",
+    "course_name",
+    "teacher",
+    "location",
+    "color",
+    "course_note",
+    "entry_id",
+    "day_of_week",
+    "start_time",
+    "end_time",
+    "weeks",
+    "room",
+    "note",
+];
+
+/// 上传的 XLSX 文件的最大大小
+pub const MAX_XLSX_BYTES: usize = 10 * 1024 * 1024;
+
+/// 解压后单个部件的最大大小，防止压缩炸弹
+const MAX_PART_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Excel 工作表的行数与列数上限，超出的单元格引用视为无效
+const MAX_ROWS: usize = 1_048_576;
+const MAX_COLUMNS: usize = 16_384;
+
+/// 读取时最多展开的单元格数（含补齐的空单元格）
+const MAX_CELLS: usize = 2_000_000;
+
+const MAX_SHEET_NAME_LEN: usize = 31;
+
+const STYLE_HEADER: usize = 1;
+const STYLE_WRAP: usize = 2;
+const FIRST_COLOR_STYLE: usize = 3;
+
+/// 导出的一个客户端：客户端 ID 下的课程与条目，以及周课表
+#[derive(Debug, Clone)]
+pub struct ClientSheet {
+    pub client_id: i32,
+    pub client_name: String,
+    pub courses: Vec<ClientCourse>,
+    pub entries: Vec<ClientScheduleEntry>,
+    pub grid: TimetableGrid,
+}
+
+/// 平铺表中一个客户端的课程与条目，以及各自所在的行号
+#[derive(Debug, Default)]
+pub struct FlatClient {
+    pub client_id: i32,
+    pub rows: usize,
+    pub courses: Vec<ClientCourse>,
+    pub entries: Vec<ClientScheduleEntry>,
+    pub course_rows: HashMap<i32, usize>,
+    pub entry_rows: HashMap<i32, usize>,
+}
+
+struct Cell {
+    text: String,
+    number: bool,
+    style: usize,
+}
+
+impl Cell {
+    fn text(text: impl Into<String>, style: usize) -> Self {
+        Cell {
+            text: text.into(),
+            number: false,
+            style,
+        }
+    }
+
+    fn number(value: i32) -> Self {
+        Cell {
+            text: value.to_string(),
+            number: true,
+            style: 0,
+        }
+    }
+
+    fn optional(text: Option<&str>) -> Self {
+        Cell::text(text.unwrap_or_default(), 0)
+    }
+}
+
+struct Sheet {
+    name: String,
+    widths: Vec<f32>,
+    rows: Vec<(Option<f32>, Vec<Cell>)>, // 行高与单元格
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// 列序号（从 0 开始）转为 `A`、`B`、…、`AA`
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// 单元格引用（如 `C12`）中的列序号
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase())
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    // 过长的字母串饱和到 usize::MAX，由调用方按列数上限拒绝
+    let number = letters.iter().fold(0usize, |acc, b| {
+        acc.saturating_mul(26)
+            .saturating_add(usize::from(b - b'A') + 1)
+    });
+    Some(number - 1)
+}
+
+/// 工作表名称：去掉不允许的字符，截断到 31 个字符，与已有名称重复时加序号
+fn sheet_name(name: &str, used: &mut BTreeSet<String>) -> String {
+    let clean: String = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            ':' | '\\' | '/' | '?' | '*' | '[' | ']' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let clean = clean.trim_matches('\'');
+    let clean = if clean.is_empty() { "Sheet" } else { clean };
+
+    let mut n = 1;
+    loop {
+        let suffix = if n == 1 {
+            String::new()
+        } else {
+            format!(" ({})", n)
+        };
+        let stem: String = clean
+            .chars()
+            .take(MAX_SHEET_NAME_LEN - suffix.chars().count())
+            .collect();
+        let candidate = format!("{}{}", stem, suffix);
+        // Excel compares sheet names case-insensitively
+        if used.insert(candidate.to_lowercase()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+fn flat_sheet(clients: &[ClientSheet]) -> Sheet {
+    let mut rows = vec![(
+        None,
+        FLAT_COLUMNS
+            .iter()
+            .map(|c| Cell::text(*c, STYLE_HEADER))
+            .collect(),
+    )];
+
+    for client in clients {
+        let course_cells = |course: &ClientCourse| {
+            vec![
+                Cell::number(client.client_id),
+                Cell::text(client.client_name.clone(), 0),
+                Cell::number(course.id),
+                Cell::text(course.name.clone(), 0),
+                Cell::optional(course.teacher.as_deref()),
+                Cell::optional(course.location.as_deref()),
+                Cell::optional(course.color.as_deref()),
+                Cell::optional(course.note.as_deref()),
+            ]
+        };
+
+        let mut entries: Vec<&ClientScheduleEntry> = client.entries.iter().collect();
+        entries.sort_by(|a, b| {
+            (a.day_of_week, &a.start_time, a.id).cmp(&(b.day_of_week, &b.start_time, b.id))
+        });
+        for entry in entries {
+            let Some(course) = client.courses.iter().find(|c| c.id == entry.course_id) else {
+                continue;
+            };
+            let mut cells = course_cells(course);
+            cells.extend([
+                Cell::number(entry.id),
+                Cell::number(entry.day_of_week),
+                Cell::text(entry.start_time.clone(), 0),
+                Cell::text(entry.end_time.clone(), 0),
+                Cell::text(
+                    entry
+                        .weeks
+                        .as_deref()
+                        .map(weeks::format)
+                        .unwrap_or_default(),
+                    0,
+                ),
+                Cell::optional(entry.room.as_deref()),
+                Cell::optional(entry.note.as_deref()),
+            ]);
+            rows.push((None, cells));
+        }
+
+        for course in &client.courses {
+            if !client.entries.iter().any(|e| e.course_id == course.id) {
+                rows.push((None, course_cells(course)));
+            }
+        }
+    }
+
+    Sheet {
+        name: FLAT_SHEET.to_string(),
+        widths: vec![
+            10.0, 18.0, 10.0, 20.0, 12.0, 12.0, 10.0, 20.0, 10.0, 12.0, 11.0, 11.0, 14.0, 12.0,
+            20.0,
+        ],
+        rows,
+    }
+}
+
+fn grid_sheet(name: String, grid: &TimetableGrid, colors: &[(u8, u8, u8)]) -> Sheet {
+    let mut header = vec![Cell::text(grid.title.clone(), STYLE_HEADER)];
+    header.extend(
+        grid.days
+            .iter()
+            .map(|day| Cell::text(DAY_NAMES[(*day - 1) as usize], STYLE_HEADER)),
+    );
+    let mut rows = vec![(None, header)];
+
+    for row in &grid.rows {
+        let mut lines = row.label.len();
+        let mut cells = vec![Cell::text(row.label.join("\n"), STYLE_WRAP)];
+        for classes in &row.cells {
+            let text = classes
+                .iter()
+                .map(|c| c.lines.join("\n"))
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            lines = lines.max(text.lines().count());
+            // A cell holding several classes takes the colour of the first one that has it
+            let style = classes
+                .iter()
+                .find_map(|c| c.color)
+                .and_then(|color| colors.iter().position(|c| *c == color))
+                .map_or(STYLE_WRAP, |i| FIRST_COLOR_STYLE + i);
+            cells.push(Cell::text(text, style));
+        }
+        rows.push((Some(15.0 * lines.max(1) as f32), cells));
+    }
+
+    let mut widths = vec![14.0];
+    widths.extend(grid.days.iter().map(|_| 18.0));
+    Sheet { name, widths, rows }
+}
+
+fn sheet_xml(sheet: &Sheet) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetViews><sheetView workbookViewId="0"><pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/></sheetView></sheetViews><cols>"#,
+    );
+    for (i, width) in sheet.widths.iter().enumerate() {
+        xml.push_str(&format!(
+            r#"<col min="{0}" max="{0}" width="{1}" customWidth="1"/>"#,
+            i + 1,
+            width
+        ));
+    }
+    xml.push_str("</cols><sheetData>");
+    for (r, (height, cells)) in sheet.rows.iter().enumerate() {
+        match height {
+            Some(height) => xml.push_str(&format!(
+                r#"<row r="{}" ht="{}" customHeight="1">"#,
+                r + 1,
+                height
+            )),
+            None => xml.push_str(&format!(r#"<row r="{}">"#, r + 1)),
+        }
+        for (c, cell) in cells.iter().enumerate() {
+            if cell.text.is_empty() && cell.style == 0 {
+                continue;
+            }
+            let reference = format!("{}{}", column_name(c), r + 1);
+            if cell.number {
+                xml.push_str(&format!(
+                    r#"<c r="{}" s="{}"><v>{}</v></c>"#,
+                    reference, cell.style, cell.text
+                ));
+            } else {
+                xml.push_str(&format!(
+                    r#"<c r="{}" s="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                    reference,
+                    cell.style,
+                    escape_xml(&cell.text)
+                ));
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// 样式：0 默认，1 表头（粗体、灰底），2 自动换行，之后每种课程颜色一个
+fn styles_xml(colors: &[(u8, u8, u8)]) -> String {
+    let mut fills = String::from(
+        r#"<fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill><fill><patternFill patternType="solid"><fgColor rgb="FFF0F0F0"/></patternFill></fill>"#,
+    );
+    let mut xfs = String::from(
+        r#"<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/><xf numFmtId="0" fontId="1" fillId="2" borderId="0" xfId="0" applyFont="1" applyFill="1"/><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0" applyAlignment="1"><alignment vertical="top" wrapText="1"/></xf>"#,
+    );
+    for (i, &(r, g, b)) in colors.iter().enumerate() {
+        fills.push_str(&format!(
+            r#"<fill><patternFill patternType="solid"><fgColor rgb="FF{:02X}{:02X}{:02X}"/></patternFill></fill>"#,
+            r, g, b
+        ));
+        xfs.push_str(&format!(
+            r#"<xf numFmtId="0" fontId="{}" fillId="{}" borderId="0" xfId="0" applyFont="1" applyFill="1" applyAlignment="1"><alignment vertical="top" wrapText="1"/></xf>"#,
+            if light_text((r, g, b)) { 2 } else { 0 },
+            3 + i
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><fonts count="3"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font><font><sz val="11"/><color rgb="FFFFFFFF"/><name val="Calibri"/></font></fonts><fills count="{}">{}</fills><borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders><cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs><cellXfs count="{}">{}</cellXfs></styleSheet>"#,
+        3 + colors.len(),
+        fills,
+        FIRST_COLOR_STYLE + colors.len(),
+        xfs
+    )
+}
+
+/// 生成 XLSX 工作簿：首个工作表为平铺表，之后每个客户端一张周课表
+pub fn write_workbook(clients: &[ClientSheet]) -> std::io::Result<Vec<u8>> {
+    let colors: Vec<(u8, u8, u8)> = clients
+        .iter()
+        .flat_map(|c| &c.grid.rows)
+        .flat_map(|row| row.cells.iter().flatten())
+        .filter_map(|cell| cell.color)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let mut used = BTreeSet::from([FLAT_SHEET.to_lowercase()]);
+    let mut sheets = vec![flat_sheet(clients)];
+    sheets.extend(
+        clients
+            .iter()
+            .map(|c| grid_sheet(sheet_name(&c.client_name, &mut used), &c.grid, &colors)),
+    );
+
+    let mut content_types = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/><Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+    );
+    let mut workbook = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+    );
+    let mut workbook_rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    for (i, sheet) in sheets.iter().enumerate() {
+        let n = i + 1;
+        content_types.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+            n
+        ));
+        workbook.push_str(&format!(
+            r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#,
+            escape_xml(&sheet.name),
+            n,
+            n
+        ));
+        workbook_rels.push_str(&format!(
+            r#"<Relationship Id="rId{0}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{0}.xml"/>"#,
+            n
+        ));
+    }
+    content_types.push_str("</Types>");
+    workbook.push_str("</sheets></workbook>");
+    workbook_rels.push_str(&format!(
+        r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#,
+        sheets.len() + 1
+    ));
+
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut parts = vec![
+        ("[Content_Types].xml".to_string(), content_types),
+        (
+            "_rels/.rels".to_string(),
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/></Relationships>"#
+                .to_string(),
+        ),
+        ("xl/workbook.xml".to_string(), workbook),
+        ("xl/_rels/workbook.xml.rels".to_string(), workbook_rels),
+        ("xl/styles.xml".to_string(), styles_xml(&colors)),
+    ];
+    parts.extend(sheets.iter().enumerate().map(|(i, sheet)| {
+        (
+            format!("xl/worksheets/sheet{}.xml", i + 1),
+            sheet_xml(sheet),
+        )
+    }));
+    for (name, content) in parts {
+        writer.start_file(name, options)?;
+        writer.write_all(content.as_bytes())?;
+    }
+    Ok(writer.finish()?.into_inner())
+}
+
+// Reading
+
+fn read_part<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, String> {
+    let file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(format!("cannot read {}: {}", name, e)),
+    };
+    let mut content = String::new();
+    file.take(MAX_PART_BYTES + 1)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("cannot read {}: {}", name, e))?;
+    if content.len() as u64 > MAX_PART_BYTES {
+        return Err(format!("{} is too large", name));
+    }
+    Ok(Some(content))
+}
+
+/// 元素下所有文本节点（`<t>`）的内容，跳过注音（`<rPh>`）
+fn element_text(node: roxmltree::Node) -> String {
+    node.descendants()
+        .filter(|n| n.has_tag_name("t"))
+        .filter(|n| !n.ancestors().any(|a| a.has_tag_name("rPh")))
+        .filter_map(|n| n.text())
+        .collect()
+}
+
+/// 读取工作簿中的一张工作表，优先取名为 `preferred` 的工作表，否则取第一张
+///
+/// 返回各行单元格的文本，按列位置对齐；空行保留为空数组，使下标与行号对应。
+pub fn read_sheet(data: &[u8], preferred: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(data)).map_err(|e| format!("not an XLSX file: {}", e))?;
+
+    let workbook = read_part(&mut archive, "xl/workbook.xml")?
+        .ok_or_else(|| "not an XLSX file: xl/workbook.xml is missing".to_string())?;
+    let workbook = roxmltree::Document::parse(&workbook).map_err(|e| e.to_string())?;
+    let sheets: Vec<(&str, &str)> = workbook
+        .descendants()
+        .filter(|n| n.has_tag_name("sheet"))
+        .filter_map(|n| {
+            let id = n.attributes().find(|a| a.name() == "id")?.value();
+            Some((n.attribute("name")?, id))
+        })
+        .collect();
+    let (_, relation) = sheets
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(preferred))
+        .or_else(|| sheets.first())
+        .ok_or_else(|| "the workbook has no sheets".to_string())?;
+
+    let rels = read_part(&mut archive, "xl/_rels/workbook.xml.rels")?
+        .ok_or_else(|| "not an XLSX file: workbook relationships are missing".to_string())?;
+    let rels = roxmltree::Document::parse(&rels).map_err(|e| e.to_string())?;
+    let target = rels
+        .descendants()
+        .find(|n| n.has_tag_name("Relationship") && n.attribute("Id") == Some(relation))
+        .and_then(|n| n.attribute("Target"))
+        .ok_or_else(|| format!("sheet relationship {} is missing", relation))?;
+    let path = match target.strip_prefix('/') {
+        Some(absolute) => absolute.to_string(),
+        None => format!("xl/{}", target),
+    };
+
+    let shared: Vec<String> = match read_part(&mut archive, "xl/sharedStrings.xml")? {
+        Some(xml) => roxmltree::Document::parse(&xml)
+            .map_err(|e| e.to_string())?
+            .root_element()
+            .children()
+            .filter(|n| n.has_tag_name("si"))
+            .map(element_text)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let sheet = read_part(&mut archive, &path)?.ok_or_else(|| format!("{} is missing", path))?;
+    let sheet = roxmltree::Document::parse(&sheet).map_err(|e| e.to_string())?;
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell_count = 0;
+    for row in sheet.descendants().filter(|n| n.has_tag_name("row")) {
+        let index = row
+            .attribute("r")
+            .and_then(|r| r.parse::<usize>().ok())
+            .map_or(rows.len(), |r| r.saturating_sub(1));
+        if index >= MAX_ROWS {
+            return Err(format!(
+                "row {} is beyond the sheet limit of {} rows",
+                index.saturating_add(1),
+                MAX_ROWS
+            ));
+        }
+        if index >= rows.len() {
+            rows.resize(index + 1, Vec::new());
+        }
+
+        let mut cells = Vec::new();
+        for cell in row.children().filter(|n| n.has_tag_name("c")) {
+            let column = cell
+                .attribute("r")
+                .and_then(column_index)
+                .unwrap_or(cells.len());
+            if column >= MAX_COLUMNS {
+                return Err(format!(
+                    "cell {} is beyond the sheet limit of {} columns",
+                    cell.attribute("r").unwrap_or_default(),
+                    MAX_COLUMNS
+                ));
+            }
+            let value = cell
+                .children()
+                .find(|n| n.has_tag_name("v"))
+                .and_then(|n| n.text())
+                .unwrap_or_default();
+            let text = match cell.attribute("t") {
+                Some("s") => value
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| shared.get(i).cloned())
+                    .unwrap_or_default(),
+                Some("inlineStr") => cell
+                    .children()
+                    .find(|n| n.has_tag_name("is"))
+                    .map(element_text)
+                    .unwrap_or_default(),
+                _ => value.to_string(),
+            };
+            if column >= cells.len() {
+                cell_count += column + 1 - cells.len();
+                if cell_count > MAX_CELLS {
+                    return Err(format!("the sheet has more than {} cells", MAX_CELLS));
+                }
+                cells.resize(column + 1, String::new());
+            }
+            cells[column] = text;
+        }
+        rows[index] = cells;
+    }
+    Ok(rows)
+}
+
+// Flat sheet
+
+/// 整数单元格，接受表格软件写出的 `3.0`
+fn parse_int(value: &str) -> Option<i32> {
+    let value = value.trim();
+    value.parse().ok().or_else(|| {
+        let number: f64 = value.parse().ok()?;
+        (number.fract() == 0.0 && number.abs() < f64::from(i32::MAX)).then_some(number as i32)
+    })
+}
+
+/// 时间单元格：`HH:MM` 文本，或表格软件存储的一天中的比例（如 `0.375` 即 09:00）
+fn parse_time(value: &str) -> Option<String> {
+    normalize_time(value).or_else(|| {
+        let fraction: f64 = value.trim().parse().ok()?;
+        if !(0.0..1.0).contains(&fraction) {
+            return None;
+        }
+        let minutes = (fraction * 1440.0).round() as u32 % 1440;
+        Some(format!("{:02}:{:02}", minutes / 60, minutes % 60))
+    })
+}
+
+fn flat_row(
+    cell: &dyn Fn(&str) -> String,
+) -> Result<(i32, ClientCourse, Option<ClientScheduleEntry>), String> {
+    let optional = |column: &str| Some(cell(column)).filter(|v| !v.is_empty());
+    let int = |column: &str| {
+        let value = cell(column);
+        parse_int(&value).ok_or_else(|| format!("{}: '{}' is not a whole number", column, value))
+    };
+
+    let client_id = int("client_id")?;
+    let course_id = int("course_id")?;
+    let course = ClientCourse {
+        id: course_id,
+        name: cell("course_name"),
+        teacher: optional("teacher"),
+        location: optional("location"),
+        color: optional("color"),
+        note: optional("course_note"),
+        attributes: Attributes::new(),
+    };
+    if cell("entry_id").is_empty() {
+        return Ok((client_id, course, None));
+    }
+
+    let time = |column: &str| {
+        let value = cell(column);
+        parse_time(&value)
+            .ok_or_else(|| format!("{}: '{}' is not a valid HH:MM time", column, value))
+    };
+    let entry = ClientScheduleEntry {
+        id: int("entry_id")?,
+        course_id,
+        day_of_week: int("day_of_week")?,
+        start_time: time("start_time")?,
+        end_time: time("end_time")?,
+        weeks: optional("weeks")
+            .map(|w| weeks::parse(&w))
+            .transpose()
+            .map_err(|e| format!("weeks: {}", e))?,
+        invalid_weeks: None,
+        note: optional("note"),
+        room: optional("room"),
+        attributes: Attributes::new(),
+    };
+    Ok((client_id, course, Some(entry)))
+}
+
+/// 解析平铺表，按客户端 ID 分组
+///
+/// 第一行为表头，列名不区分大小写，`client_id`、`course_id` 与 `course_name` 必需。
+/// 同一课程出现在多行时以首行为准，之后字段不一致的行记为错误；
+/// 无法解析的行整行跳过。字段取值的校验留给同步流程。
+pub fn parse_flat(rows: &[Vec<String>]) -> (Vec<FlatClient>, Vec<ImportIssue>) {
+    let mut issues = Vec::new();
+    let issue = |row: usize, reason: String| ImportIssue {
+        source: format!("row {}", row),
+        reason,
+    };
+
+    let Some(header) = rows.first() else {
+        return (Vec::new(), vec![issue(1, "the sheet is empty".to_string())]);
+    };
+    let columns: HashMap<String, usize> = header
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.trim().to_lowercase(), i))
+        .collect();
+    let missing: Vec<&str> = ["client_id", "course_id", "course_name"]
+        .into_iter()
+        .filter(|c| !columns.contains_key(*c))
+        .collect();
+    if !missing.is_empty() {
+        return (
+            Vec::new(),
+            vec![issue(1, format!("missing columns: {}", missing.join(", ")))],
+        );
+    }
+
+    let mut clients: BTreeMap<i32, FlatClient> = BTreeMap::new();
+    for (i, cells) in rows.iter().enumerate().skip(1) {
+        let row = i + 1;
+        if cells.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+        let cell = |column: &str| {
+            columns
+                .get(column)
+                .and_then(|i| cells.get(*i))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        let (client_id, course, entry) = match flat_row(&cell) {
+            Ok(parsed) => parsed,
+            Err(reason) => {
+                issues.push(issue(row, reason));
+                continue;
+            }
+        };
+        let client = clients.entry(client_id).or_insert_with(|| FlatClient {
+            client_id,
+            ..Default::default()
+        });
+        client.rows += 1;
+
+        match client.courses.iter().find(|c| c.id == course.id) {
+            Some(first) if *first != course => issues.push(issue(
+                row,
+                format!(
+                    "course_id: course {} differs from row {}",
+                    course.id, client.course_rows[&course.id]
+                ),
+            )),
+            Some(_) => {}
+            None => {
+                client.course_rows.insert(course.id, row);
+                client.courses.push(course);
+            }
+        }
+
+        if let Some(entry) = entry {
+            if let Some(first) = client.entry_rows.get(&entry.id) {
+                issues.push(issue(
+                    row,
+                    format!("entry_id: entry {} is already on row {}", entry.id, first),
+                ));
+                continue;
+            }
+            client.entry_rows.insert(entry.id, row);
+            client.entries.push(entry);
+        }
+    }
+
+    (clients.into_values().collect(), issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printing::{GridCell, GridRow};
+
+    fn client() -> ClientSheet {
+        let course = ClientCourse {
+            id: 3,
+            name: "数学 & 统计".to_string(),
+            teacher: Some("Li".to_string()),
+            location: Some("A101".to_string()),
+            color: Some("#336699".to_string()),
+            note: None,
+            attributes: Attributes::new(),
+        };
+        let unscheduled = ClientCourse {
+            id: 4,
+            name: "Art".to_string(),
+            teacher: None,
+            location: None,
+            color: None,
+            note: None,
+            attributes: Attributes::new(),
+        };
+        let entry = ClientScheduleEntry {
+            id: 7,
+            course_id: 3,
+            day_of_week: 2,
+            start_time: "08:00".to_string(),
+            end_time: "08:45".to_string(),
+            weeks: Some(vec![1, 3, 5]),
+            invalid_weeks: None,
+            note: None,
+            room: Some("B201".to_string()),
+            attributes: Attributes::new(),
+        };
+        ClientSheet {
+            client_id: 12,
+            client_name: "Class [1]".to_string(),
+            courses: vec![course, unscheduled],
+            entries: vec![entry],
+            grid: TimetableGrid {
+                title: "Class [1]".to_string(),
+                subtitle: String::new(),
+                days: vec![1, 2, 3, 4, 5],
+                rows: vec![GridRow {
+                    label: vec!["08:00-08:45".to_string()],
+                    cells: (1..=5)
+                        .map(|day| {
+                            (day == 2)
+                                .then(|| GridCell {
+                                    lines: vec!["数学 & 统计".to_string()],
+                                    color: Some((0x33, 0x66, 0x99)),
+                                })
+                                .into_iter()
+                                .collect()
+                        })
+                        .collect(),
+                }],
+            },
+        }
+    }
+
+    #[test]
+    fn test_workbook_round_trip() {
+        let data = write_workbook(&[client()]).unwrap();
+        let rows = read_sheet(&data, FLAT_SHEET).unwrap();
+        assert_eq!(rows[0], FLAT_COLUMNS);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1][3], "数学 & 统计");
+        assert_eq!(rows[1][12], "1-5单");
+        // Empty cells are not written, so the row of the unscheduled course ends at its name
+        assert_eq!(rows[2].len(), 4);
+
+        let (clients, issues) = parse_flat(&rows);
+        assert!(issues.is_empty());
+        assert_eq!(clients.len(), 1);
+        let expected = client();
+        assert_eq!(clients[0].client_id, 12);
+        assert_eq!(clients[0].courses, expected.courses);
+        assert_eq!(clients[0].entries, expected.entries);
+        assert_eq!(clients[0].entry_rows[&7], 2);
+
+        // The weekly grid sheet is named after the client, without forbidden characters
+        let grid = read_sheet(&data, "Class _1_").unwrap();
+        assert_eq!(grid[0][2], "星期二");
+        assert_eq!(grid[1][2], "数学 & 统计");
+    }
+
+    #[test]
+    fn test_parse_flat_reports_rows() {
+        let row = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let rows = vec![
+            row(&[
+                "Client_ID",
+                "course_id",
+                "course_name",
+                "entry_id",
+                "day_of_week",
+                "start_time",
+                "end_time",
+            ]),
+            row(&["1", "5", "Math", "1", "1.0", "0.375", "09:45:00"]),
+            row(&["1", "5", "Physics"]),
+            row(&["x", "5", "Math"]),
+            row(&[]),
+            row(&["1", "5", "Math", "2", "1", "8am", "09:00"]),
+            row(&["2", "6", "Art"]),
+        ];
+
+        let (clients, issues) = parse_flat(&rows);
+        assert_eq!(clients.len(), 2);
+        assert_eq!(clients[0].entries[0].day_of_week, 1);
+        assert_eq!(clients[0].entries[0].start_time, "09:00");
+        assert_eq!(clients[0].entries[0].end_time, "09:45");
+        let sources: Vec<&str> = issues.iter().map(|i| i.source.as_str()).collect();
+        assert_eq!(sources, vec!["row 3", "row 4", "row 6"]);
+        assert_eq!(issues[0].reason, "course_id: course 5 differs from row 2");
+
+        let (_, issues) = parse_flat(&[row(&["client_id", "name"])]);
+        assert_eq!(issues[0].reason, "missing columns: course_id, course_name");
+    }
+
+    #[test]
+    fn test_sheet_names() {
+        let mut used = BTreeSet::from([FLAT_SHEET.to_lowercase()]);
+        assert_eq!(sheet_name("timetable", &mut used), "timetable (2)");
+        assert_eq!(sheet_name("a/b?", &mut used), "a_b_");
+        let long = "x".repeat(40);
+        assert_eq!(sheet_name(&long, &mut used).len(), 31);
+        assert_eq!(
+            sheet_name(&long, &mut used),
+            format!("{} (2)", "x".repeat(27))
+        );
+        assert_eq!(column_name(27), "AB");
+        assert_eq!(column_index("AB12"), Some(27));
+        assert_eq!(column_index("ZZZZZZZZZZZZZZZZ1"), Some(usize::MAX - 1));
+    }
+
+    #[test]
+    fn test_read_sheet_rejects_out_of_range_references() {
+        let workbook = |sheet_data: &str| {
+            let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+            let options = zip::write::SimpleFileOptions::default();
+            let parts = [
+                (
+                    "xl/workbook.xml",
+                    r#"<workbook xmlns:r="urn:r"><sheets><sheet name="Timetable" r:id="rId1"/></sheets></workbook>"#
+                        .to_string(),
+                ),
+                (
+                    "xl/_rels/workbook.xml.rels",
+                    r#"<Relationships><Relationship Id="rId1" Target="worksheets/sheet1.xml"/></Relationships>"#
+                        .to_string(),
+                ),
+                (
+                    "xl/worksheets/sheet1.xml",
+                    format!("<worksheet><sheetData>{}</sheetData></worksheet>", sheet_data),
+                ),
+            ];
+            for (name, content) in parts {
+                zip.start_file(name, options).unwrap();
+                zip.write_all(content.as_bytes()).unwrap();
+            }
+            zip.finish().unwrap().into_inner()
+        };
+
+        let rows = read_sheet(
+            &workbook(r#"<row r="2"><c r="B2" t="inlineStr"><is><t>x</t></is></c></row>"#),
+            FLAT_SHEET,
+        )
+        .unwrap();
+        assert_eq!(rows, vec![vec![], vec![String::new(), "x".to_string()]]);
+        assert!(read_sheet(
+            &workbook(r#"<row r="4000000000"><c r="A4000000000"/></row>"#),
+            FLAT_SHEET
+        )
+        .is_err());
+        assert!(read_sheet(
+            &workbook(r#"<row r="1"><c r="ZZZZZZZZ1"/></row>"#),
+            FLAT_SHEET
+        )
+        .is_err());
+    }
+}
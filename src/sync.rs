@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// 客户端提供幂等键的请求头
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// 标记响应来自幂等缓存的响应头
+pub const IDEMPOTENT_REPLAY_HEADER: &str = "Idempotent-Replayed";
+
+/// 幂等键默认保留时长（小时），对应设置项 `sync_idempotency_retention_hours`
+pub const DEFAULT_IDEMPOTENCY_RETENTION_HOURS: i64 = 24;
+
+/// 幂等键最大长度，与 `sync_requests.idempotency_key` 列一致
+pub const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// 同步请求体上限，与 actix 的 JSON 默认上限相同
+pub const MAX_SYNC_BYTES: usize = 2 * 1024 * 1024;
+
+/// 按 client_uuid 串行化同步请求
+///
+/// 同一客户端的并发同步会依次执行，不同客户端之间互不影响。这里保证幂等键的查询与保存
+/// 不会交错；课程表写入本身由数据库中客户端行锁串行化，覆盖同步以外的所有写入方。
+#[derive(Clone)]
+pub struct SyncLockManager {
+    // client_uuid -> 该客户端的同步锁
+    locks: Arc<Mutex<HashMap<String, Arc<AsyncMutex<()>>>>>,
+}
+
+impl SyncLockManager {
+    pub fn new() -> Self {
+        Self {
+            locks: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 获取客户端的同步锁，返回的 guard 被释放前其他同步会等待
+    pub async fn lock(&self, client_uuid: &str) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            // 清理无人持有或等待的锁，避免映射无限增长
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks
+                .entry(client_uuid.to_string())
+                .or_insert_with(|| Arc::new(AsyncMutex::new(())))
+                .clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+impl Default for SyncLockManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_same_client_is_serialized() {
+        let manager = SyncLockManager::new();
+        let guard = manager.lock("client-a").await;
+
+        let waiter = {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                let _guard = manager.lock("client-a").await;
+            })
+        };
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        drop(guard);
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_different_clients_do_not_block() {
+        let manager = SyncLockManager::new();
+        let _guard = manager.lock("client-a").await;
+
+        tokio::time::timeout(Duration::from_secs(1), manager.lock("client-b"))
+            .await
+            .unwrap();
+    }
+}
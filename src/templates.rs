@@ -0,0 +1,281 @@
+use crate::models::{ClientCourse, ClientScheduleEntry, TemplateOverrides};
+use crate::timetable;
+use std::collections::{BTreeMap, HashSet};
+
+/// `template_items.item_type`
+pub const ITEM_COURSE: &str = "course";
+pub const ITEM_ENTRY: &str = "schedule_entry";
+
+/// 模板内 ID 到某个客户端上 ID 的对应关系
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ItemMapping {
+    pub courses: BTreeMap<i32, i32>,
+    pub entries: BTreeMap<i32, i32>,
+}
+
+/// 对模板套用客户端的覆盖项，结果仍使用模板内 ID
+///
+/// 被排除的条目去掉；覆盖项指向不存在的 ID 时忽略。
+pub fn apply_overrides(
+    courses: &[ClientCourse],
+    entries: &[ClientScheduleEntry],
+    overrides: &TemplateOverrides,
+) -> (Vec<ClientCourse>, Vec<ClientScheduleEntry>) {
+    let courses = courses
+        .iter()
+        .map(|course| {
+            let mut course = course.clone();
+            if let Some(o) = overrides.courses.iter().find(|o| o.id == course.id) {
+                course.name = o.name.clone().unwrap_or(course.name);
+                course.teacher = o.teacher.clone().or(course.teacher);
+                course.location = o.location.clone().or(course.location);
+                course.color = o.color.clone().or(course.color);
+                course.note = o.note.clone().or(course.note);
+            }
+            course
+        })
+        .collect();
+
+    let entries = entries
+        .iter()
+        .filter(|entry| !overrides.excluded_entries.contains(&entry.id))
+        .map(|entry| {
+            let mut entry = entry.clone();
+            if let Some(o) = overrides.schedule_entries.iter().find(|o| o.id == entry.id) {
+                entry.day_of_week = o.day_of_week.unwrap_or(entry.day_of_week);
+                entry.start_time = o.start_time.clone().unwrap_or(entry.start_time);
+                entry.end_time = o.end_time.clone().unwrap_or(entry.end_time);
+                entry.weeks = o.weeks.clone().or(entry.weeks);
+                entry.room = o.room.clone().or(entry.room);
+                entry.note = o.note.clone().or(entry.note);
+            }
+            entry
+        })
+        .collect();
+
+    (courses, entries)
+}
+
+/// 模板发布到一个客户端时的写入计划，课程与条目均已换成客户端上的 ID
+#[derive(Debug, Default)]
+pub struct PublishPlan {
+    pub courses: Vec<ClientCourse>,
+    pub entries: Vec<ClientScheduleEntry>,
+    pub removed_courses: Vec<i32>, // 模板中已删除的课程，客户端上的 ID
+    pub removed_entries: Vec<i32>,
+    pub mapping: ItemMapping,
+}
+
+/// 将模板（已套用覆盖项）对应到客户端上的 ID
+///
+/// 已发布过的模板条目沿用原 ID，新条目取不与现有 ID 冲突的负数 ID；
+/// 上次发布过、本次不再包含的条目列入删除。
+pub fn plan_publish(
+    existing_courses: &[ClientCourse],
+    existing_entries: &[ClientScheduleEntry],
+    previous: &ItemMapping,
+    courses: Vec<ClientCourse>,
+    entries: Vec<ClientScheduleEntry>,
+) -> PublishPlan {
+    let mut plan = PublishPlan::default();
+
+    let mut next_course_id = timetable::next_server_id(
+        existing_courses
+            .iter()
+            .map(|c| c.id)
+            .chain(previous.courses.values().copied()),
+    );
+    for mut course in courses {
+        let id = previous
+            .courses
+            .get(&course.id)
+            .copied()
+            .unwrap_or_else(|| {
+                let id = next_course_id;
+                next_course_id -= 1;
+                id
+            });
+        plan.mapping.courses.insert(course.id, id);
+        course.id = id;
+        plan.courses.push(course);
+    }
+
+    let mut next_entry_id = timetable::next_server_id(
+        existing_entries
+            .iter()
+            .map(|e| e.id)
+            .chain(previous.entries.values().copied()),
+    );
+    for mut entry in entries {
+        let Some(course_id) = plan.mapping.courses.get(&entry.course_id).copied() else {
+            continue;
+        };
+        let id = previous.entries.get(&entry.id).copied().unwrap_or_else(|| {
+            let id = next_entry_id;
+            next_entry_id -= 1;
+            id
+        });
+        plan.mapping.entries.insert(entry.id, id);
+        entry.id = id;
+        entry.course_id = course_id;
+        plan.entries.push(entry);
+    }
+
+    let kept_courses: HashSet<i32> = plan.mapping.courses.values().copied().collect();
+    plan.removed_courses = previous
+        .courses
+        .values()
+        .copied()
+        .filter(|id| !kept_courses.contains(id))
+        .collect();
+    let kept_entries: HashSet<i32> = plan.mapping.entries.values().copied().collect();
+    plan.removed_entries = previous
+        .entries
+        .values()
+        .copied()
+        .filter(|id| !kept_entries.contains(id))
+        .collect();
+
+    plan
+}
+
+/// 发布后客户端的完整课表：去掉删除的条目，用写入的条目替换同 ID 的旧版本
+pub fn published_timetable(
+    existing_courses: &[ClientCourse],
+    existing_entries: &[ClientScheduleEntry],
+    removed_courses: &[i32],
+    removed_entries: &[i32],
+    courses: &[ClientCourse],
+    entries: &[ClientScheduleEntry],
+) -> (Vec<ClientCourse>, Vec<ClientScheduleEntry>) {
+    let mut result_courses: BTreeMap<i32, ClientCourse> = existing_courses
+        .iter()
+        .filter(|c| !removed_courses.contains(&c.id))
+        .map(|c| (c.id, c.clone()))
+        .collect();
+    result_courses.extend(courses.iter().map(|c| (c.id, c.clone())));
+
+    // Entries of removed courses go with them
+    let mut result_entries: BTreeMap<i32, ClientScheduleEntry> = existing_entries
+        .iter()
+        .filter(|e| !removed_entries.contains(&e.id) && !removed_courses.contains(&e.course_id))
+        .map(|e| (e.id, e.clone()))
+        .collect();
+    result_entries.extend(entries.iter().map(|e| (e.id, e.clone())));
+
+    (
+        result_courses.into_values().collect(),
+        result_entries.into_values().collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Attributes, CourseOverride, EntryOverride};
+
+    fn course(id: i32, teacher: &str) -> ClientCourse {
+        ClientCourse {
+            id,
+            name: format!("Course {}", id),
+            teacher: Some(teacher.to_string()),
+            location: None,
+            color: None,
+            note: None,
+            attributes: Attributes::new(),
+        }
+    }
+
+    fn entry(id: i32, course_id: i32, start: &str) -> ClientScheduleEntry {
+        ClientScheduleEntry {
+            id,
+            course_id,
+            day_of_week: 1,
+            start_time: start.to_string(),
+            end_time: "23:00".to_string(),
+            weeks: None,
+            invalid_weeks: None,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+        }
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let overrides = TemplateOverrides {
+            courses: vec![CourseOverride {
+                id: 1,
+                name: None,
+                teacher: Some("Wang".to_string()),
+                location: None,
+                color: None,
+                note: None,
+            }],
+            schedule_entries: vec![EntryOverride {
+                id: 1,
+                day_of_week: None,
+                start_time: Some("09:00".to_string()),
+                end_time: None,
+                weeks: None,
+                room: Some("B201".to_string()),
+                note: None,
+            }],
+            excluded_entries: vec![2],
+        };
+
+        let (courses, entries) = apply_overrides(
+            &[course(1, "Li"), course(2, "Zhao")],
+            &[entry(1, 1, "08:00"), entry(2, 2, "10:00")],
+            &overrides,
+        );
+
+        assert_eq!(courses[0].teacher.as_deref(), Some("Wang"));
+        assert_eq!(courses[1].teacher.as_deref(), Some("Zhao"));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].start_time, "09:00");
+        assert_eq!(entries[0].room.as_deref(), Some("B201"));
+    }
+
+    #[test]
+    fn test_plan_publish_keeps_published_ids() {
+        let previous = ItemMapping {
+            courses: BTreeMap::from([(1, -1), (3, -2)]),
+            entries: BTreeMap::from([(1, -1), (5, -2)]),
+        };
+        let existing_courses = vec![course(7, "Li"), course(-1, "Li"), course(-2, "Li")];
+        let existing_entries = vec![entry(7, 7, "07:00"), entry(-1, -1, "08:00")];
+
+        let plan = plan_publish(
+            &existing_courses,
+            &existing_entries,
+            &previous,
+            vec![course(1, "Li"), course(2, "Zhao")],
+            vec![
+                entry(1, 1, "08:30"),
+                entry(2, 2, "10:00"),
+                entry(3, 9, "11:00"),
+            ],
+        );
+
+        let ids: Vec<i32> = plan.courses.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![-1, -3]);
+        let entries: Vec<(i32, i32)> = plan.entries.iter().map(|e| (e.id, e.course_id)).collect();
+        assert_eq!(entries, vec![(-1, -1), (-3, -3)]);
+        assert_eq!(plan.removed_courses, vec![-2]);
+        assert_eq!(plan.removed_entries, vec![-2]);
+
+        let (courses, entries) = published_timetable(
+            &existing_courses,
+            &existing_entries,
+            &plan.removed_courses,
+            &plan.removed_entries,
+            &plan.courses,
+            &plan.entries,
+        );
+        let course_ids: Vec<i32> = courses.iter().map(|c| c.id).collect();
+        assert_eq!(course_ids, vec![-3, -1, 7]);
+        let starts: Vec<&str> = entries.iter().map(|e| e.start_time.as_str()).collect();
+        assert_eq!(starts, vec!["10:00", "08:30", "07:00"]);
+    }
+}
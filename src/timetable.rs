@@ -0,0 +1,279 @@
+use crate::models::{
+    ClientCourse, ClientScheduleEntry, Course, CourseChange, CourseDiff, ScheduleEntry,
+    ScheduleEntryChange, ScheduleEntryDiff,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// 将服务器上的课程转换为客户端同步格式
+pub fn to_client_courses(courses: &[Course]) -> Vec<ClientCourse> {
+    courses
+        .iter()
+        .map(|c| ClientCourse {
+            id: c.course_id_on_client,
+            name: c.name.clone(),
+            teacher: c.teacher.clone(),
+            location: c.location.clone(),
+            color: c.color.clone(),
+            note: c.note.clone(),
+            attributes: c.attributes.clone(),
+        })
+        .collect()
+}
+
+/// 将服务器上的课程表条目转换为客户端同步格式，课程 ID 映射回客户端 ID
+pub fn to_client_entries(
+    courses: &[Course],
+    entries: &[ScheduleEntry],
+) -> Vec<ClientScheduleEntry> {
+    let course_ids: HashMap<i32, i32> = courses
+        .iter()
+        .map(|c| (c.id, c.course_id_on_client))
+        .collect();
+
+    entries
+        .iter()
+        .filter_map(|e| {
+            Some(ClientScheduleEntry {
+                id: e.entry_id_on_client,
+                course_id: *course_ids.get(&e.course_id)?,
+                day_of_week: e.day_of_week,
+                start_time: e.start_time.clone(),
+                end_time: e.end_time.clone(),
+                weeks: e.weeks.clone(),
+                invalid_weeks: None,
+                note: e.note.clone(),
+                room: e.room.clone(),
+                attributes: e.attributes.clone(),
+            })
+        })
+        .collect()
+}
+
+/// 管理端新建课程或条目时使用的客户端 ID
+///
+/// 取负数，不会与客户端自己分配的 ID 冲突。
+pub fn next_server_id(existing: impl IntoIterator<Item = i32>) -> i32 {
+    existing.into_iter().min().unwrap_or(0).min(0) - 1
+}
+
+/// 按客户端 ID 排序课程，使快照之间可以直接比较
+pub fn sort_courses(mut courses: Vec<ClientCourse>) -> Vec<ClientCourse> {
+    courses.sort_by_key(|c| c.id);
+    courses
+}
+
+/// 按客户端 ID 排序课程表条目
+pub fn sort_entries(mut entries: Vec<ClientScheduleEntry>) -> Vec<ClientScheduleEntry> {
+    entries.sort_by_key(|e| e.id);
+    entries
+}
+
+/// 按客户端 ID 比较两组条目，返回新增、删除与变更（均按 ID 排序）
+fn diff_by_id<T: Clone + PartialEq>(
+    before: &[T],
+    after: &[T],
+    id: impl Fn(&T) -> i32,
+) -> (Vec<T>, Vec<T>, Vec<(T, T)>) {
+    let before: BTreeMap<i32, &T> = before.iter().map(|item| (id(item), item)).collect();
+    let after: BTreeMap<i32, &T> = after.iter().map(|item| (id(item), item)).collect();
+
+    let added = after
+        .iter()
+        .filter(|(key, _)| !before.contains_key(key))
+        .map(|(_, item)| (*item).clone())
+        .collect();
+    let removed = before
+        .iter()
+        .filter(|(key, _)| !after.contains_key(key))
+        .map(|(_, item)| (*item).clone())
+        .collect();
+    let changed = before
+        .iter()
+        .filter_map(|(key, old)| {
+            let new = after.get(key)?;
+            (old != new).then(|| ((*old).clone(), (*new).clone()))
+        })
+        .collect();
+
+    (added, removed, changed)
+}
+
+pub fn diff_courses(before: &[ClientCourse], after: &[ClientCourse]) -> CourseDiff {
+    let (added, removed, changed) = diff_by_id(before, after, |c| c.id);
+    CourseDiff {
+        added,
+        removed,
+        changed: changed
+            .into_iter()
+            .map(|(before, after)| CourseChange { before, after })
+            .collect(),
+    }
+}
+
+pub fn diff_entries(
+    before: &[ClientScheduleEntry],
+    after: &[ClientScheduleEntry],
+) -> ScheduleEntryDiff {
+    let (added, removed, changed) = diff_by_id(before, after, |e| e.id);
+    ScheduleEntryDiff {
+        added,
+        removed,
+        changed: changed
+            .into_iter()
+            .map(|(before, after)| ScheduleEntryChange { before, after })
+            .collect(),
+    }
+}
+
+/// 同步时对单个条目执行的写入
+#[derive(Debug, Clone, PartialEq)]
+pub enum Upsert<T> {
+    Insert(T),
+    Update { before: T, after: T },
+}
+
+/// 同步计划：真实同步按它写库，预演直接返回它
+#[derive(Debug, Default)]
+pub struct SyncPlan {
+    pub courses: Vec<Upsert<ClientCourse>>,
+    pub entries: Vec<Upsert<ClientScheduleEntry>>,
+    pub skipped_entries: Vec<ClientScheduleEntry>,
+}
+
+/// 按客户端 ID 将同步数据与已有数据匹配
+///
+/// 已存在的 ID 更新，其余插入；同一次同步中重复的 ID 以后出现者为准。
+/// 条目所属课程既不在已有数据中也不在本次同步中时跳过。
+pub fn plan_sync(
+    existing_courses: &[ClientCourse],
+    existing_entries: &[ClientScheduleEntry],
+    courses: Vec<ClientCourse>,
+    entries: Vec<ClientScheduleEntry>,
+) -> SyncPlan {
+    let mut plan = SyncPlan::default();
+
+    let mut current_courses: HashMap<i32, ClientCourse> =
+        existing_courses.iter().map(|c| (c.id, c.clone())).collect();
+    for course in courses {
+        let action = match current_courses.insert(course.id, course.clone()) {
+            Some(before) => Upsert::Update {
+                before,
+                after: course,
+            },
+            None => Upsert::Insert(course),
+        };
+        plan.courses.push(action);
+    }
+
+    let known_courses: HashSet<i32> = current_courses.keys().copied().collect();
+    let mut current_entries: HashMap<i32, ClientScheduleEntry> =
+        existing_entries.iter().map(|e| (e.id, e.clone())).collect();
+    for entry in entries {
+        if !known_courses.contains(&entry.course_id) {
+            plan.skipped_entries.push(entry);
+            continue;
+        }
+        let action = match current_entries.insert(entry.id, entry.clone()) {
+            Some(before) => Upsert::Update {
+                before,
+                after: entry,
+            },
+            None => Upsert::Insert(entry),
+        };
+        plan.entries.push(action);
+    }
+
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Attributes;
+
+    fn entry(id: i32, day: i32, start: &str) -> ClientScheduleEntry {
+        ClientScheduleEntry {
+            id,
+            course_id: 1,
+            day_of_week: day,
+            start_time: start.to_string(),
+            end_time: "23:00".to_string(),
+            weeks: None,
+            invalid_weeks: None,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let before = vec![
+            entry(1, 1, "08:00"),
+            entry(2, 2, "09:00"),
+            entry(3, 3, "10:00"),
+        ];
+        let after = vec![
+            entry(1, 1, "08:00"),
+            entry(2, 2, "10:00"),
+            entry(4, 4, "11:00"),
+        ];
+
+        let diff = diff_entries(&before, &after);
+
+        assert_eq!(diff.added, vec![entry(4, 4, "11:00")]);
+        assert_eq!(diff.removed, vec![entry(3, 3, "10:00")]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before.start_time, "09:00");
+        assert_eq!(diff.changed[0].after.start_time, "10:00");
+    }
+
+    #[test]
+    fn test_plan_sync_matches_by_client_id() {
+        let course = ClientCourse {
+            id: 1,
+            name: "数学".to_string(),
+            teacher: None,
+            location: None,
+            color: None,
+            note: None,
+            attributes: Attributes::new(),
+        };
+        let mut orphan = entry(3, 3, "10:00");
+        orphan.course_id = 9;
+
+        let plan = plan_sync(
+            &[],
+            &[entry(1, 1, "08:00")],
+            vec![course.clone()],
+            vec![entry(1, 1, "09:00"), entry(2, 2, "08:00"), orphan.clone()],
+        );
+
+        assert_eq!(plan.courses, vec![Upsert::Insert(course)]);
+        assert_eq!(
+            plan.entries,
+            vec![
+                Upsert::Update {
+                    before: entry(1, 1, "08:00"),
+                    after: entry(1, 1, "09:00"),
+                },
+                Upsert::Insert(entry(2, 2, "08:00")),
+            ]
+        );
+        assert_eq!(plan.skipped_entries, vec![orphan]);
+    }
+
+    #[test]
+    fn test_diff_of_identical_timetables_is_empty() {
+        let entries = vec![entry(1, 1, "08:00")];
+        let diff = diff_entries(&entries, &entries);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_next_server_id_is_negative() {
+        assert_eq!(next_server_id([]), -1);
+        assert_eq!(next_server_id([3, 7]), -1);
+        assert_eq!(next_server_id([3, -2, -1]), -3);
+    }
+}
@@ -0,0 +1,760 @@
+use crate::models::{
+    Attributes, ClientCourse, ClientScheduleEntry, KeptServerItem, ScheduleEntry, SyncRejection,
+};
+use crate::weeks;
+use std::collections::HashSet;
+
+/// 周次的绝对上限，学期长度不能超过它
+pub const MAX_WEEK_NUMBER: i32 = 52;
+
+/// 扩展属性的最大数量与属性名最大长度
+pub const MAX_ATTRIBUTES: usize = 32;
+pub const MAX_ATTRIBUTE_KEY_LEN: usize = 64;
+
+/// 地点、教室等文本字段的最大长度，与数据库列一致
+pub const MAX_LOCATION_LEN: usize = 255;
+
+/// 课程名称与教师的最大长度，与数据库列一致
+pub const MAX_NAME_LEN: usize = 255;
+
+/// 课程与条目的来源：客户端同步，或管理端创建、修改
+pub const ORIGIN_CLIENT: &str = "client";
+pub const ORIGIN_SERVER: &str = "server";
+
+/// 校验 `HH:MM` 格式并返回自 0 点起的分钟数
+pub fn parse_hhmm(value: &str) -> Option<u32> {
+    let bytes = value.as_bytes();
+    if bytes.len() != 5 || bytes[2] != b':' {
+        return None;
+    }
+    let digits = [bytes[0], bytes[1], bytes[3], bytes[4]];
+    if !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let hour = u32::from(bytes[0] - b'0') * 10 + u32::from(bytes[1] - b'0');
+    let minute = u32::from(bytes[3] - b'0') * 10 + u32::from(bytes[4] - b'0');
+    if hour > 23 || minute > 59 {
+        return None;
+    }
+
+    Some(hour * 60 + minute)
+}
+
+/// 校验 `#RGB` 或 `#RRGGBB` 格式的颜色
+pub fn is_hex_color(value: &str) -> bool {
+    match value.strip_prefix('#') {
+        Some(hex) => {
+            (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+/// 两个周次列表是否有交集，`None` 表示每周都上
+pub fn weeks_intersect(a: Option<&[i32]>, b: Option<&[i32]>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.iter().any(|week| b.contains(week)),
+        _ => true,
+    }
+}
+
+fn validate_attributes(item_type: &str, id: i32, attributes: &Attributes) -> Vec<SyncRejection> {
+    let mut errors = Vec::new();
+
+    if attributes.len() > MAX_ATTRIBUTES {
+        errors.push(reject(
+            item_type,
+            id,
+            "attributes",
+            format!("at most {} attributes are allowed", MAX_ATTRIBUTES),
+        ));
+    }
+    for key in attributes.keys() {
+        if key.trim().is_empty() || key.len() > MAX_ATTRIBUTE_KEY_LEN {
+            errors.push(reject(
+                item_type,
+                id,
+                "attributes",
+                format!(
+                    "attribute name '{}' must be 1-{} characters",
+                    key, MAX_ATTRIBUTE_KEY_LEN
+                ),
+            ));
+        }
+    }
+
+    errors
+}
+
+fn validate_length(
+    item_type: &str,
+    id: i32,
+    field: &str,
+    value: Option<&str>,
+    max: usize,
+) -> Option<SyncRejection> {
+    match value {
+        Some(value) if value.chars().count() > max => Some(reject(
+            item_type,
+            id,
+            field,
+            format!("must be at most {} characters", max),
+        )),
+        _ => None,
+    }
+}
+
+fn reject(item_type: &str, id: i32, field: &str, reason: impl Into<String>) -> SyncRejection {
+    SyncRejection {
+        item_type: item_type.to_string(),
+        id,
+        field: field.to_string(),
+        reason: reason.into(),
+    }
+}
+
+pub fn validate_course(course: &ClientCourse) -> Vec<SyncRejection> {
+    let mut errors = Vec::new();
+
+    if course.name.trim().is_empty() {
+        errors.push(reject("course", course.id, "name", "must not be empty"));
+    }
+    if let Some(color) = &course.color {
+        if !is_hex_color(color) {
+            errors.push(reject(
+                "course",
+                course.id,
+                "color",
+                format!("'{}' is not a #RGB or #RRGGBB hex color", color),
+            ));
+        }
+    }
+    errors.extend(validate_length(
+        "course",
+        course.id,
+        "name",
+        Some(&course.name),
+        MAX_NAME_LEN,
+    ));
+    errors.extend(validate_length(
+        "course",
+        course.id,
+        "teacher",
+        course.teacher.as_deref(),
+        MAX_NAME_LEN,
+    ));
+    errors.extend(validate_length(
+        "course",
+        course.id,
+        "location",
+        course.location.as_deref(),
+        MAX_LOCATION_LEN,
+    ));
+    errors.extend(validate_attributes("course", course.id, &course.attributes));
+
+    errors
+}
+
+/// `max_week` 为学期周数，周次须在 1..=max_week 之内
+pub fn validate_schedule_entry(entry: &ClientScheduleEntry, max_week: i32) -> Vec<SyncRejection> {
+    let mut errors = Vec::new();
+
+    if !(1..=7).contains(&entry.day_of_week) {
+        errors.push(reject(
+            "schedule_entry",
+            entry.id,
+            "day_of_week",
+            format!("{} is not between 1 and 7", entry.day_of_week),
+        ));
+    }
+
+    let start = parse_hhmm(&entry.start_time);
+    let end = parse_hhmm(&entry.end_time);
+    if start.is_none() {
+        errors.push(reject(
+            "schedule_entry",
+            entry.id,
+            "start_time",
+            format!("'{}' is not a valid HH:MM time", entry.start_time),
+        ));
+    }
+    if end.is_none() {
+        errors.push(reject(
+            "schedule_entry",
+            entry.id,
+            "end_time",
+            format!("'{}' is not a valid HH:MM time", entry.end_time),
+        ));
+    }
+    if let (Some(start), Some(end)) = (start, end) {
+        if start >= end {
+            errors.push(reject(
+                "schedule_entry",
+                entry.id,
+                "end_time",
+                format!(
+                    "end time {} must be after start time {}",
+                    entry.end_time, entry.start_time
+                ),
+            ));
+        }
+    }
+
+    if let Some(expr) = &entry.invalid_weeks {
+        if let Err(reason) = weeks::parse(expr) {
+            errors.push(reject("schedule_entry", entry.id, "weeks", reason));
+        }
+    }
+    if let Some(weeks) = &entry.weeks {
+        if weeks.is_empty() {
+            errors.push(reject(
+                "schedule_entry",
+                entry.id,
+                "weeks",
+                "must not be empty; omit it to mean every week",
+            ));
+        }
+        let invalid: Vec<i32> = weeks
+            .iter()
+            .copied()
+            .filter(|week| !(1..=max_week).contains(week))
+            .collect();
+        if !invalid.is_empty() {
+            errors.push(reject(
+                "schedule_entry",
+                entry.id,
+                "weeks",
+                format!(
+                    "weeks {} are not between 1 and {}",
+                    weeks::format(&invalid),
+                    max_week
+                ),
+            ));
+        }
+    }
+
+    errors.extend(validate_length(
+        "schedule_entry",
+        entry.id,
+        "room",
+        entry.room.as_deref(),
+        MAX_LOCATION_LEN,
+    ));
+    errors.extend(validate_attributes(
+        "schedule_entry",
+        entry.id,
+        &entry.attributes,
+    ));
+
+    errors
+}
+
+/// 同步数据校验结果：可写入的条目与被拒绝条目的报告
+#[derive(Debug, Default)]
+pub struct ValidatedSync {
+    pub courses: Vec<ClientCourse>,
+    pub entries: Vec<ClientScheduleEntry>,
+    pub rejected: Vec<SyncRejection>,
+    /// 由管理端维护而未应用的条目，不算作拒绝
+    pub kept_server_version: Vec<KeptServerItem>,
+}
+
+/// 校验同步数据，不合法的条目会被剔除并记录原因
+///
+/// `existing_course_ids` 是服务器上该客户端已有课程的客户端 ID，
+/// `existing_entries` 用于检测与未在本次同步中更新的条目之间的时间冲突，
+/// `max_week` 为学期周数。
+pub fn validate_sync_payload(
+    courses: Vec<ClientCourse>,
+    entries: Vec<ClientScheduleEntry>,
+    existing_course_ids: &HashSet<i32>,
+    existing_entries: &[ScheduleEntry],
+    max_week: i32,
+) -> ValidatedSync {
+    let mut result = ValidatedSync::default();
+
+    // Courses
+    let mut seen_courses = HashSet::new();
+    let mut rejected_courses = HashSet::new();
+    for course in courses {
+        let mut errors = validate_course(&course);
+        if !seen_courses.insert(course.id) {
+            errors.push(reject(
+                "course",
+                course.id,
+                "id",
+                "duplicate course id in payload",
+            ));
+        }
+
+        if errors.is_empty() {
+            result.courses.push(course);
+        } else {
+            // A duplicate does not invalidate the first, accepted copy
+            if !result.courses.iter().any(|c| c.id == course.id) {
+                rejected_courses.insert(course.id);
+            }
+            result.rejected.extend(errors);
+        }
+    }
+
+    let known_courses: HashSet<i32> = existing_course_ids
+        .iter()
+        .chain(result.courses.iter().map(|c| &c.id))
+        .copied()
+        .collect();
+
+    // Schedule entries
+    let mut seen_entries = HashSet::new();
+    for entry in entries {
+        let mut errors = validate_schedule_entry(&entry, max_week);
+        if !seen_entries.insert(entry.id) {
+            errors.push(reject(
+                "schedule_entry",
+                entry.id,
+                "id",
+                "duplicate schedule entry id in payload",
+            ));
+        }
+        if rejected_courses.contains(&entry.course_id) {
+            errors.push(reject(
+                "schedule_entry",
+                entry.id,
+                "course_id",
+                format!("course {} was rejected", entry.course_id),
+            ));
+        } else if !known_courses.contains(&entry.course_id) {
+            errors.push(reject(
+                "schedule_entry",
+                entry.id,
+                "course_id",
+                format!("unknown course {}", entry.course_id),
+            ));
+        }
+
+        if errors.is_empty() {
+            if let Some(other) = find_overlap(&entry, &result.entries, existing_entries) {
+                errors.push(reject(
+                    "schedule_entry",
+                    entry.id,
+                    "start_time",
+                    format!("overlaps with schedule entry {}", other),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            result.entries.push(entry);
+        } else {
+            result.rejected.extend(errors);
+        }
+    }
+
+    result
+}
+
+/// 剔除服务器上由管理端维护的课程与条目，避免客户端同步覆盖它们
+///
+/// 参数为来源是 server 的课程、条目的客户端 ID，被剔除的条目记入 `kept_server_version`。
+pub fn skip_server_owned(
+    validated: &mut ValidatedSync,
+    server_course_ids: &HashSet<i32>,
+    server_entry_ids: &HashSet<i32>,
+) {
+    let kept = |item_type: &str, id: i32| KeptServerItem {
+        item_type: item_type.to_string(),
+        id,
+    };
+    let (courses, skipped): (Vec<_>, Vec<_>) = std::mem::take(&mut validated.courses)
+        .into_iter()
+        .partition(|c| !server_course_ids.contains(&c.id));
+    validated.courses = courses;
+    validated
+        .kept_server_version
+        .extend(skipped.iter().map(|c| kept("course", c.id)));
+
+    let (entries, skipped): (Vec<_>, Vec<_>) = std::mem::take(&mut validated.entries)
+        .into_iter()
+        .partition(|e| !server_entry_ids.contains(&e.id));
+    validated.entries = entries;
+    validated
+        .kept_server_version
+        .extend(skipped.iter().map(|e| kept("schedule_entry", e.id)));
+}
+
+/// 剔除管理端已删除的课程与条目，避免过期的客户端同步把它们加回来
+///
+/// 参数为已删除且未在服务器上重新出现的课程、条目的客户端 ID；
+/// 已删除课程下的条目一并剔除，同样记入 `kept_server_version`。
+pub fn skip_server_deleted(
+    validated: &mut ValidatedSync,
+    deleted_course_ids: &HashSet<i32>,
+    deleted_entry_ids: &HashSet<i32>,
+) {
+    let entry_ids = validated
+        .entries
+        .iter()
+        .filter(|e| deleted_entry_ids.contains(&e.id) || deleted_course_ids.contains(&e.course_id))
+        .map(|e| e.id)
+        .collect();
+    skip_server_owned(validated, deleted_course_ids, &entry_ids);
+}
+
+/// 校验管理端创建或修改的条目，包括与该客户端其他条目的时间冲突
+pub fn validate_entry_change(
+    entry: &ClientScheduleEntry,
+    existing: &[ScheduleEntry],
+    max_week: i32,
+) -> Vec<SyncRejection> {
+    let mut errors = validate_schedule_entry(entry, max_week);
+    if errors.is_empty() {
+        if let Some(other) = find_overlap(entry, &[], existing) {
+            errors.push(reject(
+                "schedule_entry",
+                entry.id,
+                "start_time",
+                format!("overlaps with schedule entry {}", other),
+            ));
+        }
+    }
+    errors
+}
+
+/// 将被拒绝的条目合并为一条 `类型 ID 字段: 原因` 形式的消息
+pub fn describe(rejected: &[SyncRejection]) -> String {
+    rejected
+        .iter()
+        .map(|r| format!("{} {} {}: {}", r.item_type, r.id, r.field, r.reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// 将校验错误合并为一条 `字段: 原因` 形式的消息，没有错误时返回 `Ok`
+pub fn check(errors: Vec<SyncRejection>) -> Result<(), String> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(errors
+        .iter()
+        .map(|e| format!("{}: {}", e.field, e.reason))
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// 查找与 `entry` 在同一天、时间段重叠且周次相交的条目，返回其客户端 ID
+fn find_overlap(
+    entry: &ClientScheduleEntry,
+    accepted: &[ClientScheduleEntry],
+    existing: &[ScheduleEntry],
+) -> Option<i32> {
+    let (start, end) = (parse_hhmm(&entry.start_time)?, parse_hhmm(&entry.end_time)?);
+    let overlaps = |day: i32, other_start: &str, other_end: &str, weeks: Option<&[i32]>| {
+        let (Some(other_start), Some(other_end)) = (parse_hhmm(other_start), parse_hhmm(other_end))
+        else {
+            return false;
+        };
+        day == entry.day_of_week
+            && start < other_end
+            && other_start < end
+            && weeks_intersect(entry.weeks.as_deref(), weeks)
+    };
+
+    if let Some(other) = accepted.iter().find(|other| {
+        overlaps(
+            other.day_of_week,
+            &other.start_time,
+            &other.end_time,
+            other.weeks.as_deref(),
+        )
+    }) {
+        return Some(other.id);
+    }
+
+    // Entries in this payload replace their stored version, so only compare
+    // against stored entries that are not being updated
+    let updated: HashSet<i32> = accepted
+        .iter()
+        .map(|e| e.id)
+        .chain(std::iter::once(entry.id))
+        .collect();
+    existing
+        .iter()
+        .filter(|other| !updated.contains(&other.entry_id_on_client))
+        .find(|other| {
+            overlaps(
+                other.day_of_week,
+                &other.start_time,
+                &other.end_time,
+                other.weeks.as_deref(),
+            )
+        })
+        .map(|other| other.entry_id_on_client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        id: i32,
+        day: i32,
+        start: &str,
+        end: &str,
+        weeks: Option<Vec<i32>>,
+    ) -> ClientScheduleEntry {
+        ClientScheduleEntry {
+            id,
+            course_id: 1,
+            day_of_week: day,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            weeks,
+            invalid_weeks: None,
+            note: None,
+            room: None,
+            attributes: Attributes::new(),
+        }
+    }
+
+    fn course(id: i32, name: &str, color: Option<&str>) -> ClientCourse {
+        ClientCourse {
+            id,
+            name: name.to_string(),
+            teacher: None,
+            location: None,
+            color: color.map(str::to_string),
+            note: None,
+            attributes: Attributes::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_hhmm() {
+        assert_eq!(parse_hhmm("08:05"), Some(485));
+        assert_eq!(parse_hhmm("23:59"), Some(1439));
+        assert_eq!(parse_hhmm("24:00"), None);
+        assert_eq!(parse_hhmm("8:05"), None);
+        assert_eq!(parse_hhmm("08-05"), None);
+    }
+
+    #[test]
+    fn test_is_hex_color() {
+        assert!(is_hex_color("#1a2B3c"));
+        assert!(is_hex_color("#fff"));
+        assert!(!is_hex_color("1a2b3c"));
+        assert!(!is_hex_color("#12345g"));
+    }
+
+    #[test]
+    fn test_long_course_texts_are_rejected() {
+        let long = "课".repeat(MAX_NAME_LEN + 1);
+        let too_long = ClientCourse {
+            teacher: Some(long.clone()),
+            ..course(1, &long, None)
+        };
+        let fields: Vec<String> = validate_course(&too_long)
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["name", "teacher"]);
+
+        let fitting = "课".repeat(MAX_NAME_LEN);
+        assert!(validate_course(&course(1, &fitting, None)).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_entry_fields_are_reported() {
+        let errors = validate_schedule_entry(
+            &entry(1, 8, "10:00", "09:00", Some(vec![0, 3])),
+            MAX_WEEK_NUMBER,
+        );
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["day_of_week", "end_time", "weeks"]);
+    }
+
+    #[test]
+    fn test_weeks_beyond_semester_are_rejected() {
+        let errors =
+            validate_schedule_entry(&entry(1, 1, "08:00", "08:45", Some(vec![19, 21, 22])), 20);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].reason, "weeks 21-22 are not between 1 and 20");
+    }
+
+    #[test]
+    fn test_payload_rejects_items_individually() {
+        let result = validate_sync_payload(
+            vec![
+                course(1, "Math", Some("#ff0000")),
+                course(2, "", Some("red")),
+            ],
+            vec![
+                entry(10, 1, "08:00", "08:45", None),
+                entry(11, 1, "08:30", "09:15", Some(vec![1, 2])),
+                ClientScheduleEntry {
+                    course_id: 2,
+                    ..entry(12, 2, "08:00", "08:45", None)
+                },
+                ClientScheduleEntry {
+                    course_id: 99,
+                    ..entry(13, 3, "08:00", "08:45", None)
+                },
+                entry(14, 1, "08:30", "09:15", Some(vec![])),
+            ],
+            &HashSet::new(),
+            &[],
+            MAX_WEEK_NUMBER,
+        );
+
+        assert_eq!(result.courses.len(), 1);
+        assert_eq!(result.entries.len(), 1);
+        let rejected: Vec<(i32, &str)> = result
+            .rejected
+            .iter()
+            .map(|r| (r.id, r.field.as_str()))
+            .collect();
+        assert_eq!(
+            rejected,
+            vec![
+                (2, "name"),
+                (2, "color"),
+                (11, "start_time"),
+                (12, "course_id"),
+                (13, "course_id"),
+                (14, "weeks"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bad_week_expression_rejects_only_its_entry() {
+        let request: crate::models::SyncRequest = serde_json::from_str(
+            r#"{
+                "client_uuid": "c1",
+                "courses": [{"id": 1, "name": "Math"}],
+                "schedule_entries": [
+                    {"id": 10, "course_id": 1, "day_of_week": 1,
+                     "start_time": "08:00", "end_time": "08:45", "weeks": "8-1"},
+                    {"id": 11, "course_id": 1, "day_of_week": 2,
+                     "start_time": "08:00", "end_time": "08:45", "weeks": "1-4"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let validated = validate_sync_payload(
+            request.courses,
+            request.schedule_entries,
+            &HashSet::new(),
+            &[],
+            MAX_WEEK_NUMBER,
+        );
+        assert_eq!(validated.entries.len(), 1);
+        assert_eq!(validated.entries[0].weeks, Some(vec![1, 2, 3, 4]));
+        assert_eq!(validated.rejected.len(), 1);
+        assert_eq!(
+            (
+                validated.rejected[0].id,
+                validated.rejected[0].field.as_str()
+            ),
+            (10, "weeks")
+        );
+    }
+
+    #[test]
+    fn test_server_owned_items_are_skipped() {
+        let mut validated = validate_sync_payload(
+            vec![course(1, "Math", None), course(2, "Physics", None)],
+            vec![
+                entry(10, 1, "08:00", "08:45", None),
+                entry(11, 2, "08:00", "08:45", None),
+            ],
+            &HashSet::new(),
+            &[],
+            MAX_WEEK_NUMBER,
+        );
+        skip_server_owned(&mut validated, &HashSet::from([2]), &HashSet::from([10]));
+
+        assert_eq!(validated.courses.len(), 1);
+        assert_eq!(validated.entries.len(), 1);
+        assert!(validated.rejected.is_empty());
+        let kept: Vec<(&str, i32)> = validated
+            .kept_server_version
+            .iter()
+            .map(|k| (k.item_type.as_str(), k.id))
+            .collect();
+        assert_eq!(kept, vec![("course", 2), ("schedule_entry", 10)]);
+    }
+
+    #[test]
+    fn test_deleted_items_stay_deleted() {
+        // A stale client still has course 2 with entry 11, and entry 10 deleted on the server
+        let mut validated = validate_sync_payload(
+            vec![course(1, "Math", None), course(2, "Physics", None)],
+            vec![
+                entry(10, 1, "08:00", "08:45", None),
+                ClientScheduleEntry {
+                    course_id: 2,
+                    ..entry(11, 2, "08:00", "08:45", None)
+                },
+                entry(12, 1, "10:00", "10:45", None),
+            ],
+            &HashSet::new(),
+            &[],
+            MAX_WEEK_NUMBER,
+        );
+        skip_server_deleted(&mut validated, &HashSet::from([2]), &HashSet::from([10]));
+
+        let ids = |v: &ValidatedSync| -> (Vec<i32>, Vec<i32>) {
+            (
+                v.courses.iter().map(|c| c.id).collect(),
+                v.entries.iter().map(|e| e.id).collect(),
+            )
+        };
+        assert_eq!(ids(&validated), (vec![1], vec![12]));
+        assert!(validated.rejected.is_empty());
+        let kept: Vec<(&str, i32)> = validated
+            .kept_server_version
+            .iter()
+            .map(|k| (k.item_type.as_str(), k.id))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                ("course", 2),
+                ("schedule_entry", 10),
+                ("schedule_entry", 11)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_joins_field_errors() {
+        let errors = validate_course(&course(-1, " ", Some("blue")));
+        assert_eq!(
+            check(errors),
+            Err(
+                "name: must not be empty; color: 'blue' is not a #RGB or #RRGGBB hex color"
+                    .to_string()
+            )
+        );
+        assert_eq!(check(Vec::new()), Ok(()));
+    }
+
+    #[test]
+    fn test_disjoint_weeks_do_not_overlap() {
+        let result = validate_sync_payload(
+            vec![course(1, "Math", None)],
+            vec![
+                entry(1, 1, "08:00", "08:45", Some(vec![1, 3, 5])),
+                entry(2, 1, "08:00", "08:45", Some(vec![2, 4, 6])),
+            ],
+            &HashSet::new(),
+            &[],
+            MAX_WEEK_NUMBER,
+        );
+
+        assert!(result.rejected.is_empty());
+        assert_eq!(result.entries.len(), 2);
+    }
+}
@@ -33,6 +33,21 @@ pub enum WSMessage {
         client_uuid: Uuid,
         client_type: String, // "client" 或 "lms"
     },
+    /// 紧急广播，显示屏应覆盖其他内容全屏显示，直到确认或解除
+    #[serde(rename = "emergency")]
+    Emergency {
+        alert_id: i32,
+        kind: String,
+        message: String,
+        drill: bool,
+        started_at: chrono::NaiveDateTime,
+    },
+    /// 解除警报
+    #[serde(rename = "all_clear")]
+    AllClear { alert_id: i32 },
+    /// 客户端确认已显示警报
+    #[serde(rename = "acknowledge")]
+    Acknowledge { alert_id: i32 },
 }
 
 /// WebSocket 连接管理器
@@ -40,12 +55,15 @@ pub enum WSMessage {
 pub struct WSConnectionManager {
     // client_uuid -> WebSocket Actor Address
     connections: Arc<Mutex<HashMap<Uuid, Addr<WSConnection>>>>,
+    // 收到、尚未写入数据库的警报确认：(client_uuid, alert_id)
+    acknowledgements: Arc<Mutex<Vec<(Uuid, i32)>>>,
 }
 
 impl WSConnectionManager {
     pub fn new() -> Self {
         Self {
             connections: Arc::new(Mutex::new(HashMap::new())),
+            acknowledgements: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -68,6 +86,27 @@ impl WSConnectionManager {
         }
     }
 
+    /// 向多个客户端发送同一命令，返回在线并已发送的客户端数
+    pub fn send_command(
+        &self,
+        targets: &[Uuid],
+        command: &str,
+        params: serde_json::Value,
+    ) -> usize {
+        targets
+            .iter()
+            .filter(|target| {
+                let msg = WSMessage::Command {
+                    target_client: **target,
+                    request_id: Uuid::new_v4().to_string(),
+                    command: command.to_string(),
+                    params: params.clone(),
+                };
+                self.send_to_client(**target, msg).is_ok()
+            })
+            .count()
+    }
+
     pub fn get_online_count(&self) -> usize {
         self.connections.lock().unwrap().len()
     }
@@ -75,6 +114,15 @@ impl WSConnectionManager {
     pub fn get_online_clients(&self) -> Vec<Uuid> {
         self.connections.lock().unwrap().keys().copied().collect()
     }
+
+    pub fn record_acknowledgement(&self, uuid: Uuid, alert_id: i32) {
+        self.acknowledgements.lock().unwrap().push((uuid, alert_id));
+    }
+
+    /// 取出所有待写入的警报确认
+    pub fn take_acknowledgements(&self) -> Vec<(Uuid, i32)> {
+        std::mem::take(&mut *self.acknowledgements.lock().unwrap())
+    }
 }
 
 impl Default for WSConnectionManager {
@@ -188,6 +236,16 @@ impl WSConnection {
                     ctx.text(json);
                 }
             }
+            WSMessage::Acknowledge { alert_id } => match self.uuid {
+                Some(uuid) => {
+                    info!("Emergency alert {} acknowledged by {}", alert_id, uuid);
+                    self.manager.record_acknowledgement(uuid, alert_id);
+                }
+                None => warn!("Acknowledgement from an unregistered connection ignored"),
+            },
+            WSMessage::Emergency { .. } | WSMessage::AllClear { .. } => {
+                warn!("Emergency messages are only sent by the server");
+            }
         }
     }
 }
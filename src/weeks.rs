@@ -0,0 +1,331 @@
+use crate::validation::MAX_WEEK_NUMBER;
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+
+/// 未配置学期长度时使用的周数
+pub const DEFAULT_SEMESTER_WEEKS: i32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Parity {
+    All,
+    Odd,
+    Even,
+}
+
+impl Parity {
+    fn accepts(&self, week: i32) -> bool {
+        match self {
+            Parity::All => true,
+            Parity::Odd => week % 2 == 1,
+            Parity::Even => week % 2 == 0,
+        }
+    }
+}
+
+/// 单双周关键字，较长的写法在前以免被较短的前缀截断
+const PARITY_KEYWORDS: &[(&str, Parity)] = &[
+    ("单周", Parity::Odd),
+    ("双周", Parity::Even),
+    ("单", Parity::Odd),
+    ("双", Parity::Even),
+    ("single", Parity::Odd),
+    ("double", Parity::Even),
+    ("odd", Parity::Odd),
+    ("even", Parity::Even),
+];
+
+/// 可忽略的修饰词
+const FILLER_WORDS: &[&str] = &["weeks", "week", "第", "周", "(", ")", "[", "]"];
+
+/// 解析周次表达式，返回排序去重后的周次
+///
+/// 支持 `1-8,10,12-16`、`1-15单`、`单周1-15`、`odd 1-15`、`single weeks 1-15`、
+/// `第2-16周(双)` 等写法。单双周关键字作用于所在的逗号分段；
+/// 若出现在第一个数字之前，则作用于整个表达式。
+pub fn parse(expr: &str) -> Result<Vec<i32>, String> {
+    let normalized: String = expr
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            '，' | '、' | ';' | '；' => ',',
+            '－' | '—' | '–' | '~' | '～' | '至' | '到' => '-',
+            '（' => '(',
+            '）' => ')',
+            c => c,
+        })
+        .collect();
+
+    let global_parity = match normalized.find(|c: char| c.is_ascii_digit()) {
+        Some(first_digit) => take_parity(&normalized[..first_digit])?.1,
+        None => Parity::All,
+    };
+
+    let mut weeks = Vec::new();
+    for segment in normalized.split(',') {
+        if segment.trim().is_empty() {
+            continue;
+        }
+        let (rest, parity) = take_parity(segment)?;
+        let parity = if parity == Parity::All {
+            global_parity
+        } else {
+            parity
+        };
+        let (start, end) = parse_range(&rest, segment.trim())?;
+        weeks.extend((start..=end).filter(|week| parity.accepts(*week)));
+    }
+
+    weeks.sort_unstable();
+    weeks.dedup();
+    if weeks.is_empty() {
+        return Err(format!("week expression '{}' contains no weeks", expr));
+    }
+    Ok(weeks)
+}
+
+/// 去除单双周关键字与修饰词，返回剩余文本与单双周
+fn take_parity(segment: &str) -> Result<(String, Parity), String> {
+    let mut rest = segment.to_string();
+    let mut parity = Parity::All;
+    for (keyword, keyword_parity) in PARITY_KEYWORDS {
+        if rest.contains(keyword) {
+            if parity != Parity::All && parity != *keyword_parity {
+                return Err(format!("'{}' mixes odd and even weeks", segment.trim()));
+            }
+            parity = *keyword_parity;
+            rest = rest.replace(keyword, "");
+        }
+    }
+    for word in FILLER_WORDS {
+        rest = rest.replace(word, "");
+    }
+    Ok((rest, parity))
+}
+
+fn parse_range(text: &str, segment: &str) -> Result<(i32, i32), String> {
+    let text: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let number = |value: &str| {
+        value
+            .parse::<i32>()
+            .map_err(|_| format!("'{}' is not a valid week range", segment))
+    };
+
+    let (start, end) = match text.split_once('-') {
+        Some((start, end)) => (number(start)?, number(end)?),
+        None => {
+            let week = number(&text)?;
+            (week, week)
+        }
+    };
+    if start > end {
+        return Err(format!("'{}' ends before it starts", segment));
+    }
+    // 展开前检查范围，避免 `1-2000000000` 之类的表达式占满内存
+    if start < 1 || end > MAX_WEEK_NUMBER {
+        return Err(format!(
+            "'{}' must be within weeks 1-{}",
+            segment, MAX_WEEK_NUMBER
+        ));
+    }
+    Ok((start, end))
+}
+
+/// 将周次渲染为紧凑的表达式，如 `1-8,10,12-16`、`1-15单`
+///
+/// 输出可被 [`parse`] 还原。
+pub fn format(weeks: &[i32]) -> String {
+    let mut weeks = weeks.to_vec();
+    weeks.sort_unstable();
+    weeks.dedup();
+
+    let run_length = |from: usize, step: i32| {
+        let mut len = 1;
+        while from + len < weeks.len() && weeks[from + len] == weeks[from] + step * len as i32 {
+            len += 1;
+        }
+        len
+    };
+
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < weeks.len() {
+        let consecutive = run_length(i, 1);
+        // Weeks are deduplicated, so a step of 2 means the week in between is absent
+        let alternating = run_length(i, 2);
+
+        if alternating >= 3 && alternating > consecutive {
+            let last = weeks[i] + 2 * (alternating as i32 - 1);
+            let parity = if weeks[i] % 2 == 1 { "单" } else { "双" };
+            parts.push(format!("{}-{}{}", weeks[i], last, parity));
+            i += alternating;
+        } else if consecutive >= 2 {
+            parts.push(format!("{}-{}", weeks[i], weeks[i + consecutive - 1]));
+            i += consecutive;
+        } else {
+            parts.push(weeks[i].to_string());
+            i += 1;
+        }
+    }
+
+    parts.join(",")
+}
+
+/// 反序列化周次：接受整数数组或周次表达式字符串，`null` 表示每周
+///
+/// 用于 `#[serde(default, deserialize_with = "crate::weeks::deserialize")]`。
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<i32>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct WeeksVisitor;
+
+    impl<'de> Visitor<'de> for WeeksVisitor {
+        type Value = Option<Vec<i32>>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of week numbers or a week expression such as \"1-8,10,12-16\"")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            parse(value).map(Some).map_err(E::custom)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut weeks = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(week) = seq.next_element::<i32>()? {
+                weeks.push(week);
+            }
+            Ok(Some(weeks))
+        }
+    }
+
+    deserializer.deserialize_any(WeeksVisitor)
+}
+
+/// 同步数据中的周次原文：整数数组或周次表达式
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum RawWeeks {
+    Numbers(Vec<i32>),
+    Expression(String),
+}
+
+impl RawWeeks {
+    /// 解析为周次；表达式无法解析时返回原文，由校验拒绝该条目
+    pub fn parse(self) -> Result<Vec<i32>, String> {
+        match self {
+            RawWeeks::Numbers(weeks) => Ok(weeks),
+            RawWeeks::Expression(expr) => parse(&expr).map_err(|_| expr),
+        }
+    }
+}
+
+/// 修改请求中的周次：省略为 `None`（不变），`null` 为 `Some(None)`（改为每周）
+///
+/// 用于 `#[serde(default, deserialize_with = "crate::weeks::deserialize_update")]`。
+pub fn deserialize_update<'de, D>(deserializer: D) -> Result<Option<Option<Vec<i32>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ranges_and_parity() {
+        assert_eq!(
+            parse("1-8,10,12-16").unwrap(),
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 13, 14, 15, 16]
+        );
+        assert_eq!(
+            parse("single weeks 1-15").unwrap(),
+            vec![1, 3, 5, 7, 9, 11, 13, 15]
+        );
+        assert_eq!(parse("单周 1-7，9-11").unwrap(), vec![1, 3, 5, 7, 9, 11]);
+        assert_eq!(parse("第2-8周(双), 9").unwrap(), vec![2, 4, 6, 8, 9]);
+        assert_eq!(parse("3, 1, 3").unwrap(), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("8-1").is_err());
+        assert!(parse("1-x").is_err());
+        assert!(parse("odd even 1-4").is_err());
+        assert!(parse("双 1").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_out_of_range_weeks() {
+        assert_eq!(
+            parse("1-2000000000"),
+            Err("'1-2000000000' must be within weeks 1-52".to_string())
+        );
+        assert!(parse("0-3").is_err());
+        assert!(parse("53").is_err());
+        assert_eq!(parse("50-52").unwrap(), vec![50, 51, 52]);
+    }
+
+    #[test]
+    fn test_format_roundtrip() {
+        for expr in ["1-8,10,12-16", "1-15单", "2-16双,17", "5"] {
+            let weeks = parse(expr).unwrap();
+            assert_eq!(format(&weeks), expr);
+            assert_eq!(parse(&format(&weeks)).unwrap(), weeks);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_array_or_expression() {
+        #[derive(serde::Deserialize)]
+        struct Entry {
+            #[serde(default, deserialize_with = "deserialize")]
+            weeks: Option<Vec<i32>>,
+        }
+
+        let parse_entry = |json: &str| serde_json::from_str::<Entry>(json).map(|e| e.weeks);
+        assert_eq!(
+            parse_entry(r#"{"weeks": [1, 2]}"#).unwrap(),
+            Some(vec![1, 2])
+        );
+        assert_eq!(
+            parse_entry(r#"{"weeks": "1-3"}"#).unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(parse_entry(r#"{"weeks": null}"#).unwrap(), None);
+        assert_eq!(parse_entry("{}").unwrap(), None);
+        assert!(parse_entry(r#"{"weeks": "3-1"}"#).is_err());
+
+        #[derive(serde::Deserialize)]
+        struct Update {
+            #[serde(default, deserialize_with = "deserialize_update")]
+            weeks: Option<Option<Vec<i32>>>,
+        }
+
+        let parse_update = |json: &str| serde_json::from_str::<Update>(json).map(|u| u.weeks);
+        assert_eq!(parse_update("{}").unwrap(), None);
+        assert_eq!(parse_update(r#"{"weeks": null}"#).unwrap(), Some(None));
+        assert_eq!(
+            parse_update(r#"{"weeks": "1-2"}"#).unwrap(),
+            Some(Some(vec![1, 2]))
+        );
+    }
+}
@@ -27,6 +27,92 @@ async fn test_root_endpoint() {
     assert_eq!(body.message, "ClassTop Management Server");
 }
 
+#[cfg(test)]
+mod codec_tests {
+    use actix_web::{http::header, test, web, App, HttpRequest, HttpResponse};
+    use classtop_management_server::{codec, error::AppResult, models};
+    use std::io::Write;
+
+    // Echoes the decoded sync request back in the negotiated format
+    async fn echo_sync(req: HttpRequest, body: web::Bytes) -> AppResult<HttpResponse> {
+        let sync: models::SyncRequest = codec::decode_body(&req, &body)?;
+        codec::respond(
+            &req,
+            HttpResponse::Ok(),
+            &models::MessageResponse {
+                message: format!("{}:{}", sync.client_uuid, sync.courses.len()),
+            },
+        )
+    }
+
+    fn sync_payload() -> serde_json::Value {
+        serde_json::json!({
+            "client_uuid": "client-1",
+            "courses": [{"id": 1, "name": "数学", "attributes": {"credits": 3}}],
+            "schedule_entries": []
+        })
+    }
+
+    #[actix_web::test]
+    async fn test_gzip_json_request() {
+        let app = test::init_service(App::new().route("/sync", web::post().to(echo_sync))).await;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(sync_payload().to_string().as_bytes())
+            .unwrap();
+        let req = test::TestRequest::post()
+            .uri("/sync")
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .insert_header((header::CONTENT_ENCODING, "gzip"))
+            .set_payload(encoder.finish().unwrap())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert!(resp.status().is_success());
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["message"], "client-1:1");
+    }
+
+    #[actix_web::test]
+    async fn test_zstd_msgpack_request_with_cbor_response() {
+        let app = test::init_service(App::new().route("/sync", web::post().to(echo_sync))).await;
+
+        let msgpack = rmp_serde::to_vec_named(&sync_payload()).unwrap();
+        let req = test::TestRequest::post()
+            .uri("/sync")
+            .insert_header((header::CONTENT_TYPE, "application/msgpack"))
+            .insert_header((header::CONTENT_ENCODING, "zstd"))
+            .insert_header((header::ACCEPT, "application/cbor"))
+            .set_payload(zstd::encode_all(msgpack.as_slice(), 0).unwrap())
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/cbor"
+        );
+        let body = test::read_body(resp).await;
+        let decoded: serde_json::Value = ciborium::from_reader(body.as_ref()).unwrap();
+        assert_eq!(decoded["message"], "client-1:1");
+    }
+
+    #[actix_web::test]
+    async fn test_unsupported_content_type() {
+        let app = test::init_service(App::new().route("/sync", web::post().to(echo_sync))).await;
+
+        let req = test::TestRequest::post()
+            .uri("/sync")
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .set_payload("hello")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 415);
+    }
+}
+
 #[cfg(test)]
 mod model_tests {
     use chrono::NaiveDateTime;